@@ -237,6 +237,18 @@ fn impl_coproc_common(ast: &syn::DeriveInput) -> quote::Tokens {
                 (self.0 & 0b0000_0000_0000_0000_0000_0000_1110_0000) >> 5
             }
             #[inline(always)]
+            fn opcd_rr(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_0000_0000_0000_1111_0000) >> 4
+            }
+            #[inline(always)]
+            fn option(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_0000_0000_0000_1111_1111)
+            }
+            #[inline(always)]
+            fn n(&self) -> bool {
+                (self.0 & 0b0000_0000_0100_0000_0000_0000_0000_0000) != 0
+            }
+            #[inline(always)]
             fn crn(&self) -> u32 {
                 (self.0 & 0b0000_0000_0000_1111_0000_0000_0000_0000) >> 16
             }
@@ -253,6 +265,108 @@ fn impl_coproc_common(ast: &syn::DeriveInput) -> quote::Tokens {
 }
 
 
+#[proc_macro_derive(VfpBits)]
+pub fn derive_vfp_common(input: TokenStream) -> TokenStream {
+    return get_tokenstream!(input, impl_vfp_common);
+}
+fn impl_vfp_common(ast: &syn::DeriveInput) -> quote::Tokens {
+    let name = &ast.ident;
+    quote! {
+        impl VfpBits for #name {
+            #[inline(always)]
+            fn double(&self) -> bool {
+                (self.0 & 0b0000_0000_0000_0000_0000_0001_0000_0000) != 0
+            }
+            #[inline(always)]
+            fn vfp_opcd(&self) -> u32 {
+                ((self.0 & 0b0000_0000_1000_0000_0000_0000_0000_0000) >> 20) |
+                ((self.0 & 0b0000_0000_0011_0000_0000_0000_0000_0000) >> 19) |
+                ((self.0 & 0b0000_0000_0000_0000_0000_0000_0100_0000) >> 6)
+            }
+            #[inline(always)]
+            fn ext_opcd(&self) -> u32 {
+                ((self.0 & 0b0000_0000_0000_1111_0000_0000_0000_0000) >> 15) |
+                ((self.0 & 0b0000_0000_0000_0000_0000_0000_1000_0000) >> 7)
+            }
+            #[inline(always)]
+            fn sd(&self) -> u32 {
+                ((self.0 & 0b0000_0000_0000_0000_1111_0000_0000_0000) >> 11) |
+                ((self.0 & 0b0000_0000_0100_0000_0000_0000_0000_0000) >> 22)
+            }
+            #[inline(always)]
+            fn sn(&self) -> u32 {
+                ((self.0 & 0b0000_0000_0000_1111_0000_0000_0000_0000) >> 15) |
+                ((self.0 & 0b0000_0000_0000_0000_0000_0000_1000_0000) >> 7)
+            }
+            #[inline(always)]
+            fn sm(&self) -> u32 {
+                ((self.0 & 0b0000_0000_0000_0000_0000_0000_0000_1111) << 1) |
+                ((self.0 & 0b0000_0000_0000_0000_0000_0000_0010_0000) >> 5)
+            }
+            #[inline(always)]
+            fn dd(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_0000_1111_0000_0000_0000) >> 12
+            }
+            #[inline(always)]
+            fn dn(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_1111_0000_0000_0000_0000) >> 16
+            }
+            #[inline(always)]
+            fn dm(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_0000_0000_0000_0000_1111)
+            }
+        }
+    }
+}
+
+
+#[proc_macro_derive(FpaBits)]
+pub fn derive_fpa_common(input: TokenStream) -> TokenStream {
+    return get_tokenstream!(input, impl_fpa_common);
+}
+fn impl_fpa_common(ast: &syn::DeriveInput) -> quote::Tokens {
+    let name = &ast.ident;
+    quote! {
+        impl FpaBits for #name {
+            #[inline(always)]
+            fn fd(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_0000_0111_0000_0000_0000) >> 12
+            }
+            #[inline(always)]
+            fn fn_(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_0111_0000_0000_0000_0000) >> 16
+            }
+            #[inline(always)]
+            fn fm(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_0000_0000_0000_0000_0111)
+            }
+            #[inline(always)]
+            fn fm_imm(&self) -> bool {
+                (self.0 & 0b0000_0000_0000_0000_0000_0000_0000_1000) != 0
+            }
+            #[inline(always)]
+            fn monadic(&self) -> bool {
+                (self.0 & 0b0000_0000_0000_0000_1000_0000_0000_0000) != 0
+            }
+            #[inline(always)]
+            fn prec(&self) -> u32 {
+                ((self.0 & 0b0000_0000_0000_1000_0000_0000_0000_0000) >> 18) |
+                ((self.0 & 0b0000_0000_0000_0000_0000_0000_1000_0000) >> 7)
+            }
+            #[inline(always)]
+            fn ls_prec(&self) -> u32 {
+                ((self.0 & 0b0000_0000_0100_0000_0000_0000_0000_0000) >> 21) |
+                ((self.0 & 0b0000_0000_0000_0000_1000_0000_0000_0000) >> 15)
+            }
+            #[inline(always)]
+            fn rounding(&self) -> u32 {
+                (self.0 & 0b0000_0000_0000_0000_0000_0000_0110_0000) >> 5
+            }
+        }
+    }
+}
+
+
 #[proc_macro_derive(RegBits)]
 pub fn derive_reg_common(input: TokenStream) -> TokenStream {
     return get_tokenstream!(input, impl_reg_common);
@@ -359,10 +473,10 @@ fn impl_dp_fmt1(ast: &syn::DeriveInput) -> quote::Tokens {
             fn rn(&self) -> u16 { (self.0 & 0b0000_0000_0011_1000) >> 3 }
 
             #[inline(always)]
-            fn rm(&self) -> u16 { (self.0 & 0b0000_0001_1100_1000) >> 6 }
+            fn rm(&self) -> u16 { (self.0 & 0b0000_0001_1100_0000) >> 6 }
 
             #[inline(always)]
-            fn op1(&self) -> bool { (self.0 & 0b0000_0010_0000_1000) != 0 }
+            fn op1(&self) -> bool { (self.0 & 0b0000_0010_0000_0000) != 0 }
         }
     }
 }
@@ -382,10 +496,10 @@ fn impl_dp_fmt2(ast: &syn::DeriveInput) -> quote::Tokens {
             fn rn(&self) -> u16 { (self.0 & 0b0000_0000_0011_1000) >> 3 }
 
             #[inline(always)]
-            fn imm3(&self) -> u16 { (self.0 & 0b0000_0001_1100_1000) >> 6 }
+            fn imm3(&self) -> u16 { (self.0 & 0b0000_0001_1100_0000) >> 6 }
 
             #[inline(always)]
-            fn op2(&self) -> bool { (self.0 & 0b0000_0010_0000_1000) != 0 }
+            fn op2(&self) -> bool { (self.0 & 0b0000_0010_0000_0000) != 0 }
         }
     }
 }
@@ -488,7 +602,7 @@ fn impl_dp_fmt7(ast: &syn::DeriveInput) -> quote::Tokens {
     quote! {
         impl DpFmt7Bits for #name {
             #[inline(always)]
-            fn op6(&self) -> bool { (self.0 & 0b0000_0000_1000_0000) != 0 }
+            fn op6(&self) -> u16 { (self.0 & 0b0000_0000_1000_0000) >> 7 }
 
             #[inline(always)]
             fn imm7(&self) -> u16 { (self.0 & 0b0000_0000_0111_1111) }
@@ -544,7 +658,7 @@ fn impl_ls_reg_fmt1(ast: &syn::DeriveInput) -> quote::Tokens {
             fn imm5(&self) -> u16 {(self.0 & 0b0000_0111_1100_0000) >> 6 }
 
             #[inline(always)]
-            fn opcd1(&self) -> u16 {(self.0 & 0b1111_1000_1000_0000) >> 11 }
+            fn opcd1(&self) -> u16 {(self.0 & 0b1111_1000_0000_0000) >> 11 }
         }
     }
 }
@@ -669,6 +783,23 @@ fn impl_thumb_excep(ast: &syn::DeriveInput) -> quote::Tokens {
 }
 
 
+#[proc_macro_derive(ThumbCondBranchBits)]
+pub fn derive_thumb_cond_branch(input: TokenStream) -> TokenStream {
+    return get_tokenstream!(input, impl_thumb_cond_branch);
+}
+fn impl_thumb_cond_branch(ast: &syn::DeriveInput) -> quote::Tokens {
+    let name = &ast.ident;
+    quote! {
+        impl ThumbCondBranchBits for #name {
+            #[inline(always)]
+            fn simm8(&self) -> u16 { (self.0 & 0b0000_0000_1111_1111) }
+            #[inline(always)]
+            fn cond(&self) -> u16 { (self.0 & 0b0000_1111_0000_0000) >> 8 }
+        }
+    }
+}
+
+
 #[proc_macro_derive(ThumbUncondBranchBits)]
 pub fn derive_thumb_uncond_branch(input: TokenStream) -> TokenStream {
     return get_tokenstream!(input, impl_thumb_uncond_branch);
@@ -287,6 +287,18 @@ macro_rules! get_cp_opcd2 { ($val:expr) => {
     ($val & 0b0000_0000_0000_0000_0000_0000_1110_0000) >> 5
 }}
 
+/// Get the coprocessor opcode (bits 7-4, specific to MCRR/MRRC).
+#[macro_export]
+macro_rules! get_cp_opcd_rr { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0000_0000_0000_1111_0000) >> 4
+}}
+
+/// Get the option field (bits 7-0, specific to unindexed LDC/STC).
+#[macro_export]
+macro_rules! get_cp_option { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0000_0000_0000_1111_1111)
+}}
+
 /// Get the crN field (bits 19-16).
 #[macro_export]
 macro_rules! get_crn { ($val:expr) => { get_rn!($val) }}
@@ -396,3 +408,116 @@ macro_rules! get_reglist { ($val:expr) => {
 }}
 
 
+
+
+// ----------------------------------------------------------------------------
+// VFP bitfields
+//
+
+/// Get the precision bit (bit 8, set for double-precision instructions).
+#[macro_export]
+macro_rules! get_vfp_double { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0000_0000_0001_0000_0000) != 0
+}}
+
+/// Get the data-processing opcode (bits 23, 21-20 and 6, in that order).
+#[macro_export]
+macro_rules! get_vfp_opcd { ($val:expr) => {
+    (($val & 0b0000_0000_1000_0000_0000_0000_0000_0000) >> 20) |
+    (($val & 0b0000_0000_0011_0000_0000_0000_0000_0000) >> 19) |
+    (($val & 0b0000_0000_0000_0000_0000_0000_0100_0000) >> 6)
+}}
+
+/// Get the extension opcode (bits 19-16 and 7, in that order).
+#[macro_export]
+macro_rules! get_vfp_ext_opcd { ($val:expr) => {
+    (($val & 0b0000_0000_0000_1111_0000_0000_0000_0000) >> 15) |
+    (($val & 0b0000_0000_0000_0000_0000_0000_1000_0000) >> 7)
+}}
+
+/// Get the Sd register (bits 15-12 and 22, in that order).
+#[macro_export]
+macro_rules! get_vfp_sd { ($val:expr) => {
+    (($val & 0b0000_0000_0000_0000_1111_0000_0000_0000) >> 11) |
+    (($val & 0b0000_0000_0100_0000_0000_0000_0000_0000) >> 22)
+}}
+
+/// Get the Sn register (bits 19-16 and 7, in that order).
+#[macro_export]
+macro_rules! get_vfp_sn { ($val:expr) => { get_vfp_ext_opcd!($val) }}
+
+/// Get the Sm register (bits 3-0 and 5, in that order).
+#[macro_export]
+macro_rules! get_vfp_sm { ($val:expr) => {
+    (($val & 0b0000_0000_0000_0000_0000_0000_0000_1111) << 1) |
+    (($val & 0b0000_0000_0000_0000_0000_0000_0010_0000) >> 5)
+}}
+
+/// Get the Dd register (bits 15-12).
+#[macro_export]
+macro_rules! get_vfp_dd { ($val:expr) => { get_rd!($val) }}
+
+/// Get the Dn register (bits 19-16).
+#[macro_export]
+macro_rules! get_vfp_dn { ($val:expr) => { get_rn!($val) }}
+
+/// Get the Dm register (bits 3-0).
+#[macro_export]
+macro_rules! get_vfp_dm { ($val:expr) => { get_rm!($val) }}
+
+
+// ----------------------------------------------------------------------------
+// FPA bitfields
+//
+
+/// Get the Fd register (bits 14-12).
+#[macro_export]
+macro_rules! get_fpa_fd { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0000_0111_0000_0000_0000) >> 12
+}}
+
+/// Get the Fn register (bits 18-16).
+#[macro_export]
+macro_rules! get_fpa_fn { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0111_0000_0000_0000_0000) >> 16
+}}
+
+/// Get the Fm register (bits 2-0).
+#[macro_export]
+macro_rules! get_fpa_fm { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0000_0000_0000_0000_0111)
+}}
+
+/// Get the immediate bit (bit 3, set when Fm is a constant).
+#[macro_export]
+macro_rules! get_fpa_imm { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0000_0000_0000_0000_1000) != 0
+}}
+
+/// Get the monadic bit (bit 15, specific to data-processing instructions).
+#[macro_export]
+macro_rules! get_fpa_monadic { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0000_1000_0000_0000_0000) != 0
+}}
+
+/// Get the precision of a data-processing instruction (bits 19 and 7, in
+/// that order).
+#[macro_export]
+macro_rules! get_fpa_prec { ($val:expr) => {
+    (($val & 0b0000_0000_0000_1000_0000_0000_0000_0000) >> 18) |
+    (($val & 0b0000_0000_0000_0000_0000_0000_1000_0000) >> 7)
+}}
+
+/// Get the precision of a load/store, or the register count of LFM/SFM
+/// (bits 22 and 15, in that order).
+#[macro_export]
+macro_rules! get_fpa_ls_prec { ($val:expr) => {
+    (($val & 0b0000_0000_0100_0000_0000_0000_0000_0000) >> 21) |
+    (($val & 0b0000_0000_0000_0000_1000_0000_0000_0000) >> 15)
+}}
+
+/// Get the rounding mode (bits 6-5).
+#[macro_export]
+macro_rules! get_fpa_round { ($val:expr) => {
+    ($val & 0b0000_0000_0000_0000_0000_0000_0110_0000) >> 5
+}}
@@ -1,4 +1,9 @@
-//! Naive disassembler for flat binaries.
+//! Naive disassembler for flat binaries, ELF and PE files, and HEX/S-record
+//! images.
+//!
+//! Usage: `disas [--le | --be8 | --be32] [FILE]`. HEX and S-record files
+//! don't say what byte order they're in, so it's little-endian unless one
+//! of the options is given.
 
 #![allow(unused_variables)]
 #![allow(unused_imports)]
@@ -10,46 +15,48 @@ use rand::prelude::*;
 use std::time::Instant;
 use std::io::Read;
 
-use armbf::newtype::*;
-use armbf::inst::*;
-use armbf::lut::*;
-
-pub mod disas;
-
-use disas::{LutFunc, ThumbLutFunc, undef_instr, undef_instr_thumb};
-
-/// Convert a Vec<u8> into a Vec<u32> (in big-endian representation).
-pub fn make_u32_buf(src_buf: &Vec<u8>) -> Vec<u32> {
-    let mut dst_buf = Vec::<u32>::new();
-    for val in src_buf.chunks(4) {
-        let mut x = 0x0000_0000u32;
-        x |= (val[0] as u32) << 24;
-        x |= (val[1] as u32) << 16;
-        x |= (val[2] as u32) << 8;
-        x |= val[3] as u32;
-        dst_buf.push(x);
-    }
-    dst_buf
-}
-
-/// Convert a Vec<u8> to a Vec<16> (big-endian).
-pub fn make_u16_buf(src_buf: &Vec<u8>) -> Vec<u16> {
-    let mut dst_buf = Vec::<u16>::new();
-    for val in src_buf.chunks(2) {
-        let mut x = 0x0000u16;
-        x |= (val[0] as u16) << 8;
-        x |= val[1] as u16;
-        dst_buf.push(x);
-    }
-    dst_buf
-}
-
+use armbf::disas::{Disassembler, Isa};
+use armbf::elf::*;
+use armbf::image::*;
+use armbf::memory::*;
+use armbf::pe::*;
+use armbf::reloc::*;
+use armbf::stream::*;
+use armbf::sweep::*;
 
 const ARM_FILE: &'static str = "../../testsuite/arm_decode_test.bin";
 const THM_FILE: &'static str = "../../testsuite/thumb_decode_test.bin";
 
 /// Read a file with some code into some buffer, then disassemble it.
 fn main() {
+    let mut order = ByteOrder::Le;
+    let mut path = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--le" => order = ByteOrder::Le,
+            "--be8" => order = ByteOrder::Be8,
+            "--be32" => order = ByteOrder::Be32,
+            _ => path = Some(arg),
+        }
+    }
+
+    if let Some(path) = path {
+        let buf = std::fs::read(&path).unwrap();
+        if buf.starts_with(b"\x7fELF") {
+            disas_elf(&buf);
+        } else if buf.starts_with(b"MZ") {
+            disas_pe(&buf);
+        } else {
+            let img = Image::parse(&String::from_utf8_lossy(&buf)).unwrap();
+            let mem = MemoryImage::from_image(&img, order, Isa::Arm);
+            let mut d = Disassembler::new();
+            for line in mem.disassemble(&mut d) {
+                println!("{}", line);
+            }
+        }
+        return;
+    }
+
     let mut arm_buf = Vec::<u8>::new();
     let mut arm_file = std::fs::File::open(ARM_FILE).unwrap();
 
@@ -59,52 +66,68 @@ fn main() {
 
     arm_file.read_to_end(&mut arm_buf).unwrap();
     thumb_file.read_to_end(&mut thumb_buf).unwrap();
-    let mut arm_code = make_u32_buf(&arm_buf);
-
-    let mut thumb_code = make_u16_buf(&thumb_buf);
-
-    disas_arm(&arm_code);
-    disas_thumb(&thumb_code);
+    disas(&arm_buf, Isa::Arm);
+    disas(&thumb_buf, Isa::Thumb);
 }
 
 
-fn disas_arm(dbuf: &Vec<u32>) {
-    let mut offset = 0x0u32;
-    let mut lut = make_arm_lut::<LutFunc>(LutFunc(undef_instr));
-    println!("LUT is {:?}b", std::mem::size_of_val(&lut));
+/// Disassemble a buffer starting in some mode.
+fn disas(buf: &[u8], isa: Isa) {
+    let mut d = Disassembler::new();
 
     let start = Instant::now();
-    for val in dbuf.iter() {
-        let idx = (((val >> 16) & 0x0ff0) | ((val >> 4) & 0x000f)) as usize;
-        let disas_str = lut.data[idx].0(&val);
-        println!("{:04x}:\t {:08x} {:04x}\t {}", offset, val, 
-            ((val >> 16) & 0x0ff0) | ((val >> 4) & 0x000f), disas_str);
-        offset += 4;
-    }
+    let lines = LinearSweep::new(buf, 0, ByteOrder::Be32, isa)
+        .disassemble(&mut d);
     let dur = start.elapsed();
-    let mdips = ((1f64 / dur.as_secs_f64()) * dbuf.len() as f64) / 1_000_000f64;
-
+    for line in lines.iter() {
+        println!("{}", line);
+    }
+    let mdips = ((1f64 / dur.as_secs_f64()) * lines.len() as f64) / 1_000_000f64;
     println!("Disassembled {} instrs in {:?} (~{:.4}Mdips)", 
-        dbuf.len(), dur, mdips);
+        lines.len(), dur, mdips);
 }
 
-fn disas_thumb(dbuf: &Vec<u16>) {
-    let mut offset = 0x0u32;
-    let mut lut = make_thumb_lut::<ThumbLutFunc>(ThumbLutFunc(undef_instr_thumb));
-    println!("LUT is {:?}b", std::mem::size_of_val(&lut));
-
-    let start = Instant::now();
-    for val in dbuf.iter() {
-        let idx = (val >> 5) as usize;
-        let disas_str = lut.data[idx].0(&val);
-        println!("{:04x}:\t {:04x} {}", offset, val, disas_str);
+/// Disassemble the code sections in an ELF file, labelling functions.
+fn disas_elf(buf: &[u8]) {
+    let elf = Elf::parse(buf).unwrap();
+    let mut d = Disassembler::new();
+    for sec in elf.code_sections() {
+        println!("Disassembly of section {}:", sec.name);
+        let labels: Vec<(u32, &str)> = elf.labels(sec)
+            .map(|s| (elf.symbol_addr(s), s.name.as_str())).collect();
+        // Resolve calls in relocatable objects
+        let lines = if elf.kind == ET_REL {
+            Relocations::new(&elf, sec).unwrap().disassemble(&mut d)
+        } else {
+            elf.sweep(sec).disassemble(&mut d)
+        };
+        for line in lines {
+            for (_, name) in labels.iter().filter(|(a, _)| *a == line.addr) {
+                println!("\n{:08x} <{}>:", line.addr, name);
+            }
+            println!("{}", line);
+        }
     }
-    offset += 2;
-    let dur = start.elapsed();
-    let mdips = ((1f64 / dur.as_secs_f64()) * dbuf.len() as f64) / 1_000_000f64;
-    println!("Disassembled {} instrs in {:?} (~{:.4}Mdips)", 
-        dbuf.len(), dur, mdips);
-
 }
 
-
+/// Disassemble a WinCE PE image at its preferred base, labelling the entry
+/// point and exports.
+fn disas_pe(buf: &[u8]) {
+    let pe = Pe::parse(buf).unwrap();
+    let targets = pe.targets(pe.image_base).unwrap();
+    let mem = MemoryImage::from_pe(&pe, pe.image_base).unwrap();
+    let mut d = Disassembler::new();
+    for seg in mem.segments().iter().filter(|s| s.perms.contains(Perms::X) && !s.bytes.is_empty()) {
+        println!("Disassembly of section {}:", seg.name);
+        let mut sweep = LinearSweep::from_memory(&mem, seg.base).unwrap();
+        for target in targets.keys() {
+            sweep.add_target(*target);
+        }
+        for line in sweep.disassemble(&mut d) {
+            for (_, name) in targets.iter().filter(|(a, _)| *a & !1 == line.addr) {
+                println!("\n{:08x} <{}>:", line.addr, name);
+            }
+            println!("{}", line);
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Recursive-descent disassembly and control-flow graphs.
+//!
+//! Starting from a set of entry points, code is followed along direct
+//! branches (in the correct instruction set) instead of being swept in order.
+//! Each entry point and each direct call target becomes a function with its
+//! own [Cfg].
+//!
+//! Basic blocks are split at branch targets, after calls, and after
+//! conditional branches/returns. Targets computed at runtime are not
+//! followed; they appear as edges without a destination.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::disas::Isa;
+use crate::flow::*;
+use crate::memory::{MemoryImage, Unmapped};
+use crate::stream::*;
+
+/// The kind of an edge between basic blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Execution continues with the next block.
+    Fallthrough,
+    /// A direct branch was taken.
+    Taken,
+    /// A call to another function. Execution continues with the next block
+    /// (see the accompanying fallthrough edge).
+    Call,
+    /// A return from this function.
+    Return,
+    /// A branch to some address computed at runtime.
+    Indirect,
+}
+
+/// An edge from the end of some basic block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    /// Address of the block this edge leaves from.
+    pub from: u32,
+    /// Address of the destination, if it's known.
+    pub to: Option<u32>,
+    pub kind: EdgeKind,
+}
+
+/// A sequence of instructions with a single entry and a single exit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub start: u32,
+    /// Address after the last instruction.
+    pub end: u32,
+    pub isa: Isa,
+    /// Address of each instruction in this block.
+    pub insts: Vec<u32>,
+}
+
+/// The control-flow graph for a single function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cfg {
+    pub entry: u32,
+    pub isa: Isa,
+    pub blocks: BTreeMap<u32, BasicBlock>,
+    pub edges: Vec<Edge>,
+}
+impl Cfg {
+    /// Edges leaving some block.
+    pub fn successors(&self, block: u32) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |e| e.from == block)
+    }
+
+    /// Edges entering some block.
+    pub fn predecessors(&self, block: u32) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |e| e.to == Some(block)
+            && e.kind != EdgeKind::Call)
+    }
+
+    /// Targets of direct calls made from this function.
+    pub fn callees(&self) -> impl Iterator<Item = u32> + '_ {
+        self.edges.iter().filter(|e| e.kind == EdgeKind::Call)
+            .filter_map(|e| e.to)
+    }
+}
+
+
+/// A recursive-descent disassembler.
+pub struct RecursiveDescent<'a> {
+    buf: &'a [u8],
+    base: u32,
+    order: ByteOrder,
+    entries: Vec<(u32, Isa)>,
+}
+
+impl<'a> RecursiveDescent<'a> {
+    pub fn new(buf: &'a [u8], base: u32, order: ByteOrder) -> Self {
+        RecursiveDescent { buf, base, order, entries: Vec::new() }
+    }
+
+    /// Create a disassembler over the segment of a memory image containing
+    /// some address.
+    pub fn from_memory(mem: &'a MemoryImage, addr: u32) -> Result<Self, Unmapped> {
+        let (seg, buf) = mem.segment_contents(addr)?;
+        Ok(RecursiveDescent::new(buf, seg.base, seg.order))
+    }
+
+    /// Add an entry point.
+    pub fn add_entry(&mut self, addr: u32, isa: Isa) {
+        self.entries.push((addr, isa));
+    }
+
+    /// Add an interworking entry point, where bit 0 is set for Thumb code.
+    pub fn add_target(&mut self, target: u32) {
+        let isa = if (target & 1) != 0 { Isa::Thumb } else { Isa::Arm };
+        self.entries.push((target & !1, isa));
+    }
+
+    /// Returns the bytes from some address until the end of the buffer.
+    fn bytes_at(&self, addr: u32) -> Option<&'a [u8]> {
+        let off = addr.checked_sub(self.base)? as usize;
+        if off < self.buf.len() { Some(&self.buf[off..]) } else { None }
+    }
+
+    /// Decode the instruction at some address.
+    fn decode(&self, addr: u32, isa: Isa) -> Option<(Flow, u32)> {
+        match isa {
+            Isa::Arm if addr & 3 == 0 => {
+                let (_, x) = ArmStream::new(self.bytes_at(addr)?, addr,
+                    self.order).next()?.ok()?;
+                Some((arm_flow(addr, x), 4))
+            },
+            Isa::Thumb if addr & 1 == 0 => {
+                let mut s = ThumbStream::new(self.bytes_at(addr)?, addr,
+                    self.order);
+                let (_, x) = s.next()?.ok()?;
+                let next = s.next().and_then(|r| r.ok()).map(|(_, lo)| lo);
+                Some(thumb_flow(addr, x, next))
+            },
+            _ => None,
+        }
+    }
+
+    /// Follow code in a single function, collecting call targets.
+    fn function(&self, entry: u32, isa: Isa, calls: &mut Vec<(u32, Isa)>)
+        -> Cfg
+    {
+        let mut insts: BTreeMap<u32, (Flow, u32)> = BTreeMap::new();
+        let mut leaders = BTreeSet::new();
+        let mut work = vec![entry];
+        leaders.insert(entry);
+
+        while let Some(mut addr) = work.pop() {
+            while !insts.contains_key(&addr) {
+                let (flow, size) = match self.decode(addr, isa) {
+                    Some(x) => x,
+                    None => break,
+                };
+                insts.insert(addr, (flow, size));
+                let next = addr.wrapping_add(size);
+                match flow {
+                    Flow::Jump { target, cond } => {
+                        leaders.insert(target);
+                        work.push(target);
+                        if cond { leaders.insert(next); }
+                    },
+                    Flow::Call { target, isa, .. } => {
+                        calls.push((target, isa));
+                        leaders.insert(next);
+                    },
+                    Flow::IndirectCall { .. } => { leaders.insert(next); },
+                    Flow::Return { cond } |
+                    Flow::IndirectJump { cond } => {
+                        if cond { leaders.insert(next); }
+                    },
+                    Flow::Next | Flow::Stop => {},
+                }
+                if !flow.falls_through() {
+                    break;
+                }
+                addr = next;
+            }
+        }
+
+        // Blocks also start wherever the previous instruction doesn't fall
+        // into this one.
+        let mut prev_end = None;
+        for (addr, (flow, size)) in insts.iter() {
+            if prev_end != Some(*addr) {
+                leaders.insert(*addr);
+            }
+            prev_end = match flow {
+                Flow::Next => Some(addr.wrapping_add(*size)),
+                _ => None,
+            };
+        }
+
+        let mut blocks = BTreeMap::new();
+        let mut edges = Vec::new();
+        for start in leaders.iter().filter(|a| insts.contains_key(a)) {
+            let mut block = BasicBlock {
+                start: *start, end: *start, isa, insts: Vec::new(),
+            };
+            let mut last = Flow::Stop;
+            while let Some((flow, size)) = insts.get(&block.end) {
+                if block.end != *start && leaders.contains(&block.end) {
+                    break;
+                }
+                block.insts.push(block.end);
+                block.end = block.end.wrapping_add(*size);
+                last = *flow;
+                if *flow != Flow::Next {
+                    break;
+                }
+            }
+
+            let from = *start;
+            let mut edge = |to, kind| edges.push(Edge { from, to, kind });
+            match last {
+                Flow::Next | Flow::Stop => {},
+                Flow::Jump { target, .. } => edge(Some(target), EdgeKind::Taken),
+                Flow::Call { target, .. } => edge(Some(target), EdgeKind::Call),
+                Flow::IndirectCall { .. } => edge(None, EdgeKind::Call),
+                Flow::Return { .. } => edge(None, EdgeKind::Return),
+                Flow::IndirectJump { .. } => edge(None, EdgeKind::Indirect),
+            }
+            if last.falls_through() && insts.contains_key(&block.end) {
+                edge(Some(block.end), EdgeKind::Fallthrough);
+            }
+            blocks.insert(*start, block);
+        }
+        Cfg { entry, isa, blocks, edges }
+    }
+
+    /// Follow code from all entry points, returning a [Cfg] for each
+    /// function.
+    pub fn analyze(&self) -> BTreeMap<u32, Cfg> {
+        let mut res = BTreeMap::new();
+        let mut work: Vec<(u32, Isa)> = self.entries.iter().rev()
+            .cloned().collect();
+        while let Some((entry, isa)) = work.pop() {
+            if res.contains_key(&entry) || self.bytes_at(entry).is_none() {
+                continue;
+            }
+            let mut calls = Vec::new();
+            let cfg = self.function(entry, isa, &mut calls);
+            res.insert(entry, cfg);
+            work.extend(calls.into_iter().rev());
+        }
+        res
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn edge(from: u32, to: Option<u32>, kind: EdgeKind) -> Edge {
+        Edge { from, to, kind }
+    }
+
+    #[test]
+    fn calls_and_interworking() {
+        let words: [u32; 8] = [
+            0xe3500000, // 00: cmp  r0, #0
+            0x0a000001, // 04: beq  0x10
+            0xfa000002, // 08: blx  0x18
+            0xe12fff1e, // 0c: bx   lr
+            0xeb000002, // 10: bl   0x20
+            0xe8bd8010, // 14: pop  {r4, pc}
+            0x20014770, // 18: movs r0, #1; bx lr
+            0x00000000,
+        ];
+        let mut buf: Vec<u8> = words.iter()
+            .flat_map(|w| w.to_be_bytes().to_vec()).collect();
+        buf.extend_from_slice(&0xe1a0f00eu32.to_be_bytes()); // 20: mov pc, lr
+
+        let mut rd = RecursiveDescent::new(&buf, 0, ByteOrder::Be32);
+        rd.add_entry(0, Isa::Arm);
+        let funcs = rd.analyze();
+        assert_eq!(funcs.keys().cloned().collect::<Vec<u32>>(),
+            vec![0x00, 0x18, 0x20]);
+
+        let main = &funcs[&0];
+        assert_eq!(main.blocks.keys().cloned().collect::<Vec<u32>>(),
+            vec![0x00, 0x08, 0x0c, 0x10, 0x14]);
+        assert_eq!(main.blocks[&0].insts, vec![0x00, 0x04]);
+        assert_eq!(main.edges, vec![
+            edge(0x00, Some(0x10), EdgeKind::Taken),
+            edge(0x00, Some(0x08), EdgeKind::Fallthrough),
+            edge(0x08, Some(0x18), EdgeKind::Call),
+            edge(0x08, Some(0x0c), EdgeKind::Fallthrough),
+            edge(0x0c, None, EdgeKind::Return),
+            edge(0x10, Some(0x20), EdgeKind::Call),
+            edge(0x10, Some(0x14), EdgeKind::Fallthrough),
+            edge(0x14, None, EdgeKind::Return),
+        ]);
+        assert_eq!(main.callees().collect::<Vec<u32>>(), vec![0x18, 0x20]);
+
+        let thumb = &funcs[&0x18];
+        assert_eq!(thumb.isa, Isa::Thumb);
+        assert_eq!(thumb.blocks[&0x18].insts, vec![0x18, 0x1a]);
+        assert_eq!(thumb.edges, vec![edge(0x18, None, EdgeKind::Return)]);
+    }
+
+    #[test]
+    fn loop_splits_blocks() {
+        let buf = [
+            0x01, 0x38, // 0: subs r0, #1
+            0x00, 0x00, // 2: movs r0, r0
+            0xfd, 0xd1, // 4: bne  0x2
+            0x70, 0x47, // 6: bx   lr
+        ];
+        let mut rd = RecursiveDescent::new(&buf, 0x100, ByteOrder::Le);
+        rd.add_target(0x101);
+        let funcs = rd.analyze();
+        let cfg = &funcs[&0x100];
+        assert_eq!(cfg.blocks.keys().cloned().collect::<Vec<u32>>(),
+            vec![0x100, 0x102, 0x106]);
+        assert_eq!(cfg.blocks[&0x102].end, 0x106);
+        assert_eq!(cfg.successors(0x102).cloned().collect::<Vec<Edge>>(), vec![
+            edge(0x102, Some(0x102), EdgeKind::Taken),
+            edge(0x102, Some(0x106), EdgeKind::Fallthrough),
+        ]);
+        assert_eq!(cfg.predecessors(0x102).count(), 2);
+    }
+}
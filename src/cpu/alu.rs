@@ -0,0 +1,132 @@
+//! Condition codes, the barrel shifter and flag-setting arithmetic.
+
+use super::psr::*;
+
+/// Returns true if some condition passes for the flags in a PSR.
+pub fn cond_passed(cond: u32, psr: u32) -> bool {
+    let n = (psr & Psr::N) != 0;
+    let z = (psr & Psr::Z) != 0;
+    let c = (psr & Psr::C) != 0;
+    let v = (psr & Psr::V) != 0;
+    match cond {
+        0b0000 => z,
+        0b0001 => !z,
+        0b0010 => c,
+        0b0011 => !c,
+        0b0100 => n,
+        0b0101 => !n,
+        0b0110 => v,
+        0b0111 => !v,
+        0b1000 => c && !z,
+        0b1001 => !c || z,
+        0b1010 => n == v,
+        0b1011 => n != v,
+        0b1100 => !z && n == v,
+        0b1101 => z || n != v,
+        _ => true,
+    }
+}
+
+/// Shift by an immediate, returning the result and the shifter carry-out.
+///
+/// An amount of zero encodes LSR #32, ASR #32 and RRX.
+pub fn shift_imm(val: u32, shift: u32, amt: u32, c: bool) -> (u32, bool) {
+    match (shift, amt) {
+        (0, 0) => (val, c),
+        (1, 0) | (2, 0) => shift_reg(val, shift, 32, c),
+        (3, 0) => (((c as u32) << 31) | (val >> 1), (val & 1) != 0),
+        _ => shift_reg(val, shift, amt, c),
+    }
+}
+
+/// Shift by a register (only the bottom byte of which is used), returning
+/// the result and the shifter carry-out.
+pub fn shift_reg(val: u32, shift: u32, amt: u32, c: bool) -> (u32, bool) {
+    let amt = amt & 0xff;
+    if amt == 0 {
+        return (val, c);
+    }
+    match shift {
+        // LSL
+        0 => match amt {
+            1..=31 => (val << amt, (val >> (32 - amt)) & 1 != 0),
+            32 => (0, val & 1 != 0),
+            _ => (0, false),
+        },
+        // LSR
+        1 => match amt {
+            1..=31 => (val >> amt, (val >> (amt - 1)) & 1 != 0),
+            32 => (0, (val >> 31) != 0),
+            _ => (0, false),
+        },
+        // ASR
+        2 => match amt {
+            1..=31 => (((val as i32) >> amt) as u32, (val >> (amt - 1)) & 1 != 0),
+            _ => (((val as i32) >> 31) as u32, (val >> 31) != 0),
+        },
+        // ROR
+        _ => {
+            let res = val.rotate_right(amt & 31);
+            (res, (res >> 31) != 0)
+        },
+    }
+}
+
+/// Add with carry, returning the result, carry-out and overflow.
+pub fn add_with_carry(a: u32, b: u32, cin: bool) -> (u32, bool, bool) {
+    let wide = a as u64 + b as u64 + cin as u64;
+    let res = wide as u32;
+    let v = ((a ^ res) & (b ^ res)) >> 31 != 0;
+    (res, wide > u32::MAX as u64, v)
+}
+
+/// Signed saturating add, returning the result and whether it saturated.
+pub fn sat_add(a: u32, b: u32) -> (u32, bool) {
+    match (a as i32).checked_add(b as i32) {
+        Some(res) => (res as u32, false),
+        None => ((a as i32).saturating_add(b as i32) as u32, true),
+    }
+}
+
+/// Signed saturating subtract, returning the result and whether it saturated.
+pub fn sat_sub(a: u32, b: u32) -> (u32, bool) {
+    match (a as i32).checked_sub(b as i32) {
+        Some(res) => (res as u32, false),
+        None => ((a as i32).saturating_sub(b as i32) as u32, true),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shifter() {
+        assert_eq!(shift_imm(0x8000_0001, 0, 0, true), (0x8000_0001, true));
+        assert_eq!(shift_imm(0x8000_0001, 0, 1, false), (2, true));
+        assert_eq!(shift_imm(0x8000_0001, 1, 0, false), (0, true));
+        assert_eq!(shift_imm(0x8000_0001, 2, 0, false), (0xffff_ffff, true));
+        assert_eq!(shift_imm(0x8000_0001, 3, 0, false), (0x4000_0000, true));
+        assert_eq!(shift_imm(0x0000_00f0, 3, 4, true), (0x0000_000f, false));
+        assert_eq!(shift_reg(1, 0, 32, false), (0, true));
+        assert_eq!(shift_reg(1, 0, 33, true), (0, false));
+        assert_eq!(shift_reg(0x8000_0000, 1, 32, false), (0, true));
+        assert_eq!(shift_reg(0x8000_0000, 2, 200, false), (0xffff_ffff, true));
+        assert_eq!(shift_reg(0x8000_0000, 3, 32, false), (0x8000_0000, true));
+        assert_eq!(shift_reg(5, 3, 0x100, true), (5, true));
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(add_with_carry(0xffff_ffff, 1, false), (0, true, false));
+        assert_eq!(add_with_carry(0x7fff_ffff, 0, true), (0x8000_0000, false, true));
+        // 1 - 2, as 1 + !2 + 1
+        assert_eq!(add_with_carry(1, !2, true), (0xffff_ffff, false, false));
+        assert_eq!(sat_add(0x7fff_ffff, 1), (0x7fff_ffff, true));
+        assert_eq!(sat_sub(0x8000_0000, 1), (0x8000_0000, true));
+        assert_eq!(sat_sub(5, 7), (0xffff_fffe, false));
+        assert!(cond_passed(0b1100, 0) && !cond_passed(0b1100, Psr::N));
+        assert!(cond_passed(0b1000, Psr::C) && !cond_passed(0b1000, Psr::C | Psr::Z));
+    }
+}
@@ -0,0 +1,772 @@
+//! Execution handlers for ARM instructions.
+
+use armbf_prim::*;
+
+use crate::newtype::*;
+use crate::sysreg::SysReg;
+use crate::traits::*;
+
+use super::*;
+use super::alu::*;
+
+/// Iterate over the registers in a register list, from lowest to highest.
+pub(crate) fn reglist(list: u32) -> impl Iterator<Item = u32> {
+    (0..16).filter(move |idx| (list & (1 << idx)) != 0)
+}
+
+/// Sign-extend the 24-bit branch offset and convert it to bytes.
+fn branch_offset(imm24: u32) -> u32 {
+    (((imm24 << 8) as i32) >> 6) as u32
+}
+
+
+/// Execute a data-processing operation, given the first operand and the
+/// output of the shifter.
+pub(crate) fn dp<B: Bus>(cpu: &mut Cpu<B>, opcd: u32, s: bool, rd: u32,
+    a: u32, (b, sc): (u32, bool)) -> Exec
+{
+    let c = cpu.regs.flag(Psr::C);
+    let (res, c, v) = match opcd {
+        0b0000 | 0b1000 => (a & b, sc, None),
+        0b0001 | 0b1001 => (a ^ b, sc, None),
+        0b1100 => (a | b, sc, None),
+        0b1101 => (b, sc, None),
+        0b1110 => (a & !b, sc, None),
+        0b1111 => (!b, sc, None),
+        _ => {
+            let (res, c, v) = match opcd {
+                0b0010 | 0b1010 => add_with_carry(a, !b, true),
+                0b0011 => add_with_carry(b, !a, true),
+                0b0100 | 0b1011 => add_with_carry(a, b, false),
+                0b0101 => add_with_carry(a, b, c),
+                0b0110 => add_with_carry(a, !b, c),
+                _ => add_with_carry(b, !a, c),
+            };
+            (res, c, Some(v))
+        },
+    };
+
+    let compare = (opcd & 0b1100) == 0b1000;
+    if s && rd == 15 && !compare {
+        // Return from an exception
+        cpu.restore_spsr();
+        cpu.branch(res);
+        return Ok(());
+    }
+    if s {
+        cpu.regs.set_nz(res);
+        cpu.regs.set_flag(Psr::C, c);
+        if let Some(v) = v {
+            cpu.regs.set_flag(Psr::V, v);
+        }
+    }
+    if !compare {
+        cpu.set_reg(rd, res);
+    }
+    Ok(())
+}
+
+pub fn dp_rot_imm<B: Bus>(cpu: &mut Cpu<B>, op: &DpRotImmBf) -> Exec {
+    let imm = op.imm8().rotate_right(op.rot_imm() * 2);
+    let sc = if op.rot_imm() == 0 { cpu.regs.flag(Psr::C) } else { (imm >> 31) != 0 };
+    dp(cpu, op.opcd(), op.s(), op.rd(), cpu.reg(op.rn()), (imm, sc))
+}
+
+pub fn dp_shift_imm<B: Bus>(cpu: &mut Cpu<B>, op: &DpShiftBf) -> Exec {
+    let b = shift_imm(cpu.reg(op.rm()), op.shift(), op.shift_imm(),
+        cpu.regs.flag(Psr::C));
+    dp(cpu, op.opcd(), op.s(), op.rd(), cpu.reg(op.rn()), b)
+}
+
+pub fn dp_shift_reg<B: Bus>(cpu: &mut Cpu<B>, op: &DpShiftBf) -> Exec {
+    // The PC reads one instruction further ahead when a register specifies
+    // the shift amount.
+    let reg = |idx| if idx == 15 { cpu.reg(15).wrapping_add(4) } else { cpu.reg(idx) };
+    let b = shift_reg(reg(op.rm()), op.shift(), cpu.reg(op.rs()),
+        cpu.regs.flag(Psr::C));
+    dp(cpu, op.opcd(), op.s(), op.rd(), reg(op.rn()), b)
+}
+
+
+/// Write the result of a 32-bit multiply.
+fn mul_result<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf, res: u32) -> Exec {
+    if get_s!(op.0) {
+        cpu.regs.set_nz(res);
+    }
+    cpu.set_reg(op.rd_hi(), res);
+    Ok(())
+}
+
+/// Write the result of a 64-bit multiply.
+fn mul_long_result<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf, res: u64) -> Exec {
+    if get_s!(op.0) {
+        cpu.regs.set_flag(Psr::N, (res as i64) < 0);
+        cpu.regs.set_flag(Psr::Z, res == 0);
+    }
+    cpu.set_reg(op.rd_lo(), res as u32);
+    cpu.set_reg(op.rd_hi(), (res >> 32) as u32);
+    Ok(())
+}
+
+/// The 64-bit accumulator held in RdHi:RdLo.
+fn acc64<B: Bus>(cpu: &Cpu<B>, op: &MulBf) -> u64 {
+    ((cpu.reg(op.rd_hi()) as u64) << 32) | cpu.reg(op.rd_lo()) as u64
+}
+
+/// Select the top or bottom signed halfword of a register.
+fn half(val: u32, top: bool) -> i32 {
+    if top { (val as i32) >> 16 } else { val as i16 as i32 }
+}
+
+pub fn mul<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let res = cpu.reg(op.rm()).wrapping_mul(cpu.reg(op.rs()));
+    mul_result(cpu, op, res)
+}
+pub fn mla<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let res = cpu.reg(op.rm()).wrapping_mul(cpu.reg(op.rs()))
+        .wrapping_add(cpu.reg(op.rd_lo()));
+    mul_result(cpu, op, res)
+}
+pub fn umull<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let res = cpu.reg(op.rm()) as u64 * cpu.reg(op.rs()) as u64;
+    mul_long_result(cpu, op, res)
+}
+pub fn umlal<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let res = (cpu.reg(op.rm()) as u64 * cpu.reg(op.rs()) as u64)
+        .wrapping_add(acc64(cpu, op));
+    mul_long_result(cpu, op, res)
+}
+pub fn smull<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let res = cpu.reg(op.rm()) as i32 as i64 * cpu.reg(op.rs()) as i32 as i64;
+    mul_long_result(cpu, op, res as u64)
+}
+pub fn smlal<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let res = cpu.reg(op.rm()) as i32 as i64 * cpu.reg(op.rs()) as i32 as i64;
+    mul_long_result(cpu, op, (res as u64).wrapping_add(acc64(cpu, op)))
+}
+
+pub fn smla_xy<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let prod = half(cpu.reg(op.rm()), op.x()) * half(cpu.reg(op.rs()), op.y());
+    let (res, _, q) = add_with_carry(prod as u32, cpu.reg(op.rd_lo()), false);
+    if q {
+        cpu.regs.set_flag(Psr::Q, true);
+    }
+    cpu.set_reg(op.rd_hi(), res);
+    Ok(())
+}
+pub fn smul_xy<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let prod = half(cpu.reg(op.rm()), op.x()) * half(cpu.reg(op.rs()), op.y());
+    cpu.set_reg(op.rd_hi(), prod as u32);
+    Ok(())
+}
+pub fn smlal_xy<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let prod = half(cpu.reg(op.rm()), op.x()) * half(cpu.reg(op.rs()), op.y());
+    let res = acc64(cpu, op).wrapping_add(prod as i64 as u64);
+    cpu.set_reg(op.rd_lo(), res as u32);
+    cpu.set_reg(op.rd_hi(), (res >> 32) as u32);
+    Ok(())
+}
+pub fn smulw_y<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let prod = cpu.reg(op.rm()) as i32 as i64 * half(cpu.reg(op.rs()), op.y()) as i64;
+    cpu.set_reg(op.rd_hi(), (prod >> 16) as u32);
+    Ok(())
+}
+pub fn smlaw_y<B: Bus>(cpu: &mut Cpu<B>, op: &MulBf) -> Exec {
+    let prod = cpu.reg(op.rm()) as i32 as i64 * half(cpu.reg(op.rs()), op.y()) as i64;
+    let (res, _, q) = add_with_carry((prod >> 16) as u32, cpu.reg(op.rd_lo()), false);
+    if q {
+        cpu.regs.set_flag(Psr::Q, true);
+    }
+    cpu.set_reg(op.rd_hi(), res);
+    Ok(())
+}
+
+
+/// Execute a saturating add/subtract, where `double` saturates twice the
+/// value of Rn first.
+fn sat<B: Bus>(cpu: &mut Cpu<B>, op: &SatBf, sub: bool, double: bool) -> Exec {
+    let mut rn = cpu.reg(op.rn());
+    let mut q = false;
+    if double {
+        let (res, sq) = sat_add(rn, rn);
+        rn = res;
+        q |= sq;
+    }
+    let (res, sq) = if sub {
+        sat_sub(cpu.reg(op.rm()), rn)
+    } else {
+        sat_add(cpu.reg(op.rm()), rn)
+    };
+    if q || sq {
+        cpu.regs.set_flag(Psr::Q, true);
+    }
+    cpu.set_reg(op.rd(), res);
+    Ok(())
+}
+pub fn qadd<B: Bus>(cpu: &mut Cpu<B>, op: &SatBf) -> Exec { sat(cpu, op, false, false) }
+pub fn qsub<B: Bus>(cpu: &mut Cpu<B>, op: &SatBf) -> Exec { sat(cpu, op, true, false) }
+pub fn qdadd<B: Bus>(cpu: &mut Cpu<B>, op: &SatBf) -> Exec { sat(cpu, op, false, true) }
+pub fn qdsub<B: Bus>(cpu: &mut Cpu<B>, op: &SatBf) -> Exec { sat(cpu, op, true, true) }
+
+pub fn clz<B: Bus>(cpu: &mut Cpu<B>, op: &ClzBf) -> Exec {
+    cpu.set_reg(op.rd(), cpu.reg(op.rm()).leading_zeros());
+    Ok(())
+}
+
+
+pub fn mrs<B: Bus>(cpu: &mut Cpu<B>, op: &StatusBf) -> Exec {
+    let val = if op.r() {
+        cpu.regs.spsr().unwrap_or_else(|| cpu.regs.cpsr())
+    } else {
+        cpu.regs.cpsr()
+    };
+    cpu.set_reg(op.rd(), val);
+    Ok(())
+}
+
+/// Write the fields of a PSR selected by the field mask.
+fn msr<B: Bus>(cpu: &mut Cpu<B>, op: &StatusBf, val: u32) -> Exec {
+    if op.r() {
+        if let Some(spsr) = cpu.regs.spsr() {
+            cpu.regs.set_spsr(Psr(spsr).msr_spsr(val, op.field_mask()).0);
+        }
+    } else {
+        cpu.regs.set_psr(cpu.regs.psr().msr_cpsr(val, op.field_mask()));
+    }
+    Ok(())
+}
+pub fn msr_imm<B: Bus>(cpu: &mut Cpu<B>, op: &StatusBf) -> Exec {
+    msr(cpu, op, op.imm8().rotate_right(op.rot_imm() * 2))
+}
+pub fn msr_reg<B: Bus>(cpu: &mut Cpu<B>, op: &StatusBf) -> Exec {
+    msr(cpu, op, cpu.reg(op.rm()))
+}
+
+
+/// Compute `(address, writeback value)` for a load/store with some offset.
+fn ls_addr<B: Bus, T: LsBits + RegBits>(cpu: &Cpu<B>, op: &T, offset: u32)
+    -> (u32, u32)
+{
+    let base = cpu.reg(op.rn());
+    let offset_addr = if op.u() {
+        base.wrapping_add(offset)
+    } else {
+        base.wrapping_sub(offset)
+    };
+    (if op.p() { offset_addr } else { base }, offset_addr)
+}
+
+/// Write back the base register for a load/store, if necessary.
+fn ls_writeback<B: Bus, T: LsBits + RegBits>(cpu: &mut Cpu<B>, op: &T, wb: u32) {
+    if !op.p() || op.w() {
+        cpu.set_reg(op.rn(), wb);
+    }
+}
+
+/// Execute a word or unsigned byte load/store.
+fn ls<B: Bus, T: LsBits + RegBits>(cpu: &mut Cpu<B>, op: &T, offset: u32) -> Exec {
+    let (addr, wb) = ls_addr(cpu, op, offset);
+    // Post-indexed with W set: ldrt/strt, which access memory as user mode
+    if !op.p() && op.w() {
+        return cpu.as_user(|cpu| ls_access(cpu, op, addr, wb));
+    }
+    ls_access(cpu, op, addr, wb)
+}
+fn ls_access<B: Bus, T: LsBits + RegBits>(cpu: &mut Cpu<B>, op: &T, addr: u32, wb: u32)
+    -> Exec
+{
+    if op.l() {
+        let val = if op.b() { cpu.load8(addr)? as u32 } else { cpu.load32(addr)? };
+        ls_writeback(cpu, op, wb);
+        if op.rd() == 15 {
+            cpu.branch_exchange(val);
+        } else {
+            cpu.set_reg(op.rd(), val);
+        }
+    } else {
+        let val = cpu.reg(op.rd());
+        if op.b() {
+            cpu.store8(addr, val as u8)?;
+        } else {
+            cpu.store32(addr, val)?;
+        }
+        ls_writeback(cpu, op, wb);
+    }
+    Ok(())
+}
+pub fn ls_imm<B: Bus>(cpu: &mut Cpu<B>, op: &LsImmBf) -> Exec {
+    ls(cpu, op, op.imm12())
+}
+pub fn ls_reg<B: Bus>(cpu: &mut Cpu<B>, op: &LsShiftBf) -> Exec {
+    let (offset, _) = shift_imm(cpu.reg(op.rm()), op.shift(), op.shift_imm(),
+        cpu.regs.flag(Psr::C));
+    ls(cpu, op, offset)
+}
+
+/// Execute a halfword, signed byte or doubleword load/store.
+pub fn ls_misc<B: Bus>(cpu: &mut Cpu<B>, op: &LsMiscBf) -> Exec {
+    let offset = if op.b() {
+        (op.off_hi() << 4) | op.off_lo()
+    } else {
+        cpu.reg(op.rm())
+    };
+    let (addr, wb) = ls_addr(cpu, op, offset);
+    let rd = op.rd();
+    match (op.l(), get_lsmisc_op1!(op.0)) {
+        (true, op1) => {
+            let val = match op1 {
+                0b01 => cpu.load16(addr)? as u32,
+                0b10 => cpu.load8(addr)? as i8 as u32,
+                _ => cpu.load16(addr)? as i16 as u32,
+            };
+            ls_writeback(cpu, op, wb);
+            cpu.set_reg(rd, val);
+        },
+        (false, 0b01) => {
+            cpu.store16(addr, cpu.reg(rd) as u16)?;
+            ls_writeback(cpu, op, wb);
+        },
+        // LDRD
+        (false, 0b10) => {
+            let lo = cpu.read32(addr)?;
+            let hi = cpu.read32(addr.wrapping_add(4))?;
+            ls_writeback(cpu, op, wb);
+            cpu.set_reg(rd, lo);
+            cpu.set_reg(rd | 1, hi);
+        },
+        // STRD
+        _ => {
+            cpu.store32(addr, cpu.reg(rd))?;
+            cpu.store32(addr.wrapping_add(4), cpu.reg(rd | 1))?;
+            ls_writeback(cpu, op, wb);
+        },
+    }
+    Ok(())
+}
+
+
+/// Execute a load/store multiple.
+fn ls_multi<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf, inc: bool, before: bool)
+    -> Exec
+{
+    let list = op.reglist();
+    let size = list.count_ones() * 4;
+    let base = cpu.reg(op.rn());
+    let (start, wb) = match (inc, before) {
+        (true, false) => (base, base.wrapping_add(size)),
+        (true, true) => (base.wrapping_add(4), base.wrapping_add(size)),
+        (false, false) => (base.wrapping_sub(size).wrapping_add(4), base.wrapping_sub(size)),
+        (false, true) => (base.wrapping_sub(size), base.wrapping_sub(size)),
+    };
+    // With the S bit set, the user bank is transferred unless this is a
+    // load including the PC (which returns from an exception instead).
+    let pc = (list & (1 << 15)) != 0;
+    let user = op.s() && !(op.l() && pc);
+
+    if op.l() {
+        let mut vals = [0; 16];
+        for (i, idx) in reglist(list).enumerate() {
+            vals[idx as usize] = cpu.read32(start.wrapping_add(i as u32 * 4))?;
+        }
+        if op.w() {
+            cpu.set_reg(op.rn(), wb);
+        }
+        for idx in reglist(list & 0x7fff) {
+            if user {
+                cpu.regs.set_user(idx, vals[idx as usize]);
+            } else {
+                cpu.set_reg(idx, vals[idx as usize]);
+            }
+        }
+        if pc && op.s() {
+            cpu.restore_spsr();
+            cpu.branch(vals[15]);
+        } else if pc {
+            cpu.branch_exchange(vals[15]);
+        }
+    } else {
+        for (i, idx) in reglist(list).enumerate() {
+            let val = if user && idx != 15 { cpu.regs.get_user(idx) } else { cpu.reg(idx) };
+            cpu.store32(start.wrapping_add(i as u32 * 4), val)?;
+        }
+        if op.w() {
+            cpu.set_reg(op.rn(), wb);
+        }
+    }
+    Ok(())
+}
+pub fn ldmia<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf) -> Exec { ls_multi(cpu, op, true, false) }
+pub fn ldmib<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf) -> Exec { ls_multi(cpu, op, true, true) }
+pub fn ldmda<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf) -> Exec { ls_multi(cpu, op, false, false) }
+pub fn ldmdb<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf) -> Exec { ls_multi(cpu, op, false, true) }
+pub fn stmia<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf) -> Exec { ls_multi(cpu, op, true, false) }
+pub fn stmib<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf) -> Exec { ls_multi(cpu, op, true, true) }
+pub fn stmda<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf) -> Exec { ls_multi(cpu, op, false, false) }
+pub fn stmdb<B: Bus>(cpu: &mut Cpu<B>, op: &LsMultiBf) -> Exec { ls_multi(cpu, op, false, true) }
+
+
+pub fn swp<B: Bus>(cpu: &mut Cpu<B>, op: &SwpBf) -> Exec {
+    let addr = cpu.reg(op.rn());
+    let val = cpu.load32(addr)?;
+    cpu.store32(addr, cpu.reg(op.rm()))?;
+    cpu.set_reg(op.rd(), val);
+    Ok(())
+}
+pub fn swpb<B: Bus>(cpu: &mut Cpu<B>, op: &SwpBf) -> Exec {
+    let addr = cpu.reg(op.rn());
+    let val = cpu.load8(addr)?;
+    cpu.store8(addr, cpu.reg(op.rm()) as u8)?;
+    cpu.set_reg(op.rd(), val as u32);
+    Ok(())
+}
+
+
+pub fn b<B: Bus>(cpu: &mut Cpu<B>, op: &BranchBf) -> Exec {
+    cpu.branch(cpu.reg(15).wrapping_add(branch_offset(op.imm24())));
+    Ok(())
+}
+pub fn bl<B: Bus>(cpu: &mut Cpu<B>, op: &BranchBf) -> Exec {
+    cpu.set_reg(14, cpu.regs.pc().wrapping_add(4));
+    b(cpu, op)
+}
+pub fn blx_imm<B: Bus>(cpu: &mut Cpu<B>, op: &BranchBf) -> Exec {
+    // The H bit selects a halfword-aligned Thumb target
+    let h = (op.link() as u32) << 1;
+    let target = cpu.reg(15).wrapping_add(branch_offset(op.imm24())) | h;
+    cpu.set_reg(14, cpu.regs.pc().wrapping_add(4));
+    cpu.branch_exchange(target | 1);
+    Ok(())
+}
+pub fn bx<B: Bus>(cpu: &mut Cpu<B>, op: &BxBf) -> Exec {
+    cpu.branch_exchange(cpu.reg(op.rm()));
+    Ok(())
+}
+pub fn blx_reg<B: Bus>(cpu: &mut Cpu<B>, op: &BxBf) -> Exec {
+    let target = cpu.reg(op.rm());
+    cpu.set_reg(14, cpu.regs.pc().wrapping_add(4));
+    cpu.branch_exchange(target);
+    Ok(())
+}
+
+
+pub fn swi<B: Bus>(_cpu: &mut Cpu<B>, op: &SwiBf) -> Exec {
+    Err(Trap::Swi(op.imm24()))
+}
+pub fn bkpt<B: Bus>(_cpu: &mut Cpu<B>, op: &BkptBf) -> Exec {
+    Err(Trap::Bkpt((op.imm12_hi() << 4) | op.imm4()))
+}
+pub fn coproc<B: Bus>(cpu: &mut Cpu<B>, op: &CoprocBf) -> Exec {
+    if VfpInst::is_vfp(op.0) {
+        return vfp::execute(cpu, &VfpBf(op.0));
+    }
+    Err(Trap::Undefined(op.0))
+}
+
+/// The CP15 register named by an `mcr`/`mrc`, if it's accessible.
+fn cp15_reg<B: Bus>(cpu: &Cpu<B>, op: &CoprocBf) -> Result<SysReg, Trap> {
+    if op.cp_num() != 15 || !cpu.regs.privileged() {
+        return Err(Trap::Undefined(op.0));
+    }
+    Ok(SysReg::from_inst(op))
+}
+pub fn mcr<B: Bus>(cpu: &mut Cpu<B>, op: &CoprocBf) -> Exec {
+    if VfpInst::is_vfp(op.0) {
+        return vfp::execute(cpu, &VfpBf(op.0));
+    }
+    let reg = cp15_reg(cpu, op)?;
+    let maint = cpu.cp15.write(reg, cpu.reg(op.rd())).map_err(|_| Trap::Undefined(op.0))?;
+    if maint.is_some() {
+        cpu.last_maint = maint;
+    }
+    Ok(())
+}
+pub fn mrc<B: Bus>(cpu: &mut Cpu<B>, op: &CoprocBf) -> Exec {
+    if VfpInst::is_vfp(op.0) {
+        return vfp::execute(cpu, &VfpBf(op.0));
+    }
+    let reg = cp15_reg(cpu, op)?;
+    let val = cpu.cp15.read(reg).map_err(|_| Trap::Undefined(op.0))?;
+    if op.rd() == 15 {
+        // Only the flags are written
+        let cpsr = cpu.regs.cpsr();
+        cpu.regs.set_cpsr((cpsr & 0x0fff_ffff) | (val & 0xf000_0000));
+    } else {
+        cpu.set_reg(op.rd(), val);
+    }
+    Ok(())
+}
+pub fn undef<B: Bus>(_cpu: &mut Cpu<B>, x: u32) -> Exec {
+    Err(Trap::Undefined(x))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::test::exec;
+
+    #[test]
+    fn data_processing() {
+        let interp = exec(&[(6, 4), (8, 1)], &[
+            0xe3b00102, // movs  r0, #0x80000000
+            0xe0901000, // adds  r1, r0, r0
+            0xe2b12005, // adcs  r2, r1, #5
+            0xe2723000, // rsbs  r3, r2, #0
+            0xe1a04063, // rrx   r4, r3
+            0xe1a05650, // asr   r5, r0, r6
+            0xe02f6080, // eor   r6, pc, r0, lsl #1
+            0xe08f7810, // add   r7, pc, r0, lsl r8
+            0xe1b08084, // lsls  r8, r4, #1
+        ]);
+        let regs = &interp.cpu.regs;
+        let r: Vec<u32> = (0..9).map(|i| regs.get(i)).collect();
+        assert_eq!(r, vec![
+            0x8000_0000, 0, 6, 0xffff_fffa, 0x7fff_fffd, 0xf800_0000,
+            0x20, 0x28, 0xffff_fffa,
+        ]);
+        assert_eq!(regs.cpsr() & 0xf000_0000, Psr::N);
+    }
+
+    #[test]
+    fn multiplies() {
+        let interp = exec(&[(1, 3), (2, -2i32 as u32), (3, 5), (4, 10)], &[
+            0xe0000291, // mul   r0, r1, r2
+            0xe0314392, // mlas  r1, r2, r3, r4
+        ]);
+        assert_eq!(interp.cpu.regs.get(0), -6i32 as u32);
+        assert_eq!(interp.cpu.regs.get(1), 0);
+        assert!(interp.cpu.regs.flag(Psr::Z));
+
+        let interp = exec(&[(4, 0xffff_ffff), (5, 2), (6, -3i32 as u32), (7, 7)], &[
+            0xe0832594, // umull r2, r3, r4, r5
+            0xe0a32594, // umlal r2, r3, r4, r5
+            0xe0c54796, // smull r4, r5, r6, r7
+            0xe0e54796, // smlal r4, r5, r6, r7
+        ]);
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(2), regs.get(3)), (0xffff_fffc, 3));
+        assert_eq!((regs.get(4), regs.get(5)), (-42i32 as u32, 0xffff_ffff));
+    }
+
+    #[test]
+    fn dsp() {
+        let interp = exec(&[(1, 0x8000), (2, 0x8000_0000), (3, 0x4000_0000)], &[
+            0xe10032c1, // smlabt  r0, r1, r2, r3
+        ]);
+        assert_eq!(interp.cpu.regs.get(0), 0x8000_0000);
+        assert!(interp.cpu.regs.flag(Psr::Q));
+
+        let interp = exec(&[(0, 0xffff_fff0), (1, 0), (2, 0x10), (3, 0xffff_0001)], &[
+            0xe1410382, // smlalbb r0, r1, r2, r3
+        ]);
+        assert_eq!((interp.cpu.regs.get(0), interp.cpu.regs.get(1)), (0, 1));
+
+        let interp = exec(&[(1, 0x0003_0000), (2, 0xfffe_0000)], &[
+            0xe12002e1, // smulwt  r0, r1, r2
+        ]);
+        assert_eq!(interp.cpu.regs.get(0), -6i32 as u32);
+        assert!(!interp.cpu.regs.flag(Psr::Q));
+
+        let interp = exec(&[(1, 0x7fff_fff0), (2, 0x10)], &[
+            0xe1024051, // qadd    r4, r1, r2
+            0xe1625051, // qdsub   r5, r1, r2
+            0xe16f6f14, // clz     r6, r4
+        ]);
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(4), regs.get(5), regs.get(6)), (0x7fff_ffff, 0x7fff_ffd0, 1));
+        assert!(regs.flag(Psr::Q));
+    }
+
+    #[test]
+    fn status() {
+        let interp = exec(&[(0, 0x2000_00d2), (1, 0x1234_5678), (13, 0x800)], &[
+            0xe129f000, // msr   cpsr_fc, r0
+            0xe16ff001, // msr   spsr_fsxc, r1
+            0xe14f2000, // mrs   r2, spsr
+            0xe10f3000, // mrs   r3, cpsr
+            0xe328f20f, // msr   apsr_nzcvq, #0xf0000000
+        ]);
+        let regs = &interp.cpu.regs;
+        assert_eq!(regs.mode(), Mode::Irq);
+        assert_eq!((regs.get(2), regs.get(3)), (0x1234_5678, 0x2000_00d2));
+        assert_eq!(regs.cpsr(), 0xf000_00d2);
+        assert_eq!(regs.get(13), 0);
+
+        // Only the flags can be written in user mode.
+        let interp = exec(&[(0, 0x10), (1, 0x8000_00d3)], &[
+            0xe129f000, // msr   cpsr_fc, r0
+            0xe129f001, // msr   cpsr_fc, r1
+        ]);
+        assert_eq!(interp.cpu.regs.cpsr(), 0x8000_0010);
+    }
+
+    #[test]
+    fn load_store() {
+        let words = [0x1122_3344, 0x5566_7788, 0x99aa_bbcc, 0xddee_ff00];
+        let setup = |regs: &[(u32, u32)], code: &[u32]| {
+            let mut interp = exec(&[], &[]);
+            for (i, w) in words.iter().enumerate() {
+                interp.cpu.bus.write32(0x100 + i as u32 * 4, *w).unwrap();
+            }
+            for (idx, val) in regs {
+                interp.cpu.regs.set(*idx, *val);
+            }
+            for (i, x) in code.iter().enumerate() {
+                interp.cpu.bus.write32(i as u32 * 4, *x).unwrap();
+            }
+            interp.run_until(code.len() as u32 * 4, 100).unwrap();
+            interp
+        };
+        let regs = |interp: &Interpreter<Ram>, n: &[u32]| -> Vec<u32> {
+            n.iter().map(|i| interp.cpu.regs.get(*i)).collect()
+        };
+
+        let interp = setup(&[(1, 0x100), (2, 1)], &[
+            0xe5b10004, // ldr   r0, [r1, #4]!
+            0xe6113102, // ldr   r3, [r1], -r2, lsl #2
+            0xe5d14003, // ldrb  r4, [r1, #3]
+        ]);
+        assert_eq!(regs(&interp, &[0, 1, 3, 4]), vec![0x5566_7788, 0x100, 0x5566_7788, 0x11]);
+
+        // Unaligned words are rotated
+        let interp = setup(&[(1, 0x101)], &[0xe5b10004]);
+        assert_eq!(regs(&interp, &[0, 1]), vec![0x8855_6677, 0x105]);
+
+        let interp = setup(&[(1, 0x100), (2, 9)], &[
+            0xe1f100b2, // ldrh  r0, [r1, #2]!
+            0xe19130d2, // ldrsb r3, [r1, r2]
+            0xe1d140f6, // ldrsh r4, [r1, #6]
+            0xe05150f2, // ldrsh r5, [r1], #-2
+        ]);
+        assert_eq!(regs(&interp, &[0, 1, 3, 4, 5]),
+            vec![0x1122, 0x100, 0xffff_ff99, 0xffff_bbcc, 0x1122]);
+
+        let mut interp = setup(&[(0, 0xabcd_1234), (1, 0x100), (2, 0x13)], &[
+            0xe1c100be, // strh  r0, [r1, #14]
+            0xe7c10002, // strb  r0, [r1, r2]
+            0xe5810008, // str   r0, [r1, #8]
+            0xe1c120d8, // ldrd  r2, r3, [r1, #8]
+            0xe04120f8, // strd  r2, r3, [r1], #-8
+        ]);
+        assert_eq!(regs(&interp, &[1, 2, 3]), vec![0xf8, 0xabcd_1234, 0x1234_ff00]);
+        let bus = &mut interp.cpu.bus;
+        assert_eq!(bus.read32(0x100).unwrap(), 0xabcd_1234);
+        assert_eq!(bus.read32(0x104).unwrap(), 0x1234_ff00);
+        assert_eq!(bus.read32(0x10c).unwrap(), 0x1234_ff00);
+        assert_eq!(bus.read32(0x110).unwrap(), 0x3400_0000);
+
+        // Loads into the PC interwork
+        let mut interp = setup(&[(1, 0x100)], &[]);
+        interp.cpu.bus.write32(0x100, 0x201).unwrap();
+        interp.cpu.bus.write32(0, 0xe591f000).unwrap(); // ldr pc, [r1]
+        interp.step().unwrap();
+        assert_eq!(interp.cpu.regs.pc(), 0x200);
+        assert!(interp.cpu.regs.thumb());
+    }
+
+    #[test]
+    fn load_store_multiple() {
+        let mut interp = exec(&[(0, 1), (1, 2), (13, 0x200), (14, 0x10)], &[
+            0xe92d4003, // push  {r0, r1, lr}
+            0xe3a00000, // mov   r0, #0
+            0xe3a01000, // mov   r1, #0
+            0xe8bd8003, // pop   {r0, r1, pc}
+        ]);
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(0), regs.get(1), regs.get(13)), (1, 2, 0x200));
+        assert_eq!(interp.cpu.bus.read32(0x1fc).unwrap(), 0x10);
+
+        let interp = exec(&[(0, 0x200)], &[
+            0xe3a01011, // mov   r1, #17
+            0xe3a02022, // mov   r2, #34
+            0xe8000006, // stmda r0, {r1, r2}
+            0xe3a01000, // mov   r1, #0
+            0xe9900006, // ldmib r0, {r1, r2}
+        ]);
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(1), regs.get(2)), (0, 0));
+
+        // User bank transfers from FIQ mode
+        let mut interp = exec(&[], &[]);
+        interp.cpu.regs.set_cpsr(Mode::Sys.bits());
+        interp.cpu.regs.set(13, 0x1313);
+        interp.cpu.regs.set(8, 0x88);
+        interp.cpu.regs.set_cpsr(Mode::Fiq.bits());
+        interp.cpu.regs.set(0, 0x1fc);
+        interp.cpu.regs.set(8, 0xf8);
+        interp.cpu.bus.write32(0, 0xe9c02100).unwrap(); // stmib r0, {r8, sp}^
+        interp.step().unwrap();
+        assert_eq!(interp.cpu.bus.read32(0x200).unwrap(), 0x88);
+        assert_eq!(interp.cpu.bus.read32(0x204).unwrap(), 0x1313);
+
+        // Exception return
+        let mut interp = exec(&[], &[]);
+        interp.cpu.regs.set(13, 0x200);
+        interp.cpu.regs.set_spsr(Mode::Usr.bits() | Psr::T | Psr::C);
+        interp.cpu.bus.write32(0x200, 0x55).unwrap();
+        interp.cpu.bus.write32(0x204, 0x103).unwrap();
+        interp.cpu.bus.write32(0, 0xe8fd8001).unwrap(); // ldm sp!, {r0, pc}^
+        interp.step().unwrap();
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(0), regs.pc(), regs.cpsr()),
+            (0x55, 0x102, Mode::Usr.bits() | Psr::T | Psr::C));
+        assert_eq!(regs.get(13), 0);
+        interp.cpu.regs.set_cpsr(Mode::Svc.bits());
+        assert_eq!(interp.cpu.regs.get(13), 0x208);
+    }
+
+    #[test]
+    fn swap() {
+        let mut interp = exec(&[(1, 0x1234_5678), (2, 0x100)], &[
+            0xe1020091, // swp   r0, r1, [r2]
+            0xe1423091, // swpb  r3, r1, [r2]
+        ]);
+        assert_eq!(interp.cpu.regs.get(0), 0);
+        assert_eq!(interp.cpu.regs.get(3), 0x78);
+        assert_eq!(interp.cpu.bus.read32(0x100).unwrap(), 0x1234_5678);
+    }
+
+    #[test]
+    fn branches() {
+        let mut interp = exec(&[], &[]);
+        let mut step = |pc, x| {
+            interp.cpu.regs.set_pc(pc);
+            interp.cpu.bus.write32(pc, x).unwrap();
+            interp.step().unwrap();
+            let regs = &interp.cpu.regs;
+            (regs.pc(), regs.get(14), regs.thumb())
+        };
+        assert_eq!(step(0x100, 0xea000000), (0x108, 0, false)); // b   .+8
+        assert_eq!(step(0x100, 0xebfffffc), (0xf8, 0x104, false)); // bl  .-8
+        assert_eq!(step(0x200, 0xfb000000), (0x20a, 0x204, true)); // blx .+10
+
+        let mut interp = exec(&[(0, 0x301), (1, 0x400)], &[]);
+        interp.cpu.bus.write32(0, 0xe12fff10).unwrap(); // bx  r0
+        interp.step().unwrap();
+        assert_eq!((interp.cpu.regs.pc(), interp.cpu.regs.thumb()), (0x300, true));
+        interp.cpu.regs.set_cpsr(Mode::Svc.bits());
+        interp.cpu.regs.set_pc(4);
+        interp.cpu.bus.write32(4, 0xe12fff31).unwrap(); // blx r1
+        interp.step().unwrap();
+        assert_eq!((interp.cpu.regs.pc(), interp.cpu.regs.get(14)), (0x400, 8));
+    }
+
+    #[test]
+    fn exception_return() {
+        let mut interp = exec(&[], &[]);
+        interp.cpu.regs.set(14, 0x105);
+        interp.cpu.regs.set_spsr(Mode::Usr.bits() | Psr::T);
+        interp.cpu.bus.write32(0, 0xe25ef004).unwrap(); // subs pc, lr, #4
+        interp.step().unwrap();
+        assert_eq!((interp.cpu.regs.pc(), interp.cpu.regs.cpsr()),
+            (0x100, Mode::Usr.bits() | Psr::T));
+
+        let mut interp = exec(&[], &[]);
+        interp.cpu.regs.set(14, 0x100);
+        interp.cpu.regs.set_spsr(Mode::Sys.bits() | Psr::Z);
+        interp.cpu.bus.write32(0, 0xe1b0f00e).unwrap(); // movs pc, lr
+        interp.step().unwrap();
+        assert_eq!((interp.cpu.regs.pc(), interp.cpu.regs.cpsr()),
+            (0x100, Mode::Sys.bits() | Psr::Z));
+    }
+}
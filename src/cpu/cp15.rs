@@ -0,0 +1,310 @@
+//! The ARM926EJ-S system control coprocessor (CP15).
+//!
+//! Caches, TLBs and write buffers aren't modelled, so maintenance operations
+//! don't change any state here: [Cp15::write] hands them back to the caller
+//! as a [Maint] instead, which the interpreter leaves in
+//! [Cpu::last_maint](super::Cpu::last_maint) for embedders which want to
+//! hook them.
+//!
+//! Which registers exist (and in which direction) is taken from the
+//! [crate::sysreg] catalogue for the ARM926EJ-S; accesses to anything else
+//! are undefined.
+//!
+//! The TCMs aren't modelled either. Their region registers can be read and
+//! written, but accesses inside the regions go to the bus like any other.
+
+use std::fmt;
+
+use crate::sysreg::{Core, Dir, SysReg};
+
+use super::mmu::Fault;
+
+/// Main ID register (ARM, ARMv5TEJ, ARM926, revision 5).
+pub const ID_ARM926: u32 = 0x4106_9265;
+/// Cache type register: separate 16KiB, 4-way, 8-word line caches.
+pub const CACHE_TYPE_ARM926: u32 = 0x1d15_2152;
+/// TCM status register: one ITCM and one DTCM present.
+pub const TCM_STATUS_ARM926: u32 = 0x0001_0001;
+
+/// Control register bits.
+pub const CTRL_M: u32 = 1 << 0;
+pub const CTRL_A: u32 = 1 << 1;
+pub const CTRL_C: u32 = 1 << 2;
+pub const CTRL_B: u32 = 1 << 7;
+pub const CTRL_S: u32 = 1 << 8;
+pub const CTRL_R: u32 = 1 << 9;
+pub const CTRL_I: u32 = 1 << 12;
+pub const CTRL_V: u32 = 1 << 13;
+pub const CTRL_RR: u32 = 1 << 14;
+pub const CTRL_L4: u32 = 1 << 15;
+
+/// Control register bits which read as one.
+const CTRL_SBO: u32 = 0x0005_0078;
+/// Control register bits which can be written.
+const CTRL_WRITABLE: u32 = CTRL_M | CTRL_A | CTRL_C | CTRL_B | CTRL_S |
+    CTRL_R | CTRL_I | CTRL_V | CTRL_RR | CTRL_L4;
+
+/// The core whose registers are modelled.
+pub const CORE: Core = Core::Arm926ejs;
+
+/// Error returned for accesses to registers which don't exist (or can't be
+/// accessed in that direction). The instruction is undefined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoReg(pub SysReg);
+impl fmt::Display for NoReg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.0.name(CORE, Dir::Read).or(self.0.name(CORE, Dir::Write));
+        match name {
+            Some(name) => write!(f, "{} ({}) can't be accessed that way", name, self.0),
+            None => write!(f, "no such register ({})", self.0),
+        }
+    }
+}
+impl std::error::Error for NoReg {}
+
+/// A cache, TLB or power management operation.
+///
+/// The operand is the value written to the register (a modified virtual
+/// address, or a set/way/index, depending on the operation), or [None]
+/// for operations on the whole cache or TLB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Maint {
+    WaitForInterrupt,
+    InvalidateICache(Option<u32>),
+    InvalidateDCache(Option<u32>),
+    InvalidateCaches,
+    CleanDCache(u32),
+    CleanInvalidateDCache(u32),
+    PrefetchICache(u32),
+    FlushPrefetchBuffer,
+    DrainWriteBuffer,
+    InvalidateITlb(Option<u32>),
+    InvalidateDTlb(Option<u32>),
+    InvalidateTlbs(Option<u32>),
+}
+
+/// The CP15 register state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cp15 {
+    pub id: u32,
+    pub cache_type: u32,
+    pub tcm_status: u32,
+    pub control: u32,
+    /// Translation table base (c2).
+    pub ttb: u32,
+    /// Domain access control (c3).
+    pub dacr: u32,
+    /// Data and instruction fault status (c5).
+    pub dfsr: u32,
+    pub ifsr: u32,
+    /// Fault address (c6).
+    pub far: u32,
+    /// Cache lockdown (c9, c0).
+    pub dcache_lockdown: u32,
+    pub icache_lockdown: u32,
+    /// TCM region registers (c9, c1): base in bits 31-12, size in bits 5-2
+    /// and an enable bit. These don't affect how accesses are routed.
+    pub dtcm_region: u32,
+    pub itcm_region: u32,
+    /// TLB lockdown (c10).
+    pub tlb_lockdown: u32,
+    /// FCSE process ID and context ID (c13).
+    pub fcse_pid: u32,
+    pub context_id: u32,
+}
+
+impl Default for Cp15 {
+    fn default() -> Self { Self::new() }
+}
+
+impl Cp15 {
+    /// CP15 in its reset state, with the MMU and caches off.
+    pub fn new() -> Self {
+        Cp15 {
+            id: ID_ARM926,
+            cache_type: CACHE_TYPE_ARM926,
+            tcm_status: TCM_STATUS_ARM926,
+            control: CTRL_SBO,
+            ttb: 0,
+            dacr: 0,
+            dfsr: 0,
+            ifsr: 0,
+            far: 0,
+            dcache_lockdown: 0,
+            icache_lockdown: 0,
+            dtcm_region: 0,
+            itcm_region: 0,
+            tlb_lockdown: 0,
+            fcse_pid: 0,
+            context_id: 0,
+        }
+    }
+
+    pub fn mmu_enabled(&self) -> bool { (self.control & CTRL_M) != 0 }
+    pub fn alignment_checks(&self) -> bool { (self.control & CTRL_A) != 0 }
+    pub fn high_vectors(&self) -> bool { (self.control & CTRL_V) != 0 }
+
+    /// Apply the fast context switch extension to a virtual address.
+    pub fn mva(&self, va: u32) -> u32 {
+        if va < (1 << 25) { va | (self.fcse_pid & 0xfe00_0000) } else { va }
+    }
+
+    /// The DTCM region as `(base, size)`, if it's enabled.
+    pub fn dtcm(&self) -> Option<(u32, u32)> { tcm_region(self.dtcm_region) }
+
+    /// The ITCM region as `(base, size)`, if it's enabled.
+    pub fn itcm(&self) -> Option<(u32, u32)> { tcm_region(self.itcm_region) }
+
+    /// Record a data abort in the DFSR and FAR.
+    pub fn data_fault(&mut self, fault: &Fault) {
+        self.dfsr = fault.status();
+        self.far = fault.va;
+    }
+
+    /// Record a prefetch abort in the IFSR.
+    pub fn prefetch_fault(&mut self, fault: &Fault) {
+        self.ifsr = fault.status();
+    }
+
+    /// Read a register with `mrc`.
+    pub fn read(&self, reg: SysReg) -> Result<u32, NoReg> {
+        if reg.cp != 15 || reg.name(CORE, Dir::Read).is_none() {
+            return Err(NoReg(reg));
+        }
+        let SysReg { crn, crm, op2, .. } = reg;
+        Ok(match (crn, crm, op2) {
+            (0, 0, 1) => self.cache_type,
+            (0, 0, 2) => self.tcm_status,
+            // Unimplemented ID registers read as the main ID
+            (0, 0, _) => self.id,
+            (1, 0, 0) => self.control,
+            (2, 0, 0) => self.ttb,
+            (3, 0, 0) => self.dacr,
+            (5, 0, 0) => self.dfsr,
+            (5, 0, 1) => self.ifsr,
+            (6, 0, 0) => self.far,
+            // Test (and clean, and invalidate): the cache is always clean,
+            // which sets Z when the destination is the PC.
+            (7, 10, 3) | (7, 14, 3) => 1 << 30,
+            (9, 0, 0) => self.dcache_lockdown,
+            (9, 0, 1) => self.icache_lockdown,
+            (9, 1, 0) => self.dtcm_region,
+            (9, 1, 1) => self.itcm_region,
+            (10, 0, 0) => self.tlb_lockdown,
+            (13, 0, 0) => self.fcse_pid,
+            (13, 0, 1) => self.context_id,
+            _ => return Err(NoReg(reg)),
+        })
+    }
+
+    /// Write a register with `mcr`, returning the maintenance operation it
+    /// requests, if any.
+    pub fn write(&mut self, reg: SysReg, val: u32) -> Result<Option<Maint>, NoReg> {
+        if reg.cp != 15 || reg.name(CORE, Dir::Write).is_none() {
+            return Err(NoReg(reg));
+        }
+        let SysReg { crn, crm, op2, .. } = reg;
+        let maint = match (crn, crm, op2) {
+            (1, 0, 0) => {
+                self.control = (val & CTRL_WRITABLE) | CTRL_SBO;
+                return Ok(None);
+            },
+            (2, 0, 0) => { self.ttb = val & 0xffff_c000; return Ok(None); },
+            (3, 0, 0) => { self.dacr = val; return Ok(None); },
+            (5, 0, 0) => { self.dfsr = val & 0xff; return Ok(None); },
+            (5, 0, 1) => { self.ifsr = val & 0xff; return Ok(None); },
+            (6, 0, 0) => { self.far = val; return Ok(None); },
+            (9, 0, 0) => { self.dcache_lockdown = val; return Ok(None); },
+            (9, 0, 1) => { self.icache_lockdown = val; return Ok(None); },
+            (9, 1, 0) => { self.dtcm_region = val & 0xffff_f03d; return Ok(None); },
+            (9, 1, 1) => { self.itcm_region = val & 0xffff_f03d; return Ok(None); },
+            (10, 0, 0) => { self.tlb_lockdown = val; return Ok(None); },
+            (13, 0, 0) => { self.fcse_pid = val & 0xfe00_0000; return Ok(None); },
+            (13, 0, 1) => { self.context_id = val; return Ok(None); },
+
+            (7, 0, 4) => Maint::WaitForInterrupt,
+            (7, 5, 0) => Maint::InvalidateICache(None),
+            (7, 5, 1) | (7, 5, 2) => Maint::InvalidateICache(Some(val)),
+            (7, 5, 4) => Maint::FlushPrefetchBuffer,
+            (7, 6, 0) => Maint::InvalidateDCache(None),
+            (7, 6, 1) | (7, 6, 2) => Maint::InvalidateDCache(Some(val)),
+            (7, 7, 0) => Maint::InvalidateCaches,
+            (7, 10, 1) | (7, 10, 2) => Maint::CleanDCache(val),
+            (7, 10, 4) => Maint::DrainWriteBuffer,
+            (7, 13, 1) => Maint::PrefetchICache(val),
+            (7, 14, 1) | (7, 14, 2) => Maint::CleanInvalidateDCache(val),
+            (8, 5, 0) => Maint::InvalidateITlb(None),
+            (8, 5, 1) => Maint::InvalidateITlb(Some(val)),
+            (8, 6, 0) => Maint::InvalidateDTlb(None),
+            (8, 6, 1) => Maint::InvalidateDTlb(Some(val)),
+            (8, 7, 0) => Maint::InvalidateTlbs(None),
+            (8, 7, 1) => Maint::InvalidateTlbs(Some(val)),
+            _ => return Err(NoReg(reg)),
+        };
+        Ok(Some(maint))
+    }
+}
+
+/// Decode a TCM region register.
+fn tcm_region(x: u32) -> Option<(u32, u32)> {
+    let size = (x >> 2) & 0xf;
+    if (x & 1) == 0 || size < 3 {
+        return None;
+    }
+    Some((x & 0xffff_f000, 1 << (size + 9)))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registers() {
+        let mut cp15 = Cp15::new();
+        assert_eq!(cp15.read(SysReg::new(15, 0, 0, 0, 0)), Ok(ID_ARM926));
+        assert_eq!(cp15.read(SysReg::new(15, 0, 0, 0, 1)), Ok(CACHE_TYPE_ARM926));
+        assert_eq!(cp15.read(SysReg::new(15, 0, 1, 0, 0)), Ok(0x0005_0078));
+
+        // SBZ/SBO bits in the control register are fixed
+        cp15.write(SysReg::new(15, 0, 1, 0, 0), 0xffff_ffff).unwrap();
+        assert_eq!(cp15.control, 0x0005_f3ff);
+        assert!(cp15.mmu_enabled() && cp15.high_vectors());
+
+        cp15.write(SysReg::new(15, 0, 2, 0, 0), 0x1234_5678).unwrap();
+        assert_eq!(cp15.read(SysReg::new(15, 0, 2, 0, 0)), Ok(0x1234_4000));
+        cp15.write(SysReg::new(15, 0, 13, 0, 0), 0x0300_0000).unwrap();
+        assert_eq!(cp15.mva(0x0000_1000), 0x0200_1000);
+        assert_eq!(cp15.mva(0x8000_1000), 0x8000_1000);
+
+        cp15.write(SysReg::new(15, 0, 9, 1, 0), 0x0040_0015).unwrap();
+        assert_eq!(cp15.dtcm(), Some((0x0040_0000, 0x4000)));
+        assert_eq!(cp15.itcm(), None);
+
+        // Registers come from the catalogue
+        let c4 = SysReg::new(15, 0, 4, 0, 0);
+        assert_eq!(cp15.read(c4), Err(NoReg(c4)));
+        assert_eq!(NoReg(c4).to_string(), "no such register (p15, 0, c4, c0, 0)");
+        assert!(cp15.read(SysReg::new(15, 1, 1, 0, 0)).is_err());
+        assert!(cp15.read(SysReg::new(14, 0, 0, 0, 0)).is_err());
+        assert_eq!(cp15.read(SysReg::new(15, 0, 0, 0, 5)), Ok(ID_ARM926));
+        let id = SysReg::new(15, 0, 0, 0, 0);
+        assert_eq!(cp15.write(id, 0), Err(NoReg(id)));
+        assert_eq!(NoReg(id).to_string(),
+            "Main ID (p15, 0, c0, c0, 0) can't be accessed that way");
+    }
+
+    #[test]
+    fn maintenance() {
+        let mut cp15 = Cp15::new();
+        let mut op = |crn, crm, op2, val| {
+            cp15.write(SysReg::new(15, 0, crn, crm, op2), val).unwrap()
+        };
+        assert_eq!(op(7, 5, 0, 0), Some(Maint::InvalidateICache(None)));
+        assert_eq!(op(7, 14, 1, 0x8000), Some(Maint::CleanInvalidateDCache(0x8000)));
+        assert_eq!(op(7, 10, 4, 0), Some(Maint::DrainWriteBuffer));
+        assert_eq!(op(8, 7, 0, 0), Some(Maint::InvalidateTlbs(None)));
+        assert_eq!(op(7, 0, 4, 0), Some(Maint::WaitForInterrupt));
+        assert_eq!(cp15.read(SysReg::new(15, 0, 7, 14, 3)), Ok(1 << 30));
+    }
+}
@@ -0,0 +1,314 @@
+//! The exception model: entry into and return from exception modes.
+//!
+//! [enter] and [leave] are plain state transitions over a [RegisterFile],
+//! so they can be used without the rest of the interpreter. The
+//! [crate::cpu::Interpreter] routes each [Trap] into the matching exception
+//! with [crate::cpu::Interpreter::step_with_exceptions].
+
+use std::fmt;
+
+use super::psr::*;
+use super::regs::*;
+use super::Trap;
+
+/// Base address of the vector table when high vectors are enabled.
+pub const HIGH_VECTORS: u32 = 0xffff_0000;
+
+/// An ARMv5 exception.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Exception {
+    Reset,
+    Undefined,
+    Swi,
+    PrefetchAbort,
+    DataAbort,
+    Irq,
+    Fiq,
+}
+impl Exception {
+    pub const ALL: [Exception; 7] = [
+        Exception::Reset, Exception::Undefined, Exception::Swi,
+        Exception::PrefetchAbort, Exception::DataAbort,
+        Exception::Irq, Exception::Fiq,
+    ];
+
+    /// Offset of the vector from the base of the vector table.
+    pub fn vector(self) -> u32 {
+        match self {
+            Exception::Reset => 0x00,
+            Exception::Undefined => 0x04,
+            Exception::Swi => 0x08,
+            Exception::PrefetchAbort => 0x0c,
+            Exception::DataAbort => 0x10,
+            Exception::Irq => 0x18,
+            Exception::Fiq => 0x1c,
+        }
+    }
+
+    /// Address of the vector, with the table at zero or at [HIGH_VECTORS].
+    pub fn vector_addr(self, high_vectors: bool) -> u32 {
+        let base = if high_vectors { HIGH_VECTORS } else { 0 };
+        base | self.vector()
+    }
+
+    /// The mode entered when taking this exception.
+    pub fn mode(self) -> Mode {
+        match self {
+            Exception::Reset | Exception::Swi => Mode::Svc,
+            Exception::Undefined => Mode::Und,
+            Exception::PrefetchAbort | Exception::DataAbort => Mode::Abt,
+            Exception::Irq => Mode::Irq,
+            Exception::Fiq => Mode::Fiq,
+        }
+    }
+
+    /// Priority when several exceptions occur at once (1 is the highest).
+    /// Undefined instructions and SWIs are mutually exclusive, so they
+    /// share the lowest priority.
+    pub fn priority(self) -> u32 {
+        match self {
+            Exception::Reset => 1,
+            Exception::DataAbort => 2,
+            Exception::Fiq => 3,
+            Exception::Irq => 4,
+            Exception::PrefetchAbort => 5,
+            Exception::Undefined | Exception::Swi => 6,
+        }
+    }
+
+    /// Returns true if the exception also masks FIQs on entry.
+    pub fn masks_fiq(self) -> bool {
+        matches!(self, Exception::Reset | Exception::Fiq)
+    }
+
+    /// Returns true if interrupts of this kind are disabled by some CPSR.
+    /// Only IRQ and FIQ can be masked.
+    pub fn masked(self, cpsr: Psr) -> bool {
+        match self {
+            Exception::Irq => cpsr.i(),
+            Exception::Fiq => cpsr.f(),
+            _ => false,
+        }
+    }
+
+    /// The value written to the banked LR, relative to the address of the
+    /// instruction which caused the exception (or, for interrupts, the
+    /// address of the next instruction to execute).
+    pub fn lr_offset(self, thumb: bool) -> u32 {
+        match self {
+            Exception::Undefined | Exception::Swi if thumb => 2,
+            Exception::DataAbort => 8,
+            _ => 4,
+        }
+    }
+
+    /// The amount subtracted from the LR by the usual return sequence:
+    /// `movs pc, lr` (0), `subs pc, lr, #4` (4) or `subs pc, lr, #8` (8).
+    pub fn return_offset(self) -> u32 {
+        match self {
+            Exception::Reset | Exception::Undefined | Exception::Swi => 0,
+            Exception::DataAbort => 8,
+            _ => 4,
+        }
+    }
+
+    /// Pick the exception to take first from some set of pending ones.
+    pub fn highest<I>(pending: I) -> Option<Exception>
+        where I: IntoIterator<Item = Exception>
+    {
+        pending.into_iter().min_by_key(|e| e.priority())
+    }
+}
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Exception::Reset => "reset",
+            Exception::Undefined => "undefined instruction",
+            Exception::Swi => "software interrupt",
+            Exception::PrefetchAbort => "prefetch abort",
+            Exception::DataAbort => "data abort",
+            Exception::Irq => "irq",
+            Exception::Fiq => "fiq",
+        })
+    }
+}
+
+/// The exception taken for some trap. Breakpoints are treated as prefetch
+/// aborts, as on ARMv5 cores without a debug unit attached.
+impl From<Trap> for Exception {
+    fn from(trap: Trap) -> Self {
+        match trap {
+            Trap::Undefined(_) => Exception::Undefined,
+            Trap::Swi(_) => Exception::Swi,
+            Trap::Bkpt(_) | Trap::PrefetchAbort(_) => Exception::PrefetchAbort,
+            Trap::DataAbort(_) => Exception::DataAbort,
+        }
+    }
+}
+
+/// Take an exception.
+///
+/// The PC must hold the address of the instruction which caused the
+/// exception or, for interrupts, the next instruction to execute. The CPSR
+/// is saved in the SPSR of the new mode, the return address is written to
+/// its LR, and execution continues in ARM state at the vector with IRQs
+/// (and for FIQ and reset, FIQs) disabled. On reset, the LR and SPSR are
+/// unpredictable and left alone.
+pub fn enter(regs: &mut RegisterFile, exc: Exception, high_vectors: bool) {
+    let old = regs.psr();
+    let lr = regs.pc().wrapping_add(exc.lr_offset(old.t()));
+    regs.set_mode(exc.mode());
+    if exc != Exception::Reset {
+        regs.set_spsr(old.0);
+        regs.set(14, lr);
+    }
+    let mut psr = regs.psr();
+    psr.set_t(false);
+    psr.set_i(true);
+    if exc.masks_fiq() {
+        psr.set_f(true);
+    }
+    regs.set_psr(psr);
+    regs.set_pc(exc.vector_addr(high_vectors));
+}
+
+/// Return from an exception, as the usual return sequence for it would:
+/// the PC is set to the LR less [Exception::return_offset] and the CPSR is
+/// restored from the SPSR. Does nothing in modes without an SPSR.
+pub fn leave(regs: &mut RegisterFile, exc: Exception) {
+    if let Some(spsr) = regs.spsr() {
+        let addr = regs.get(14).wrapping_sub(exc.return_offset());
+        regs.set_cpsr(spsr);
+        let mask = if regs.thumb() { !1 } else { !3 };
+        regs.set_pc(addr & mask);
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::{Interpreter, Ram, Bus};
+
+    #[test]
+    fn entry() {
+        let mut regs = RegisterFile::new();
+        regs.set_cpsr(Mode::Usr.bits() | Psr::T | Psr::Z);
+        regs.set(14, 0x1234);
+        regs.set_pc(0x102);
+        enter(&mut regs, Exception::Swi, false);
+        assert_eq!((regs.pc(), regs.get(14)), (0x08, 0x104));
+        assert_eq!(regs.cpsr(), Mode::Svc.bits() | Psr::Z | Psr::I);
+        assert_eq!(regs.spsr(), Some(Mode::Usr.bits() | Psr::T | Psr::Z));
+
+        // FIQ from supervisor mode, with high vectors
+        regs.set_pc(0x400);
+        enter(&mut regs, Exception::Fiq, true);
+        assert_eq!((regs.pc(), regs.get(14)), (0xffff_001c, 0x404));
+        assert_eq!(regs.cpsr(), Mode::Fiq.bits() | Psr::Z | Psr::I | Psr::F);
+        assert_eq!(regs.spsr(), Some(Mode::Svc.bits() | Psr::Z | Psr::I));
+        assert_eq!(regs.get_banked(Mode::Usr, 14), 0x1234);
+
+        leave(&mut regs, Exception::Fiq);
+        assert_eq!((regs.pc(), regs.get(14)), (0x400, 0x104));
+        leave(&mut regs, Exception::Swi);
+        assert_eq!((regs.pc(), regs.cpsr()), (0x104, Mode::Usr.bits() | Psr::T | Psr::Z));
+        assert_eq!(regs.get(14), 0x1234);
+
+        // leave() is a no-op without an SPSR
+        leave(&mut regs, Exception::Swi);
+        assert_eq!(regs.pc(), 0x104);
+
+        enter(&mut regs, Exception::Reset, false);
+        assert_eq!((regs.pc(), regs.cpsr()),
+            (0, Mode::Svc.bits() | Psr::Z | Psr::I | Psr::F));
+    }
+
+    #[test]
+    fn priorities() {
+        use Exception::*;
+        assert_eq!(Exception::highest([Irq, Fiq, Undefined]), Some(Fiq));
+        assert_eq!(Exception::highest([PrefetchAbort, DataAbort]), Some(DataAbort));
+        assert_eq!(Exception::highest([]), None);
+        assert!(Irq.masked(Psr(Psr::I)) && !Fiq.masked(Psr(Psr::I)));
+        assert!(!Swi.masked(Psr(Psr::I | Psr::F)));
+        assert_eq!(Exception::from(Trap::Bkpt(3)), PrefetchAbort);
+    }
+
+    /// Take each exception through the interpreter, then return with the
+    /// usual sequence from a handler at the vector.
+    #[test]
+    fn round_trip() {
+        const MOVS_PC_LR: u32 = 0xe1b0_f00e;
+        const SUBS_PC_LR_4: u32 = 0xe25e_f004;
+        const SUBS_PC_LR_8: u32 = 0xe25e_f008;
+        const LDMFD_SP_PC_HAT: u32 = 0xe8fd_8000;
+
+        let mut interp = Interpreter::new(Ram::new(0, 0x1000));
+        let bus = &mut interp.cpu.bus;
+        bus.write32(0x04, MOVS_PC_LR).unwrap();
+        bus.write32(0x08, MOVS_PC_LR).unwrap();
+        bus.write32(0x0c, SUBS_PC_LR_4).unwrap();
+        bus.write32(0x10, SUBS_PC_LR_8).unwrap();
+        bus.write32(0x18, SUBS_PC_LR_4).unwrap();
+        bus.write32(0x1c, LDMFD_SP_PC_HAT).unwrap();
+        bus.write32(0x100, 0xef00_0001).unwrap();   // swi   #1
+        bus.write32(0x104, 0xe7f0_00f0).unwrap();   // udf
+        bus.write32(0x108, 0xe591_0000).unwrap();   // ldr   r0, [r1]
+        bus.write32(0x10c, 0xe120_0070).unwrap();   // bkpt  #0
+        bus.write16(0x200, 0xdf07).unwrap();        // svc   #7
+        bus.write32(0x800, 0x08).unwrap();          // FIQ stack
+
+        let cpu = &mut interp.cpu;
+        cpu.regs.set_cpsr(Mode::Usr.bits() | Psr::C);
+        cpu.regs.set(1, 0x8000);
+        cpu.regs.set_pc(0x100);
+
+        for (at, exc) in [
+            (0x100, Exception::Swi),
+            (0x104, Exception::Undefined),
+            (0x108, Exception::DataAbort),
+            (0x10c, Exception::PrefetchAbort),
+        ] {
+            assert_eq!(interp.cpu.regs.pc(), at);
+            assert_eq!(interp.step_with_exceptions(), Some(exc));
+            let regs = &interp.cpu.regs;
+            assert_eq!((regs.pc(), regs.mode()), (exc.vector(), exc.mode()));
+            assert_eq!(regs.spsr(), Some(Mode::Usr.bits() | Psr::C));
+            assert_eq!(interp.step_with_exceptions(), None);
+            let regs = &interp.cpu.regs;
+            // Aborts retry the instruction, the others carry on after it.
+            let ret = match exc {
+                Exception::Swi | Exception::Undefined => at + 4,
+                _ => at,
+            };
+            assert_eq!((regs.pc(), regs.cpsr()), (ret, Mode::Usr.bits() | Psr::C));
+            interp.cpu.regs.set_pc(at + 4);
+        }
+
+        // IRQ is masked by the I bit
+        interp.cpu.regs.set_flag(Psr::I, true);
+        assert!(!interp.interrupt(Exception::Irq));
+        interp.cpu.regs.set_flag(Psr::I, false);
+        assert!(interp.interrupt(Exception::Irq));
+        assert_eq!(interp.cpu.regs.get(14), 0x114);
+        interp.step().unwrap();
+        assert_eq!(interp.cpu.regs.pc(), 0x110);
+
+        // SWI from Thumb, then FIQ in the handler returning with ldm ^
+        interp.cpu.regs.set_cpsr(Mode::Usr.bits() | Psr::T);
+        interp.cpu.regs.set_pc(0x200);
+        assert_eq!(interp.step_with_exceptions(), Some(Exception::Swi));
+        assert_eq!(interp.cpu.regs.get(14), 0x202);
+        interp.cpu.regs.set_banked(Mode::Fiq, 13, 0x800);
+        assert!(interp.interrupt(Exception::Fiq));
+        assert_eq!(interp.cpu.regs.spsr(), Some(Mode::Svc.bits() | Psr::I));
+        interp.step().unwrap();
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.pc(), regs.mode(), regs.get(14)), (0x08, Mode::Svc, 0x202));
+        assert_eq!(regs.get_banked(Mode::Fiq, 13), 0x804);
+        interp.step().unwrap();
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.pc(), regs.cpsr()), (0x202, Mode::Usr.bits() | Psr::T));
+    }
+}
@@ -0,0 +1,244 @@
+//! The ARMv5 virtual memory system: translation table walks and access
+//! permission checks.
+//!
+//! There's no TLB, so every access walks the tables in memory and changes
+//! to them take effect immediately.
+
+use std::fmt;
+
+use super::Bus;
+use super::cp15::*;
+
+/// The kind of a memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Fetch,
+}
+
+/// The cause of an abort, in the order of the fault status encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FaultKind {
+    Alignment,
+    /// An external abort while reading a first- or second-level descriptor.
+    ExternalL1,
+    ExternalL2,
+    TranslationSection,
+    TranslationPage,
+    DomainSection,
+    DomainPage,
+    PermissionSection,
+    PermissionPage,
+}
+impl FaultKind {
+    /// The status field of the FSR.
+    pub fn status(self) -> u32 {
+        match self {
+            FaultKind::Alignment => 0b0001,
+            FaultKind::ExternalL1 => 0b1100,
+            FaultKind::ExternalL2 => 0b1110,
+            FaultKind::TranslationSection => 0b0101,
+            FaultKind::TranslationPage => 0b0111,
+            FaultKind::DomainSection => 0b1001,
+            FaultKind::DomainPage => 0b1011,
+            FaultKind::PermissionSection => 0b1101,
+            FaultKind::PermissionPage => 0b1111,
+        }
+    }
+}
+
+/// A translation fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fault {
+    pub kind: FaultKind,
+    /// The domain of the section or page, where it's known.
+    pub domain: u32,
+    /// The (modified) virtual address being accessed.
+    pub va: u32,
+}
+impl Fault {
+    /// The value recorded in the FSR.
+    pub fn status(&self) -> u32 {
+        (self.domain << 4) | self.kind.status()
+    }
+}
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} fault (domain {}) at 0x{:08x}", self.kind, self.domain, self.va)
+    }
+}
+impl std::error::Error for Fault {}
+
+/// Check an access against the AP bits of a section or page.
+fn permitted(cp15: &Cp15, ap: u32, access: Access, privileged: bool) -> bool {
+    let write = access == Access::Write;
+    let (s, r) = ((cp15.control & CTRL_S) != 0, (cp15.control & CTRL_R) != 0);
+    match ap {
+        0b00 => !write && match (s, r) {
+            (true, false) => privileged,
+            (false, true) => true,
+            _ => false,
+        },
+        0b01 => privileged,
+        0b10 => privileged || !write,
+        _ => true,
+    }
+}
+
+/// Translate a virtual address to a physical address.
+///
+/// The FCSE PID is applied first. With the MMU disabled, addresses are
+/// flat-mapped. Alignment faults aren't checked here, since they depend on
+/// the size of the access.
+pub fn translate<B: Bus>(bus: &mut B, cp15: &Cp15, va: u32, access: Access,
+    privileged: bool) -> Result<u32, Fault>
+{
+    let mva = cp15.mva(va);
+    if !cp15.mmu_enabled() {
+        return Ok(mva);
+    }
+    let fault = |kind, domain| Fault { kind, domain, va: mva };
+
+    let l1_addr = cp15.ttb | ((mva >> 20) << 2);
+    let l1 = bus.read32(l1_addr).map_err(|_| fault(FaultKind::ExternalL1, 0))?;
+    let domain = (l1 >> 5) & 0xf;
+    let (l2_addr, section) = match l1 & 3 {
+        0b00 => return Err(fault(FaultKind::TranslationSection, 0)),
+        0b01 => ((l1 & 0xffff_fc00) | (((mva >> 12) & 0xff) << 2), false),
+        0b11 => ((l1 & 0xffff_f000) | (((mva >> 10) & 0x3ff) << 2), false),
+        _ => (0, true),
+    };
+
+    let (pa, ap) = if section {
+        ((l1 & 0xfff0_0000) | (mva & 0x000f_ffff), (l1 >> 10) & 3)
+    } else {
+        let l2 = bus.read32(l2_addr).map_err(|_| fault(FaultKind::ExternalL2, domain))?;
+        // Large and small pages have four subpages, each with its own AP
+        let subpage = |shift: u32| (l2 >> (4 + ((mva >> shift) & 3) * 2)) & 3;
+        match l2 & 3 {
+            0b01 => ((l2 & 0xffff_0000) | (mva & 0xffff), subpage(14)),
+            0b10 => ((l2 & 0xffff_f000) | (mva & 0xfff), subpage(10)),
+            // Tiny pages only exist in fine tables
+            0b11 if (l1 & 3) == 0b11 => ((l2 & 0xffff_fc00) | (mva & 0x3ff), (l2 >> 4) & 3),
+            _ => return Err(fault(FaultKind::TranslationPage, domain)),
+        }
+    };
+
+    let (domain_fault, perm_fault) = if section {
+        (FaultKind::DomainSection, FaultKind::PermissionSection)
+    } else {
+        (FaultKind::DomainPage, FaultKind::PermissionPage)
+    };
+    match (cp15.dacr >> (domain * 2)) & 3 {
+        // Manager
+        0b11 => Ok(pa),
+        // Client
+        0b01 if permitted(cp15, ap, access, privileged) => Ok(pa),
+        0b01 => Err(fault(perm_fault, domain)),
+        // No access, or reserved
+        _ => Err(fault(domain_fault, domain)),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::Ram;
+
+    /// Page tables at 0x4000 (L1), 0x8000 (coarse) and 0x9000 (fine).
+    fn tables() -> (Ram, Cp15) {
+        let mut ram = Ram::new(0, 0x10000);
+        let mut l1 = |mb: u32, desc: u32| ram.write32(0x4000 + mb * 4, desc).unwrap();
+        l1(0x000, 0x0000_0c02);             // section, flat, AP=11, domain 0
+        l1(0x001, 0x8000_0002 | (1 << 5));  // section, AP=00, domain 1
+        l1(0x002, 0x0000_8001 | (2 << 5));  // coarse, domain 2
+        l1(0x003, 0x0000_9003 | (3 << 5));  // fine, domain 3
+        l1(0x004, 0x0040_0402 | (4 << 5));  // section, AP=01, domain 4
+        l1(0x005, 0x0050_0802 | (5 << 5));  // section, AP=10, domain 5
+        let mut l2 = |addr: u32, desc: u32| ram.write32(addr, desc).unwrap();
+        l2(0x8000, 0x1234_0ff1);            // large page, AP=11 in each subpage
+        l2(0x8004, 0x5678_9ffe);            // small page, AP=11
+        l2(0x8008, 0x0000_1003);            // tiny page, not allowed in coarse
+        l2(0x800c, 0x0000_dd82);            // small page, AP=00,10,01,11
+        l2(0x9000, 0xabcd_e433);            // tiny page, AP=11
+        let mut cp15 = Cp15::new();
+        cp15.ttb = 0x4000;
+        cp15.dacr = 0b11_01_01_01_01_01;
+        cp15.control |= CTRL_M;
+        (ram, cp15)
+    }
+
+    /// Check user reads, user writes and privileged writes to a subpage of
+    /// the small page at 0x0020_3000.
+    fn perms(ram: &mut Ram, cp15: &Cp15, sub: u32) -> [bool; 3] {
+        let va = 0x0020_3000 | (sub << 10);
+        [(Access::Read, false), (Access::Write, false), (Access::Write, true)]
+            .map(|(a, p)| translate(ram, cp15, va, a, p).is_ok())
+    }
+
+    #[test]
+    fn walk() {
+        let (mut ram, mut cp15) = tables();
+        let tr = |ram: &mut Ram, cp15: &Cp15, va, access, privileged| {
+            translate(ram, cp15, va, access, privileged)
+        };
+        assert_eq!(tr(&mut ram, &cp15, 0x0001_2345, Access::Read, false), Ok(0x0001_2345));
+        assert_eq!(tr(&mut ram, &cp15, 0x0020_0abc, Access::Write, false), Ok(0x1234_0abc));
+        assert_eq!(tr(&mut ram, &cp15, 0x0020_1abc, Access::Fetch, false), Ok(0x5678_9abc));
+        assert_eq!(tr(&mut ram, &cp15, 0x0030_0123, Access::Read, false), Ok(0xabcd_e523));
+
+        let fault = |kind, domain, va| Err(Fault { kind, domain, va });
+        assert_eq!(tr(&mut ram, &cp15, 0x0060_0000, Access::Read, true),
+            fault(FaultKind::TranslationSection, 0, 0x0060_0000));
+        assert_eq!(tr(&mut ram, &cp15, 0x0020_2000, Access::Read, true),
+            fault(FaultKind::TranslationPage, 2, 0x0020_2000));
+        assert_eq!(tr(&mut ram, &cp15, 0x0030_0400, Access::Read, true),
+            fault(FaultKind::TranslationPage, 3, 0x0030_0400));
+
+        // Domain 5 is a manager, so its AP bits are ignored
+        assert_eq!(tr(&mut ram, &cp15, 0x0050_0000, Access::Write, false), Ok(0x0050_0000));
+        cp15.dacr &= !(3 << 10);
+        assert_eq!(tr(&mut ram, &cp15, 0x0050_0000, Access::Read, true),
+            fault(FaultKind::DomainSection, 5, 0x0050_0000));
+        cp15.dacr |= 1 << 10;
+        assert_eq!(tr(&mut ram, &cp15, 0x0050_0000, Access::Read, false), Ok(0x0050_0000));
+        assert_eq!(tr(&mut ram, &cp15, 0x0050_0000, Access::Write, false),
+            fault(FaultKind::PermissionSection, 5, 0x0050_0000));
+        assert_eq!(tr(&mut ram, &cp15, 0x0040_0000, Access::Read, false),
+            fault(FaultKind::PermissionSection, 4, 0x0040_0000));
+        assert_eq!(tr(&mut ram, &cp15, 0x0040_0000, Access::Write, true), Ok(0x0040_0000));
+
+        // Subpages of a small page
+        assert_eq!(perms(&mut ram, &cp15, 0), [false, false, false]);
+        assert_eq!(perms(&mut ram, &cp15, 1), [true, false, true]);
+        assert_eq!(perms(&mut ram, &cp15, 2), [false, false, true]);
+        assert_eq!(perms(&mut ram, &cp15, 3), [true, true, true]);
+
+        // AP=00 with the S and R bits
+        cp15.control |= CTRL_S;
+        assert_eq!(perms(&mut ram, &cp15, 0), [false, false, false]);
+        assert!(tr(&mut ram, &cp15, 0x0020_3000, Access::Read, true).is_ok());
+        cp15.control ^= CTRL_S | CTRL_R;
+        assert_eq!(perms(&mut ram, &cp15, 0), [true, false, false]);
+
+        // Disabling the MMU flat-maps everything
+        cp15.control &= !CTRL_M;
+        assert_eq!(tr(&mut ram, &cp15, 0x0060_0000, Access::Write, false), Ok(0x0060_0000));
+
+        assert_eq!(Fault { kind: FaultKind::DomainPage, domain: 3, va: 0 }.status(), 0x3b);
+    }
+
+    #[test]
+    fn external_aborts() {
+        let (mut ram, mut cp15) = tables();
+        cp15.ttb = 0xf000_0000;
+        assert_eq!(translate(&mut ram, &cp15, 0, Access::Read, true).unwrap_err().kind,
+            FaultKind::ExternalL1);
+        cp15.ttb = 0x4000;
+        ram.write32(0x4000, 0x00ff_0001).unwrap();
+        assert_eq!(translate(&mut ram, &cp15, 0, Access::Read, true).unwrap_err().kind,
+            FaultKind::ExternalL2);
+    }
+}
@@ -0,0 +1,699 @@
+//! An ARMv5TE interpreter built on top of the lookup tables.
+//!
+//! The interpreter only models the core: instructions are fetched from and
+//! access memory through a user-supplied [Bus], and anything which would
+//! normally be handled by an exception vector (SWI, BKPT, undefined
+//! instructions and aborts) stops execution with a [Trap], leaving the
+//! register file as it was before the offending instruction. Traps can
+//! also be routed into the [exception] model, which enters the handler at
+//! the vector as the hardware would.
+
+use std::fmt;
+
+use armbf_prim::*;
+
+use crate::inst::*;
+use crate::lut::*;
+
+pub mod psr;
+pub mod regs;
+pub mod alu;
+pub mod arm;
+pub mod thumb;
+pub mod exception;
+pub mod cp15;
+pub mod mmu;
+pub mod softfloat;
+pub mod vfp;
+
+use psr::*;
+use regs::*;
+use exception::Exception;
+use cp15::{Cp15, Maint};
+use vfp::Vfp;
+use mmu::{Access, Fault, FaultKind};
+
+/// Error returned by a [Bus] when an access can't be completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Abort;
+impl fmt::Display for Abort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bus abort")
+    }
+}
+impl std::error::Error for Abort {}
+
+/// The memory system seen by the core.
+///
+/// Addresses are always naturally aligned: the interpreter takes care of
+/// the rotations and masking applied to unaligned loads and stores. Values
+/// are passed in host order, so the bus is responsible for byte order.
+pub trait Bus {
+    fn read8(&mut self, addr: u32) -> Result<u8, Abort>;
+    fn read16(&mut self, addr: u32) -> Result<u16, Abort>;
+    fn read32(&mut self, addr: u32) -> Result<u32, Abort>;
+    fn write8(&mut self, addr: u32, val: u8) -> Result<(), Abort>;
+    fn write16(&mut self, addr: u32, val: u16) -> Result<(), Abort>;
+    fn write32(&mut self, addr: u32, val: u32) -> Result<(), Abort>;
+
+    /// Fetch an ARM instruction (by default, the same as a data read).
+    fn fetch32(&mut self, addr: u32) -> Result<u32, Abort> { self.read32(addr) }
+
+    /// Fetch a Thumb instruction (by default, the same as a data read).
+    fn fetch16(&mut self, addr: u32) -> Result<u16, Abort> { self.read16(addr) }
+}
+
+/// A flat, little-endian RAM at some base address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ram {
+    pub base: u32,
+    pub data: Vec<u8>,
+}
+impl Ram {
+    /// Create a zero-filled RAM.
+    pub fn new(base: u32, size: usize) -> Self {
+        Ram { base, data: vec![0; size] }
+    }
+
+    /// Get the bytes at some address.
+    fn slice(&mut self, addr: u32, len: usize) -> Result<&mut [u8], Abort> {
+        let off = addr.wrapping_sub(self.base) as usize;
+        self.data.get_mut(off..off.checked_add(len).ok_or(Abort)?)
+            .ok_or(Abort)
+    }
+
+    /// Copy some bytes into memory.
+    pub fn load(&mut self, addr: u32, bytes: &[u8]) -> Result<(), Abort> {
+        self.slice(addr, bytes.len())?.copy_from_slice(bytes);
+        Ok(())
+    }
+}
+impl Bus for Ram {
+    fn read8(&mut self, addr: u32) -> Result<u8, Abort> {
+        Ok(self.slice(addr, 1)?[0])
+    }
+    fn read16(&mut self, addr: u32) -> Result<u16, Abort> {
+        let b = self.slice(addr, 2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+    fn read32(&mut self, addr: u32) -> Result<u32, Abort> {
+        let b = self.slice(addr, 4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    fn write8(&mut self, addr: u32, val: u8) -> Result<(), Abort> {
+        self.load(addr, &[val])
+    }
+    fn write16(&mut self, addr: u32, val: u16) -> Result<(), Abort> {
+        self.load(addr, &val.to_le_bytes())
+    }
+    fn write32(&mut self, addr: u32, val: u32) -> Result<(), Abort> {
+        self.load(addr, &val.to_le_bytes())
+    }
+}
+
+
+/// The reason execution stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    /// An undefined instruction. Only CP15 and the VFP (cp10/cp11) are
+    /// attached, so this includes all other coprocessor instructions.
+    Undefined(u32),
+    /// A software interrupt, with its comment field.
+    Swi(u32),
+    /// A breakpoint, with its comment field.
+    Bkpt(u32),
+    /// An instruction fetch from some address was aborted.
+    PrefetchAbort(u32),
+    /// A data access to some address was aborted.
+    DataAbort(u32),
+}
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Trap::Undefined(x) => write!(f, "undefined instruction 0x{:08x}", x),
+            Trap::Swi(imm) => write!(f, "software interrupt #{}", imm),
+            Trap::Bkpt(imm) => write!(f, "breakpoint #{}", imm),
+            Trap::PrefetchAbort(addr) => write!(f, "prefetch abort at 0x{:08x}", addr),
+            Trap::DataAbort(addr) => write!(f, "data abort at 0x{:08x}", addr),
+        }
+    }
+}
+impl std::error::Error for Trap {}
+
+/// The result of executing an instruction.
+pub type Exec = Result<(), Trap>;
+
+
+/// State carried between calls to the execution handlers.
+pub struct Cpu<B: Bus> {
+    pub regs: RegisterFile,
+    pub bus: B,
+    /// The system control coprocessor, which also holds the MMU state.
+    pub cp15: Cp15,
+    /// The VFP coprocessor, which is disabled at reset.
+    pub vfp: Vfp,
+    /// The cache, TLB or power management operation requested by the most
+    /// recent `mcr` which asked for one. None of these are modelled, so
+    /// embedders which want to hook them (e.g. to sleep until an interrupt)
+    /// should take this after each step.
+    pub last_maint: Option<Maint>,
+    /// Always take exceptions at [exception::HIGH_VECTORS], as if the V bit
+    /// were set in the CP15 control register.
+    pub high_vectors: bool,
+    /// Set when the current instruction writes the PC.
+    branched: bool,
+    /// Set while an `ldrt`/`strt` makes a user-mode access.
+    user_access: bool,
+}
+
+impl<B: Bus> Cpu<B> {
+    pub fn new(bus: B) -> Self {
+        Cpu {
+            regs: RegisterFile::new(),
+            bus,
+            cp15: Cp15::new(),
+            vfp: Vfp::new(),
+            last_maint: None,
+            high_vectors: false,
+            branched: false,
+            user_access: false,
+        }
+    }
+
+    /// Read a register as seen by the current instruction, where the PC
+    /// reads as the address of the instruction plus 8 (or 4 in Thumb state).
+    #[inline(always)]
+    pub fn reg(&self, idx: u32) -> u32 {
+        if idx == 15 {
+            let off = if self.regs.thumb() { 4 } else { 8 };
+            self.regs.pc().wrapping_add(off)
+        } else {
+            self.regs.get(idx)
+        }
+    }
+
+    /// Write a register. Writes to the PC branch without changing state.
+    #[inline(always)]
+    pub fn set_reg(&mut self, idx: u32, val: u32) {
+        if idx == 15 {
+            self.branch(val);
+        } else {
+            self.regs.set(idx, val);
+        }
+    }
+
+    /// Branch to some address in the current state.
+    pub fn branch(&mut self, addr: u32) {
+        let mask = if self.regs.thumb() { !1 } else { !3 };
+        self.regs.set_pc(addr & mask);
+        self.branched = true;
+    }
+
+    /// Branch to some address, selecting ARM or Thumb state with bit 0.
+    pub fn branch_exchange(&mut self, addr: u32) {
+        self.regs.set_flag(Psr::T, (addr & 1) != 0);
+        self.branch(addr);
+    }
+
+    /// Take an exception (see [exception::enter]).
+    pub fn raise(&mut self, exc: Exception) {
+        let high = self.high_vectors || self.cp15.high_vectors();
+        exception::enter(&mut self.regs, exc, high);
+    }
+
+    /// Copy the SPSR into the CPSR (when returning from an exception).
+    pub fn restore_spsr(&mut self) {
+        if let Some(spsr) = self.regs.spsr() {
+            self.regs.set_cpsr(spsr);
+        }
+    }
+
+    /// Translate the address of an instruction fetch, recording any fault
+    /// in the IFSR.
+    fn fetch_addr(&mut self, va: u32) -> Result<u32, Trap> {
+        let privileged = self.regs.privileged();
+        mmu::translate(&mut self.bus, &self.cp15, va, Access::Fetch, privileged)
+            .map_err(|fault| {
+                self.cp15.prefetch_fault(&fault);
+                Trap::PrefetchAbort(va)
+            })
+    }
+
+    /// Check the alignment of a data access of some size and translate its
+    /// address, recording any fault in the DFSR and FAR.
+    fn data_addr(&mut self, va: u32, size: u32, access: Access) -> Result<u32, Trap> {
+        let privileged = self.regs.privileged() && !self.user_access;
+        let res = if self.cp15.alignment_checks() && (va & (size - 1)) != 0 {
+            Err(Fault { kind: FaultKind::Alignment, domain: 0, va })
+        } else {
+            mmu::translate(&mut self.bus, &self.cp15, va, access, privileged)
+        };
+        res.map_err(|fault| {
+            self.cp15.data_fault(&fault);
+            Trap::DataAbort(va)
+        })
+    }
+
+    /// Read a word at an aligned address.
+    pub fn read32(&mut self, addr: u32) -> Result<u32, Trap> {
+        let pa = self.data_addr(addr, 4, Access::Read)? & !3;
+        self.bus.read32(pa).map_err(|_| Trap::DataAbort(addr & !3))
+    }
+
+    /// Load a word, rotating unaligned data as ARMv5 does.
+    pub fn load32(&mut self, addr: u32) -> Result<u32, Trap> {
+        Ok(self.read32(addr)?.rotate_right((addr & 3) * 8))
+    }
+
+    /// Load a halfword (ignoring bit 0 of the address).
+    pub fn load16(&mut self, addr: u32) -> Result<u16, Trap> {
+        let pa = self.data_addr(addr, 2, Access::Read)? & !1;
+        self.bus.read16(pa).map_err(|_| Trap::DataAbort(addr & !1))
+    }
+
+    pub fn load8(&mut self, addr: u32) -> Result<u8, Trap> {
+        let pa = self.data_addr(addr, 1, Access::Read)?;
+        self.bus.read8(pa).map_err(|_| Trap::DataAbort(addr))
+    }
+
+    /// Store a word (ignoring bits 1-0 of the address).
+    pub fn store32(&mut self, addr: u32, val: u32) -> Exec {
+        let pa = self.data_addr(addr, 4, Access::Write)? & !3;
+        self.bus.write32(pa, val).map_err(|_| Trap::DataAbort(addr & !3))
+    }
+
+    /// Store a halfword (ignoring bit 0 of the address).
+    pub fn store16(&mut self, addr: u32, val: u16) -> Exec {
+        let pa = self.data_addr(addr, 2, Access::Write)? & !1;
+        self.bus.write16(pa, val).map_err(|_| Trap::DataAbort(addr & !1))
+    }
+
+    pub fn store8(&mut self, addr: u32, val: u8) -> Exec {
+        let pa = self.data_addr(addr, 1, Access::Write)?;
+        self.bus.write8(pa, val).map_err(|_| Trap::DataAbort(addr))
+    }
+
+    /// Make a load or store with user-mode permissions (for `ldrt`/`strt`).
+    pub fn as_user<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.user_access = true;
+        let res = f(self);
+        self.user_access = false;
+        res
+    }
+}
+
+
+/// A map from ThumbInst to some execution handler.
+impl<B: Bus> ThumbHandlers<Exec> for Cpu<B> {
+    fn thumb_handler(inst: ThumbInst) -> ThumbFn<Self, Exec> {
+        match inst {
+            ThumbInst::AddReg1 =>   ThumbFn::DpFmt1(thumb::add_reg1),
+            ThumbInst::SubReg =>    ThumbFn::DpFmt1(thumb::sub_reg),
+            ThumbInst::AddImm1 =>   ThumbFn::DpFmt2(thumb::add_imm1),
+            ThumbInst::SubImm1 =>   ThumbFn::DpFmt2(thumb::sub_imm1),
+            ThumbInst::MovImm =>    ThumbFn::DpFmt3(thumb::dp_fmt3),
+            ThumbInst::CmpImm =>    ThumbFn::DpFmt3(thumb::dp_fmt3),
+            ThumbInst::AddImm2 =>   ThumbFn::DpFmt3(thumb::dp_fmt3),
+            ThumbInst::SubImm2 =>   ThumbFn::DpFmt3(thumb::dp_fmt3),
+            ThumbInst::LslImm =>    ThumbFn::DpFmt4(thumb::dp_fmt4),
+            ThumbInst::LsrImm =>    ThumbFn::DpFmt4(thumb::dp_fmt4),
+            ThumbInst::AsrImm =>    ThumbFn::DpFmt4(thumb::dp_fmt4),
+
+            ThumbInst::AndReg | ThumbInst::EorReg | ThumbInst::LslReg |
+            ThumbInst::LsrReg | ThumbInst::AsrReg | ThumbInst::AdcReg |
+            ThumbInst::SbcReg | ThumbInst::RorReg | ThumbInst::TstReg |
+            ThumbInst::RsbImm | ThumbInst::CmpReg1 | ThumbInst::CmnReg |
+            ThumbInst::OrrReg | ThumbInst::MulReg | ThumbInst::BicReg |
+            ThumbInst::MvnReg => ThumbFn::DpFmt5(thumb::dp_fmt5),
+
+            ThumbInst::AddImmPc =>  ThumbFn::DpFmt6(thumb::dp_fmt6),
+            ThumbInst::AddImmSp =>  ThumbFn::DpFmt6(thumb::dp_fmt6),
+            ThumbInst::AddImmSp7 => ThumbFn::DpFmt7(thumb::dp_fmt7),
+            ThumbInst::SubImmSp7 => ThumbFn::DpFmt7(thumb::dp_fmt7),
+            ThumbInst::AddReg2 =>   ThumbFn::DpSpecial(thumb::dp_special),
+            ThumbInst::CmpReg2 =>   ThumbFn::DpSpecial(thumb::dp_special),
+            ThumbInst::MovReg =>    ThumbFn::DpSpecial(thumb::dp_special),
+
+            ThumbInst::Bx =>        ThumbFn::BranchExchange(thumb::bx),
+            ThumbInst::BlxReg =>    ThumbFn::BranchExchange(thumb::blx_reg),
+            ThumbInst::BranchCond =>   ThumbFn::CondBranch(thumb::b_cond),
+            ThumbInst::BranchUncond => ThumbFn::BranchLink(thumb::b),
+            ThumbInst::BlPrefix =>  ThumbFn::BranchLink(thumb::bl_prefix),
+            ThumbInst::Bl =>        ThumbFn::BranchLink(thumb::bl),
+            ThumbInst::Blx =>       ThumbFn::BranchLink(thumb::blx),
+
+            ThumbInst::LdrImm1 =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::StrImm1 =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::LdrbImm =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::StrbImm =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::LdrhImm =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::StrhImm =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+
+            ThumbInst::LdrReg | ThumbInst::StrReg | ThumbInst::LdrbReg |
+            ThumbInst::StrbReg | ThumbInst::LdrhReg | ThumbInst::StrhReg |
+            ThumbInst::LdrsbReg | ThumbInst::LdrshReg =>
+                ThumbFn::LsFmt2(thumb::ls_fmt2),
+
+            ThumbInst::LdrLit =>    ThumbFn::LdrLit(thumb::ldr_lit),
+            ThumbInst::LdrImm2 =>   ThumbFn::LsFmt4(thumb::ls_fmt4),
+            ThumbInst::StrImm2 =>   ThumbFn::LsFmt4(thumb::ls_fmt4),
+            ThumbInst::Ldmia =>     ThumbFn::LsMulti(thumb::ldmia),
+            ThumbInst::Stmia =>     ThumbFn::LsMulti(thumb::stmia),
+            ThumbInst::Push =>      ThumbFn::PushPop(thumb::push_pop),
+            ThumbInst::Pop =>       ThumbFn::PushPop(thumb::push_pop),
+
+            ThumbInst::Swi =>       ThumbFn::Excep(thumb::swi),
+            ThumbInst::Bkpt =>      ThumbFn::Excep(thumb::bkpt),
+
+            ThumbInst::AddImmPc7 | ThumbInst::None => ThumbFn::Raw(thumb::undef),
+        }
+    }
+}
+
+
+/// A map from ArmInst to some execution handler.
+impl<B: Bus> ArmHandlers<Exec> for Cpu<B> {
+    fn arm_handler(inst: ArmInst) -> ArmFn<Self, Exec> {
+        match inst {
+            ArmInst::MsrReg =>      ArmFn::Status(arm::msr_reg),
+            ArmInst::MsrImm =>      ArmFn::Status(arm::msr_imm),
+            ArmInst::Mrs =>         ArmFn::Status(arm::mrs),
+            ArmInst::Swi =>         ArmFn::Swi(arm::swi),
+            ArmInst::Bkpt =>        ArmFn::Bkpt(arm::bkpt),
+            ArmInst::Clz =>         ArmFn::Clz(arm::clz),
+            ArmInst::Qadd =>        ArmFn::Sat(arm::qadd),
+            ArmInst::Qsub =>        ArmFn::Sat(arm::qsub),
+            ArmInst::QdAdd =>       ArmFn::Sat(arm::qdadd),
+            ArmInst::QdSub =>       ArmFn::Sat(arm::qdsub),
+
+            ArmInst::Mrc =>         ArmFn::Coproc(arm::mrc),
+            ArmInst::Mcr =>         ArmFn::Coproc(arm::mcr),
+            ArmInst::Mrrc | ArmInst::Mcrr | ArmInst::Ldc | ArmInst::Stc |
+            ArmInst::Cdp | ArmInst::Mrc2 | ArmInst::Mcr2 | ArmInst::Ldc2 |
+            ArmInst::Stc2 | ArmInst::Cdp2 => ArmFn::Coproc(arm::coproc),
+
+            ArmInst::B =>           ArmFn::Branch(arm::b),
+            ArmInst::Bl =>          ArmFn::Branch(arm::bl),
+            ArmInst::Bx =>          ArmFn::Bx(arm::bx),
+            ArmInst::BlxReg =>      ArmFn::Bx(arm::blx_reg),
+            ArmInst::BlxImm =>      ArmFn::Branch(arm::blx_imm),
+
+            ArmInst::LdrsbReg | ArmInst::LdrshReg | ArmInst::LdrsbImm |
+            ArmInst::LdrshImm | ArmInst::StrdReg | ArmInst::LdrdReg |
+            ArmInst::StrdImm | ArmInst::LdrdImm | ArmInst::StrhImm |
+            ArmInst::LdrhImm | ArmInst::StrhReg | ArmInst::LdrhReg =>
+                ArmFn::LsMisc(arm::ls_misc),
+
+            ArmInst::Stmia =>       ArmFn::LsMulti(arm::stmia),
+            ArmInst::Stmib =>       ArmFn::LsMulti(arm::stmib),
+            ArmInst::Stmda =>       ArmFn::LsMulti(arm::stmda),
+            ArmInst::Stmdb =>       ArmFn::LsMulti(arm::stmdb),
+            ArmInst::Ldmia =>       ArmFn::LsMulti(arm::ldmia),
+            ArmInst::Ldmib =>       ArmFn::LsMulti(arm::ldmib),
+            ArmInst::Ldmda =>       ArmFn::LsMulti(arm::ldmda),
+            ArmInst::Ldmdb =>       ArmFn::LsMulti(arm::ldmdb),
+
+            ArmInst::StrImm | ArmInst::LdrImm |
+            ArmInst::StrbImm | ArmInst::LdrbImm => ArmFn::LsImm(arm::ls_imm),
+            ArmInst::StrReg | ArmInst::LdrReg |
+            ArmInst::StrbReg | ArmInst::LdrbReg => ArmFn::LsShift(arm::ls_reg),
+            ArmInst::Swp =>         ArmFn::Swp(arm::swp),
+            ArmInst::Swpb =>        ArmFn::Swp(arm::swpb),
+
+            ArmInst::Mul =>         ArmFn::Mul(arm::mul),
+            ArmInst::Mla =>         ArmFn::Mul(arm::mla),
+            ArmInst::Umull =>       ArmFn::Mul(arm::umull),
+            ArmInst::Umlal =>       ArmFn::Mul(arm::umlal),
+            ArmInst::Smull =>       ArmFn::Mul(arm::smull),
+            ArmInst::Smlal =>       ArmFn::Mul(arm::smlal),
+            ArmInst::SmlaXy =>      ArmFn::Mul(arm::smla_xy),
+            ArmInst::SmulwY =>      ArmFn::Mul(arm::smulw_y),
+            ArmInst::SmlawY =>      ArmFn::Mul(arm::smlaw_y),
+            ArmInst::SmlalXy =>     ArmFn::Mul(arm::smlal_xy),
+            ArmInst::SmulXy =>      ArmFn::Mul(arm::smul_xy),
+
+            ArmInst::AndRotImm | ArmInst::EorRotImm | ArmInst::SubRotImm |
+            ArmInst::RsbRotImm | ArmInst::AddRotImm | ArmInst::AdcRotImm |
+            ArmInst::SbcRotImm | ArmInst::RscRotImm | ArmInst::OrrRotImm |
+            ArmInst::BicRotImm | ArmInst::TstRotImm | ArmInst::TeqRotImm |
+            ArmInst::CmpRotImm | ArmInst::CmnRotImm | ArmInst::MovRotImm |
+            ArmInst::MvnRotImm => ArmFn::DpRotImm(arm::dp_rot_imm),
+
+            ArmInst::AndShiftImm | ArmInst::EorShiftImm |
+            ArmInst::SubShiftImm | ArmInst::RsbShiftImm |
+            ArmInst::AddShiftImm | ArmInst::AdcShiftImm |
+            ArmInst::SbcShiftImm | ArmInst::RscShiftImm |
+            ArmInst::OrrShiftImm | ArmInst::BicShiftImm |
+            ArmInst::TstShiftImm | ArmInst::TeqShiftImm |
+            ArmInst::CmpShiftImm | ArmInst::CmnShiftImm |
+            ArmInst::MovShiftImm | ArmInst::MvnShiftImm =>
+                ArmFn::DpShift(arm::dp_shift_imm),
+
+            ArmInst::AndShiftReg | ArmInst::EorShiftReg |
+            ArmInst::SubShiftReg | ArmInst::RsbShiftReg |
+            ArmInst::AddShiftReg | ArmInst::AdcShiftReg |
+            ArmInst::SbcShiftReg | ArmInst::RscShiftReg |
+            ArmInst::OrrShiftReg | ArmInst::BicShiftReg |
+            ArmInst::TstShiftReg | ArmInst::TeqShiftReg |
+            ArmInst::CmpShiftReg | ArmInst::CmnShiftReg |
+            ArmInst::MovShiftReg | ArmInst::MvnShiftReg =>
+                ArmFn::DpShift(arm::dp_shift_reg),
+
+            ArmInst::None =>        ArmFn::Raw(arm::undef),
+        }
+    }
+}
+
+
+/// Executes instructions.
+///
+/// Like the [crate::disas::Disassembler], this holds a lookup table for
+/// each instruction set, so it should be built once and reused.
+pub struct Interpreter<B: Bus> {
+    arm: ArmLut<ArmFn<Cpu<B>, Exec>, ArmCondIndex>,
+    thumb: ThumbLut<ThumbFn<Cpu<B>, Exec>>,
+    pub cpu: Cpu<B>,
+}
+
+impl<B: Bus> Interpreter<B> {
+    pub fn new(bus: B) -> Self {
+        Interpreter {
+            arm: ArmLut::new(),
+            thumb: ThumbLut::new(),
+            cpu: Cpu::new(bus),
+        }
+    }
+
+    /// Execute a single instruction.
+    ///
+    /// On a [Trap], the PC is left pointing at the offending instruction.
+    pub fn step(&mut self) -> Exec {
+        let cpu = &mut self.cpu;
+        let pc = cpu.regs.pc();
+        if cpu.regs.thumb() {
+            let pa = cpu.fetch_addr(pc)?;
+            let x = cpu.bus.fetch16(pa).map_err(|_| Trap::PrefetchAbort(pc))?;
+            cpu.branched = false;
+            self.thumb.dispatch(cpu, x)?;
+            if !cpu.branched {
+                cpu.regs.set_pc(pc.wrapping_add(2));
+            }
+            return Ok(());
+        }
+
+        let pa = cpu.fetch_addr(pc)?;
+        let x = cpu.bus.fetch32(pa).map_err(|_| Trap::PrefetchAbort(pc))?;
+        let cond = get_cond!(x);
+        if cond == 0b1111 {
+            // Only BLX(1) and PLD are defined in the unconditional space,
+            // and PLD is a hint.
+            if (x & 0x0d70_f000) == 0x0550_f000 {
+                cpu.regs.set_pc(pc.wrapping_add(4));
+                return Ok(());
+            }
+            if (x & 0x0e00_0000) != 0x0a00_0000 {
+                return Err(Trap::Undefined(x));
+            }
+        } else if !alu::cond_passed(cond, cpu.regs.cpsr()) {
+            cpu.regs.set_pc(pc.wrapping_add(4));
+            return Ok(());
+        }
+
+        cpu.branched = false;
+        self.arm.dispatch(cpu, x)?;
+        if !cpu.branched {
+            cpu.regs.set_pc(pc.wrapping_add(4));
+        }
+        Ok(())
+    }
+
+    /// Execute a single instruction, taking the matching exception if it
+    /// traps. Returns the exception taken, if any.
+    pub fn step_with_exceptions(&mut self) -> Option<Exception> {
+        let exc = Exception::from(self.step().err()?);
+        self.cpu.raise(exc);
+        Some(exc)
+    }
+
+    /// Signal an interrupt (or any other exception) between instructions.
+    /// Returns false if it's masked by the CPSR.
+    pub fn interrupt(&mut self, exc: Exception) -> bool {
+        if exc.masked(self.cpu.regs.psr()) {
+            return false;
+        }
+        self.cpu.raise(exc);
+        true
+    }
+
+    /// Execute instructions until the PC reaches some address, or until some
+    /// number of instructions have been executed. Returns the number of
+    /// instructions executed.
+    pub fn run_until(&mut self, addr: u32, limit: usize) -> Result<usize, Trap> {
+        let mut count = 0;
+        while count < limit && self.cpu.regs.pc() != addr {
+            self.step()?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Load some ARM code at zero, set up some registers and run until the
+    /// code falls off the end.
+    pub(crate) fn exec(regs: &[(u32, u32)], code: &[u32]) -> Interpreter<Ram> {
+        let mut ram = Ram::new(0, 0x1000);
+        for (i, x) in code.iter().enumerate() {
+            ram.write32(i as u32 * 4, *x).unwrap();
+        }
+        let mut interp = Interpreter::new(ram);
+        for (idx, val) in regs {
+            interp.cpu.regs.set(*idx, *val);
+        }
+        let end = code.len() as u32 * 4;
+        interp.run_until(end, 1000).unwrap();
+        interp
+    }
+
+    #[test]
+    fn loop_and_conditions() {
+        // Sum 1..=10
+        let interp = exec(&[], &[
+            0xe3a0000a, // mov   r0, #10
+            0xe3a01000, // mov   r1, #0
+            0xe0811000, // add   r1, r1, r0
+            0xe2500001, // subs  r0, r0, #1
+            0x1afffffc, // bne   0x8
+            0x03a02001, // moveq r2, #1
+            0x13a03001, // movne r3, #1
+        ]);
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(0), regs.get(1)), (0, 55));
+        assert_eq!((regs.get(2), regs.get(3)), (1, 0));
+        assert!(regs.flag(Psr::Z) && regs.flag(Psr::C));
+    }
+
+    #[test]
+    fn traps() {
+        let mut ram = Ram::new(0, 0x20);
+        ram.write32(0, 0xef000011).unwrap(); // svc   #17
+        ram.write32(4, 0xe1200073).unwrap(); // bkpt  #3
+        ram.write32(8, 0xee110e10).unwrap(); // mrc   p14, #0, r0, c1, c0, #0
+        ram.write32(12, 0xe5910100).unwrap(); // ldr   r0, [r1, #256]
+        let mut interp = Interpreter::new(ram);
+        assert_eq!(interp.step(), Err(Trap::Swi(17)));
+        assert_eq!(interp.cpu.regs.pc(), 0);
+        let mut expect = |pc, trap| {
+            interp.cpu.regs.set_pc(pc);
+            assert_eq!(interp.step(), Err(trap));
+            assert_eq!(interp.cpu.regs.pc(), pc);
+        };
+        expect(4, Trap::Bkpt(3));
+        expect(8, Trap::Undefined(0xee110e10));
+        expect(12, Trap::DataAbort(0x100));
+        expect(0x20, Trap::PrefetchAbort(0x20));
+    }
+
+    #[test]
+    fn maintenance() {
+        let mut interp = exec(&[(1, 0x8000)], &[
+            0xee071f3a, // mcr   p15, #0, r1, c7, c10, #1
+            0xe3a00000, // mov   r0, #0
+        ]);
+        assert_eq!(interp.cpu.last_maint.take(), Some(Maint::CleanDCache(0x8000)));
+        let mut interp = exec(&[], &[
+            0xee070f90, // mcr   p15, #0, r0, c7, c0, #4
+        ]);
+        assert_eq!(interp.cpu.last_maint.take(), Some(Maint::WaitForInterrupt));
+        assert_eq!(interp.cpu.regs.pc(), 4);
+    }
+
+    #[test]
+    fn system_control() {
+        let mut ram = Ram::new(0, 0x10000);
+        // A flat section at zero, for privileged access only
+        ram.write32(0x4000, 0x0000_0402).unwrap();
+        let code = [
+            0xee070f15, // mcr   p15, #0, r0, c7, c5, #0
+            0xee021f10, // mcr   p15, #0, r1, c2, c0, #0
+            0xee153f10, // mrc   p15, #0, r3, c5, c0, #0
+            0xee032f10, // mcr   p15, #0, r2, c3, c0, #0
+            0xee014f10, // mcr   p15, #0, r4, c1, c0, #0
+            0xe5965000, // ldr   r5, [r6]
+            0xe4b75000, // ldrt  r5, [r7], #0
+            0xee168f10, // mrc   p15, #0, r8, c6, c0, #0
+            0xee17ff7e, // mrc   p15, #0, pc, c7, c14, #3
+            0xee100f10, // mrc   p15, #0, r0, c0, c0, #0
+        ];
+        for (i, x) in code.iter().enumerate() {
+            ram.write32(i as u32 * 4, *x).unwrap();
+        }
+        let mut interp = Interpreter::new(ram);
+        for (idx, val) in [(1, 0x4000), (2, 1), (3, 0xff), (4, 1), (6, 0x10_0000), (7, 0x800)] {
+            interp.cpu.regs.set(idx, val);
+        }
+        interp.run_until(0x14, 5).unwrap();
+        let cpu = &interp.cpu;
+        assert_eq!(cpu.regs.get(3), 0);
+        assert_eq!((cpu.cp15.ttb, cpu.cp15.dacr), (0x4000, 1));
+        assert!(cpu.cp15.mmu_enabled());
+
+        // Unmapped, then a user access to a privileged page
+        assert_eq!(interp.step(), Err(Trap::DataAbort(0x10_0000)));
+        assert_eq!((interp.cpu.cp15.dfsr, interp.cpu.cp15.far), (0x05, 0x10_0000));
+        interp.cpu.regs.set_pc(0x18);
+        assert_eq!(interp.step(), Err(Trap::DataAbort(0x800)));
+        assert_eq!((interp.cpu.cp15.dfsr, interp.cpu.cp15.far), (0x0d, 0x800));
+
+        interp.cpu.regs.set_pc(0x1c);
+        interp.run_until(0x28, 3).unwrap();
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(8), regs.get(0)), (0x800, cp15::ID_ARM926));
+        assert_eq!(regs.cpsr() >> 28, 0b0100);
+
+        // User mode can't fetch from the page, or access CP15 at all
+        interp.cpu.regs.set_cpsr(Mode::Usr.bits());
+        interp.cpu.regs.set_pc(0x24);
+        assert_eq!(interp.step(), Err(Trap::PrefetchAbort(0x24)));
+        assert_eq!(interp.cpu.cp15.ifsr, 0x0d);
+        interp.cpu.cp15.control &= !cp15::CTRL_M;
+        assert_eq!(interp.step(), Err(Trap::Undefined(0xee100f10)));
+
+        // Alignment checking, and high vectors
+        interp.cpu.cp15.control |= cp15::CTRL_A | cp15::CTRL_V;
+        interp.cpu.regs.set(6, 0x802);
+        interp.cpu.regs.set_pc(0x14);
+        assert_eq!(interp.step_with_exceptions(), Some(Exception::DataAbort));
+        assert_eq!((interp.cpu.cp15.dfsr, interp.cpu.cp15.far), (0x01, 0x802));
+        assert_eq!(interp.cpu.regs.pc(), 0xffff_0010);
+        interp.cpu.cp15.control &= !cp15::CTRL_V;
+        interp.cpu.high_vectors = true;
+        interp.cpu.regs.set_pc(0x14);
+        assert_eq!(interp.step_with_exceptions(), Some(Exception::DataAbort));
+        assert_eq!(interp.cpu.regs.pc(), 0xffff_0010);
+    }
+}
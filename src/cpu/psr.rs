@@ -0,0 +1,214 @@
+//! Processor modes and program status registers.
+
+use std::fmt;
+
+/// A processor mode.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Usr = 0b10000,
+    Fiq = 0b10001,
+    Irq = 0b10010,
+    Svc = 0b10011,
+    Abt = 0b10111,
+    Und = 0b11011,
+    Sys = 0b11111,
+}
+impl Mode {
+    pub const ALL: [Mode; 7] = [
+        Mode::Usr, Mode::Fiq, Mode::Irq, Mode::Svc,
+        Mode::Abt, Mode::Und, Mode::Sys,
+    ];
+
+    /// Decode the mode field of a PSR. Returns [None] for the reserved
+    /// encodings (including the 26-bit modes, which ARMv5 doesn't have).
+    pub fn from_bits(bits: u32) -> Option<Mode> {
+        Mode::ALL.iter().copied().find(|m| *m as u32 == bits & Psr::MODE)
+    }
+
+    /// Returns true if the mode field of a PSR holds a valid mode.
+    pub fn is_valid(bits: u32) -> bool { Mode::from_bits(bits).is_some() }
+
+    pub fn bits(self) -> u32 { self as u32 }
+
+    /// Returns true for every mode except user mode.
+    pub fn privileged(self) -> bool { self != Mode::Usr }
+
+    /// Returns true for the exception modes, which have an SPSR (and their
+    /// own r13/r14).
+    pub fn has_spsr(self) -> bool { !matches!(self, Mode::Usr | Mode::Sys) }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Usr => "usr",
+            Mode::Fiq => "fiq",
+            Mode::Irq => "irq",
+            Mode::Svc => "svc",
+            Mode::Abt => "abt",
+            Mode::Und => "und",
+            Mode::Sys => "sys",
+        }
+    }
+}
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Expand the field mask of an MSR instruction (bits 19-16, one bit for each
+/// of the c, x, s and f bytes) into a mask over the PSR.
+pub fn field_bytes(field_mask: u32) -> u32 {
+    (0..4).filter(|i| (field_mask & (1 << i)) != 0)
+        .fold(0, |acc, i| acc | (0xff << (i * 8)))
+}
+
+macro_rules! psr_flags {
+    ($($get:ident, $set:ident, $mask:expr;)*) => { $(
+        #[inline(always)]
+        pub fn $get(self) -> bool { (self.0 & $mask) != 0 }
+        #[inline(always)]
+        pub fn $set(&mut self, val: bool) {
+            if val { self.0 |= $mask; } else { self.0 &= !$mask; }
+        }
+    )* }
+}
+
+/// The contents of the CPSR or an SPSR.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Psr(pub u32);
+impl Psr {
+    pub const N: u32 = 1 << 31;
+    pub const Z: u32 = 1 << 30;
+    pub const C: u32 = 1 << 29;
+    pub const V: u32 = 1 << 28;
+    pub const Q: u32 = 1 << 27;
+    pub const I: u32 = 1 << 7;
+    pub const F: u32 = 1 << 6;
+    pub const T: u32 = 1 << 5;
+    /// All of the single-bit flags above.
+    pub const FLAGS: u32 = Psr::N | Psr::Z | Psr::C | Psr::V | Psr::Q
+        | Psr::I | Psr::F | Psr::T;
+    /// The mode field.
+    pub const MODE: u32 = 0b11111;
+
+    /// A PSR in some mode, with every other bit clear.
+    pub fn new(mode: Mode) -> Self { Psr(mode.bits()) }
+
+    psr_flags! {
+        n, set_n, Psr::N;
+        z, set_z, Psr::Z;
+        c, set_c, Psr::C;
+        v, set_v, Psr::V;
+        q, set_q, Psr::Q;
+        i, set_i, Psr::I;
+        f, set_f, Psr::F;
+        t, set_t, Psr::T;
+    }
+
+    /// The mode, or [None] if the mode field is reserved.
+    pub fn mode(self) -> Option<Mode> { Mode::from_bits(self.0) }
+
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.0 = (self.0 & !Psr::MODE) | mode.bits();
+    }
+
+    /// The N, Z, C and V flags (in that order, from bit 3 down).
+    pub fn nzcv(self) -> u32 { self.0 >> 28 }
+
+    /// The result of `msr cpsr_<fields>, val` executed with this PSR as the
+    /// CPSR.
+    ///
+    /// Only the flags byte is writable in user mode, and the T bit can only
+    /// be changed by interworking branches. Writing a reserved mode is
+    /// unpredictable; here the mode is left unchanged.
+    pub fn msr_cpsr(self, val: u32, field_mask: u32) -> Psr {
+        let privileged = self.mode().is_some_and(Mode::privileged);
+        let mut mask = field_bytes(field_mask);
+        mask &= if privileged { !Psr::T } else { 0xff00_0000 };
+        if !Mode::is_valid(val) {
+            mask &= !Psr::MODE;
+        }
+        Psr((self.0 & !mask) | (val & mask))
+    }
+
+    /// The result of `msr spsr_<fields>, val` with this PSR as the SPSR.
+    /// Every bit of the selected fields is written.
+    pub fn msr_spsr(self, val: u32, field_mask: u32) -> Psr {
+        let mask = field_bytes(field_mask);
+        Psr((self.0 & !mask) | (val & mask))
+    }
+}
+impl From<u32> for Psr {
+    fn from(x: u32) -> Self { Psr(x) }
+}
+impl From<Psr> for u32 {
+    fn from(x: Psr) -> Self { x.0 }
+}
+
+/// Formats like `nZCvq IfT svc`, with set flags in upper case.
+impl fmt::Display for Psr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flag = |set: bool, c: char| {
+            if set { c.to_ascii_uppercase() } else { c }
+        };
+        write!(f, "{}{}{}{}{} {}{}{} ",
+            flag(self.n(), 'n'), flag(self.z(), 'z'), flag(self.c(), 'c'),
+            flag(self.v(), 'v'), flag(self.q(), 'q'),
+            flag(self.i(), 'i'), flag(self.f(), 'f'), flag(self.t(), 't'))?;
+        match self.mode() {
+            Some(mode) => write!(f, "{}", mode),
+            None => write!(f, "{:#07b}", self.0 & Psr::MODE),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        assert_eq!(Mode::from_bits(0x6000_00d3), Some(Mode::Svc));
+        assert_eq!(Mode::from_bits(0b00011), None);
+        assert_eq!(Mode::from_bits(0b10100), None);
+        assert!(Mode::is_valid(Mode::Sys.bits()) && !Mode::is_valid(0));
+        assert!(Mode::Sys.privileged() && !Mode::Usr.privileged());
+        assert!(Mode::Und.has_spsr() && !Mode::Sys.has_spsr());
+        assert_eq!(Mode::Abt.to_string(), "abt");
+    }
+
+    #[test]
+    fn fields() {
+        let mut psr = Psr::new(Mode::Irq);
+        psr.set_c(true);
+        psr.set_i(true);
+        assert!(psr.c() && psr.i() && !psr.z() && !psr.t());
+        assert_eq!(psr.0, Psr::C | Psr::I | Mode::Irq.bits());
+        assert_eq!(psr.nzcv(), 0b0010);
+        psr.set_mode(Mode::Fiq);
+        assert_eq!(psr.mode(), Some(Mode::Fiq));
+        assert_eq!(psr.to_string(), "nzCvq Ift fiq");
+        assert_eq!(Psr(Psr::T | 0b00100).to_string(), "nzcvq ifT 0b00100");
+        assert_eq!(Psr(0).mode(), None);
+    }
+
+    #[test]
+    fn msr() {
+        assert_eq!(field_bytes(0b1001), 0xff00_00ff);
+        let svc = Psr(Mode::Svc.bits() | Psr::I | Psr::F);
+        // cpsr_fc: flags and mode, but never T
+        assert_eq!(svc.msr_cpsr(0xf000_0030, 0b1001), Psr(0xf000_0010));
+        // cpsr_f leaves the control byte alone
+        assert_eq!(svc.msr_cpsr(0x8000_0010, 0b1000), Psr(0x8000_00d3));
+        // a reserved mode isn't written
+        assert_eq!(svc.msr_cpsr(0x0000_0000, 0b0001), Psr(Mode::Svc.bits()));
+        // user mode can only write the flags
+        let usr = Psr(Mode::Usr.bits());
+        assert_eq!(usr.msr_cpsr(0x4000_00d3, 0b1111), Psr(0x4000_0010));
+        // the SPSR takes anything
+        assert_eq!(usr.msr_spsr(0x1234_5678, 0b0110), Psr(0x0034_5610));
+    }
+}
@@ -0,0 +1,243 @@
+//! The register file, with banked registers for each processor mode.
+
+use super::psr::*;
+
+/// Index of the bank holding r13/r14 (and the SPSR) for some mode. User and
+/// system mode share bank 0, which has no SPSR.
+fn bank(mode: Mode) -> usize {
+    match mode {
+        Mode::Usr | Mode::Sys => 0,
+        Mode::Fiq => 1,
+        Mode::Irq => 2,
+        Mode::Svc => 3,
+        Mode::Abt => 4,
+        Mode::Und => 5,
+    }
+}
+
+/// The ARM register file.
+///
+/// `r` always holds the registers visible in the current mode. The others
+/// are kept in the banks and swapped in whenever the mode changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterFile {
+    r: [u32; 16],
+    /// Always holds a valid mode.
+    cpsr: Psr,
+    /// r13 and r14 for each bank.
+    banked: [[u32; 2]; 6],
+    /// r8-r12 for FIQ mode (0) and every other mode (1).
+    fiq: [[u32; 5]; 2],
+    /// The SPSR for each bank (unused for bank 0).
+    spsr: [u32; 6],
+}
+
+impl Default for RegisterFile {
+    fn default() -> Self { Self::new() }
+}
+
+impl RegisterFile {
+    /// Create a register file in the reset state (supervisor mode, ARM
+    /// state, interrupts masked, PC at zero).
+    pub fn new() -> Self {
+        RegisterFile {
+            r: [0; 16],
+            cpsr: Psr(Mode::Svc.bits() | Psr::I | Psr::F),
+            banked: [[0; 2]; 6],
+            fiq: [[0; 5]; 2],
+            spsr: [0; 6],
+        }
+    }
+
+    /// Read a register in the current mode. The PC reads as the address of
+    /// the current instruction (see [crate::cpu::Cpu::reg] for the value
+    /// seen by executing code).
+    #[inline(always)]
+    pub fn get(&self, idx: u32) -> u32 { self.r[idx as usize] }
+
+    /// Write a register in the current mode.
+    #[inline(always)]
+    pub fn set(&mut self, idx: u32, val: u32) { self.r[idx as usize] = val; }
+
+    pub fn pc(&self) -> u32 { self.r[15] }
+    pub fn set_pc(&mut self, val: u32) { self.r[15] = val; }
+
+    pub fn cpsr(&self) -> u32 { self.cpsr.0 }
+    pub fn psr(&self) -> Psr { self.cpsr }
+    pub fn thumb(&self) -> bool { self.cpsr.t() }
+
+    /// The current mode.
+    pub fn mode(&self) -> Mode {
+        self.cpsr.mode().expect("register file holds a valid mode")
+    }
+
+    /// Returns true when running in a privileged mode.
+    pub fn privileged(&self) -> bool { self.mode().privileged() }
+
+    /// Write the CPSR, switching register banks if the mode changes.
+    ///
+    /// Writing a reserved mode is unpredictable on real hardware; here the
+    /// mode is left unchanged and only the other bits are written.
+    pub fn set_cpsr(&mut self, val: u32) {
+        let mode = Mode::from_bits(val).unwrap_or_else(|| self.mode());
+        self.switch_mode(mode);
+        self.cpsr = Psr((val & !Psr::MODE) | mode.bits());
+    }
+
+    /// Write the CPSR from a [Psr].
+    pub fn set_psr(&mut self, psr: Psr) { self.set_cpsr(psr.0) }
+
+    /// Switch to some mode, leaving the rest of the CPSR unchanged.
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.switch_mode(mode);
+        self.cpsr.set_mode(mode);
+    }
+
+    /// Returns true if some flag (i.e. [Psr::C]) is set.
+    #[inline(always)]
+    pub fn flag(&self, mask: u32) -> bool { (self.cpsr.0 & mask) != 0 }
+
+    /// Set or clear some flags. Only the bits in [Psr::FLAGS] are written,
+    /// so the mode can't be changed this way (see [Self::set_mode]).
+    #[inline(always)]
+    pub fn set_flag(&mut self, mask: u32, val: bool) {
+        debug_assert!((mask & !Psr::FLAGS) == 0, "not a PSR flag: {:#x}", mask);
+        let mask = mask & Psr::FLAGS;
+        if val { self.cpsr.0 |= mask; } else { self.cpsr.0 &= !mask; }
+    }
+
+    /// Set the N and Z flags from some result.
+    pub fn set_nz(&mut self, res: u32) {
+        self.set_flag(Psr::N, (res as i32) < 0);
+        self.set_flag(Psr::Z, res == 0);
+    }
+
+    /// The SPSR of the current mode, if it has one.
+    pub fn spsr(&self) -> Option<u32> { self.spsr_of(self.mode()) }
+
+    /// Write the SPSR of the current mode. Does nothing in user and system
+    /// mode.
+    pub fn set_spsr(&mut self, val: u32) { self.set_spsr_of(self.mode(), val) }
+
+    /// The SPSR of some mode, if it has one.
+    pub fn spsr_of(&self, mode: Mode) -> Option<u32> {
+        match bank(mode) {
+            0 => None,
+            b => Some(self.spsr[b]),
+        }
+    }
+
+    /// Write the SPSR of some mode. Does nothing for user and system mode.
+    pub fn set_spsr_of(&mut self, mode: Mode, val: u32) {
+        let b = bank(mode);
+        if b != 0 {
+            self.spsr[b] = val;
+        }
+    }
+
+    /// Read a register as seen from some mode, regardless of the current
+    /// mode.
+    pub fn get_banked(&self, mode: Mode, idx: u32) -> u32 {
+        let cur = self.mode();
+        let i = idx as usize;
+        match idx {
+            8..=12 if (cur == Mode::Fiq) != (mode == Mode::Fiq) => {
+                self.fiq[(mode != Mode::Fiq) as usize][i - 8]
+            },
+            13 | 14 if bank(cur) != bank(mode) => self.banked[bank(mode)][i - 13],
+            _ => self.r[i],
+        }
+    }
+
+    /// Write a register as seen from some mode, regardless of the current
+    /// mode.
+    pub fn set_banked(&mut self, mode: Mode, idx: u32, val: u32) {
+        let cur = self.mode();
+        let i = idx as usize;
+        match idx {
+            8..=12 if (cur == Mode::Fiq) != (mode == Mode::Fiq) => {
+                self.fiq[(mode != Mode::Fiq) as usize][i - 8] = val
+            },
+            13 | 14 if bank(cur) != bank(mode) => {
+                self.banked[bank(mode)][i - 13] = val
+            },
+            _ => self.r[i] = val,
+        }
+    }
+
+    /// Read a user-mode register, regardless of the current mode (for
+    /// `stm ... ^`).
+    pub fn get_user(&self, idx: u32) -> u32 { self.get_banked(Mode::Usr, idx) }
+
+    /// Write a user-mode register, regardless of the current mode (for
+    /// `ldm ... ^`).
+    pub fn set_user(&mut self, idx: u32, val: u32) {
+        self.set_banked(Mode::Usr, idx, val)
+    }
+
+    /// Swap the banked registers for the current mode out, and those for
+    /// some new mode in.
+    fn switch_mode(&mut self, new: Mode) {
+        let old = self.mode();
+        let (ob, nb) = (bank(old), bank(new));
+        if ob != nb {
+            self.banked[ob].copy_from_slice(&self.r[13..15]);
+            self.r[13..15].copy_from_slice(&self.banked[nb]);
+        }
+        let (of, nf) = ((old != Mode::Fiq) as usize, (new != Mode::Fiq) as usize);
+        if of != nf {
+            self.fiq[of].copy_from_slice(&self.r[8..13]);
+            self.r[8..13].copy_from_slice(&self.fiq[nf]);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn banking() {
+        let mut regs = RegisterFile::new();
+        regs.set(13, 0x1000);
+        regs.set(8, 8);
+        regs.set_spsr(Mode::Usr.bits());
+        regs.set_cpsr(Mode::Fiq.bits());
+        assert_eq!((regs.get(13), regs.get(8)), (0, 0));
+        regs.set(8, 0x88);
+        regs.set(14, 0x14);
+        assert_eq!(regs.get_user(8), 8);
+        regs.set_user(13, 0x2000);
+        regs.set_cpsr(Mode::Sys.bits());
+        assert_eq!((regs.get(8), regs.get(13), regs.get(14)), (8, 0x2000, 0));
+        assert_eq!(regs.spsr(), None);
+        regs.set_cpsr(Mode::Svc.bits());
+        assert_eq!((regs.get(13), regs.spsr()), (0x1000, Some(Mode::Usr.bits())));
+        regs.set_cpsr(Mode::Fiq.bits());
+        assert_eq!((regs.get(8), regs.get(14)), (0x88, 0x14));
+    }
+
+    #[test]
+    fn banked_access() {
+        let mut regs = RegisterFile::new();
+        regs.set_banked(Mode::Fiq, 8, 0xf8);
+        regs.set_banked(Mode::Irq, 13, 0x1d);
+        regs.set_banked(Mode::Svc, 14, 0x5e);
+        regs.set_spsr_of(Mode::Und, Psr::Z | Mode::Usr.bits());
+        assert_eq!((regs.get(8), regs.get(14)), (0, 0x5e));
+        regs.set_mode(Mode::Irq);
+        assert_eq!(regs.get(13), 0x1d);
+        assert_eq!(regs.get_banked(Mode::Fiq, 8), 0xf8);
+        assert_eq!(regs.get_banked(Mode::Svc, 14), 0x5e);
+        assert_eq!(regs.spsr_of(Mode::Und), Some(Psr::Z | Mode::Usr.bits()));
+        assert_eq!(regs.spsr_of(Mode::Sys), None);
+        // Reserved modes don't change the mode (or the banks)
+        regs.set_cpsr(Psr::N | 0b10100);
+        assert_eq!((regs.mode(), regs.cpsr()), (Mode::Irq, Psr::N | Mode::Irq.bits()));
+        assert_eq!(regs.psr().to_string(), "Nzcvq ift irq");
+        regs.set_flag(Psr::C | Psr::V, true);
+        regs.set_flag(Psr::N, false);
+        assert_eq!(regs.psr().to_string(), "nzCVq ift irq");
+    }
+}
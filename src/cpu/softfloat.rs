@@ -0,0 +1,649 @@
+//! IEEE 754 arithmetic in software, as performed by the VFP.
+//!
+//! Values are passed around as bit patterns (single-precision values in the
+//! low bits of a `u64`), so NaN payloads and signed zeros are preserved.
+//! Operations follow the ARM pseudocode: the rounding mode, flush-to-zero
+//! and default NaN controls come from the [Fpscr], and exceptions set its
+//! cumulative flags. Tininess is detected before rounding, and trapped
+//! exceptions aren't supported.
+
+use std::cmp::Ordering;
+
+use super::vfp::*;
+
+/// A binary floating-point format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    pub exp_bits: u32,
+    pub frac_bits: u32,
+}
+pub const F32: Format = Format { exp_bits: 8, frac_bits: 23 };
+pub const F64: Format = Format { exp_bits: 11, frac_bits: 52 };
+
+impl Format {
+    fn bias(self) -> i32 { (1 << (self.exp_bits - 1)) - 1 }
+    /// The exponent of the smallest normal number.
+    fn emin(self) -> i32 { 1 - self.bias() }
+    fn max_exp(self) -> u64 { (1 << self.exp_bits) - 1 }
+    fn frac_mask(self) -> u64 { (1 << self.frac_bits) - 1 }
+    fn sign_bit(self) -> u64 { 1 << (self.exp_bits + self.frac_bits) }
+    fn quiet_bit(self) -> u64 { 1 << (self.frac_bits - 1) }
+
+    pub fn zero(self, sign: bool) -> u64 {
+        if sign { self.sign_bit() } else { 0 }
+    }
+    pub fn infinity(self, sign: bool) -> u64 {
+        self.zero(sign) | (self.max_exp() << self.frac_bits)
+    }
+    pub fn max_normal(self, sign: bool) -> u64 { self.infinity(sign) - 1 }
+    pub fn default_nan(self) -> u64 { self.infinity(false) | self.quiet_bit() }
+
+    /// Negation and absolute value only touch the sign bit, even for NaNs.
+    pub fn neg(self, x: u64) -> u64 { x ^ self.sign_bit() }
+    pub fn abs(self, x: u64) -> u64 { x & !self.sign_bit() }
+}
+
+/// Rounding modes, in the order of the FPSCR RMode encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Nearest,
+    PlusInf,
+    MinusInf,
+    Zero,
+}
+impl Rounding {
+    pub fn from_bits(bits: u32) -> Self {
+        match bits & 3 {
+            0b00 => Rounding::Nearest,
+            0b01 => Rounding::PlusInf,
+            0b10 => Rounding::MinusInf,
+            _ => Rounding::Zero,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Zero,
+    Finite,
+    Infinity,
+    QNaN,
+    SNaN,
+}
+
+/// An unpacked operand. Finite values are `(-1)^sign * mant * 2^exp`.
+#[derive(Debug, Clone, Copy)]
+struct Unpacked {
+    class: Class,
+    sign: bool,
+    mant: u64,
+    exp: i32,
+}
+impl Unpacked {
+    fn is_nan(&self) -> bool { matches!(self.class, Class::QNaN | Class::SNaN) }
+}
+
+/// Unpack an operand, flushing denormals to zero if the FPSCR says so.
+fn unpack(fmt: Format, x: u64, fpscr: &mut Fpscr) -> Unpacked {
+    let sign = (x & fmt.sign_bit()) != 0;
+    let bexp = (x >> fmt.frac_bits) & fmt.max_exp();
+    let frac = x & fmt.frac_mask();
+    // The exponent of the LSB of a denormal
+    let exp = fmt.emin() - fmt.frac_bits as i32;
+    let (class, mant, exp) = match bexp {
+        0 if frac == 0 => (Class::Zero, 0, exp),
+        0 if fpscr.flush_to_zero() => {
+            fpscr.raise(FPSCR_IDC);
+            (Class::Zero, 0, exp)
+        },
+        0 => (Class::Finite, frac, exp),
+        e if e == fmt.max_exp() => match (frac, frac & fmt.quiet_bit()) {
+            (0, _) => (Class::Infinity, 0, 0),
+            (_, 0) => (Class::SNaN, 0, 0),
+            _ => (Class::QNaN, 0, 0),
+        },
+        e => (Class::Finite, frac | (1 << fmt.frac_bits), exp + e as i32 - 1),
+    };
+    Unpacked { class, sign, mant, exp }
+}
+
+/// The part of a value discarded by rounding, relative to half an ULP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Loss {
+    Exact,
+    Below,
+    Half,
+    Above,
+}
+
+/// Shift out the low bits of an integer.
+fn split(mant: u128, shift: u32) -> (u128, Loss) {
+    if shift == 0 {
+        return (mant, Loss::Exact);
+    }
+    if shift > 128 {
+        return (0, if mant == 0 { Loss::Exact } else { Loss::Below });
+    }
+    let (int, rem) = match shift {
+        128 => (0, mant),
+        _ => (mant >> shift, mant & ((1 << shift) - 1)),
+    };
+    let loss = match rem.cmp(&(1 << (shift - 1))) {
+        Ordering::Less if rem == 0 => Loss::Exact,
+        Ordering::Less => Loss::Below,
+        Ordering::Equal => Loss::Half,
+        Ordering::Greater => Loss::Above,
+    };
+    (int, loss)
+}
+
+/// Returns true if the magnitude of a truncated value should be
+/// incremented.
+fn round_up(rounding: Rounding, sign: bool, loss: Loss, odd: bool) -> bool {
+    match rounding {
+        Rounding::Nearest => loss == Loss::Above || (loss == Loss::Half && odd),
+        Rounding::PlusInf => loss != Loss::Exact && !sign,
+        Rounding::MinusInf => loss != Loss::Exact && sign,
+        Rounding::Zero => false,
+    }
+}
+
+/// Round the nonzero value `(-1)^sign * mant * 2^exp` to some format.
+fn round(fmt: Format, sign: bool, mant: u128, exp: i32, fpscr: &mut Fpscr) -> u64 {
+    let f = fmt.frac_bits as i32;
+    // The value lies in [2^top, 2^(top + 1))
+    let top = exp + 127 - mant.leading_zeros() as i32;
+    if fpscr.flush_to_zero() && top < fmt.emin() {
+        fpscr.raise(FPSCR_UFC);
+        return fmt.zero(sign);
+    }
+
+    // The exponent of the LSB of the result
+    let mut lsb = top.max(fmt.emin()) - f;
+    let (mut int, loss) = match lsb - exp {
+        shift if shift <= 0 => (mant << -shift, Loss::Exact),
+        shift => split(mant, shift as u32),
+    };
+    if top < fmt.emin() && loss != Loss::Exact {
+        fpscr.raise(FPSCR_UFC);
+    }
+    if round_up(fpscr.rounding(), sign, loss, (int & 1) != 0) {
+        int += 1;
+        if (int >> (f + 1)) != 0 {
+            int >>= 1;
+            lsb += 1;
+        }
+    }
+
+    // Denormals (including those which rounded up to the smallest normal)
+    // have a biased exponent of zero, plus the carry into the leading bit.
+    let bexp = match (int >> f) != 0 {
+        true => (lsb + f + fmt.bias()) as u64,
+        false => 0,
+    };
+    if bexp >= fmt.max_exp() {
+        fpscr.raise(FPSCR_OFC | FPSCR_IXC);
+        let to_inf = match fpscr.rounding() {
+            Rounding::Nearest => true,
+            Rounding::PlusInf => !sign,
+            Rounding::MinusInf => sign,
+            Rounding::Zero => false,
+        };
+        return if to_inf { fmt.infinity(sign) } else { fmt.max_normal(sign) };
+    }
+    if loss != Loss::Exact {
+        fpscr.raise(FPSCR_IXC);
+    }
+    fmt.zero(sign) | (bexp << fmt.frac_bits) | (int as u64 & fmt.frac_mask())
+}
+
+/// The result of an invalid operation.
+fn invalid(fmt: Format, fpscr: &mut Fpscr) -> u64 {
+    fpscr.raise(FPSCR_IOC);
+    fmt.default_nan()
+}
+
+/// Quiet a NaN operand, or replace it with the default NaN.
+fn process_nan(fmt: Format, x: u64, op: Unpacked, fpscr: &mut Fpscr) -> u64 {
+    if op.class == Class::SNaN {
+        fpscr.raise(FPSCR_IOC);
+    }
+    if fpscr.default_nan() { fmt.default_nan() } else { x | fmt.quiet_bit() }
+}
+
+/// The NaN propagated from a pair of operands, if either is a NaN.
+/// Signalling NaNs take priority, then the first operand.
+fn process_nans(fmt: Format, (a, ua): (u64, Unpacked), (b, ub): (u64, Unpacked),
+    fpscr: &mut Fpscr) -> Option<u64>
+{
+    let nan = match (ua.class, ub.class) {
+        (Class::SNaN, _) => (a, ua),
+        (_, Class::SNaN) => (b, ub),
+        (Class::QNaN, _) => (a, ua),
+        (_, Class::QNaN) => (b, ub),
+        _ => return None,
+    };
+    Some(process_nan(fmt, nan.0, nan.1, fpscr))
+}
+
+/// Shift a finite, nonzero significand up so its leading bit is the
+/// implicit bit of a normal number.
+fn normalize(fmt: Format, op: Unpacked) -> (u128, i32) {
+    let shift = op.mant.leading_zeros() as i32 - (63 - fmt.frac_bits as i32);
+    ((op.mant as u128) << shift, op.exp - shift)
+}
+
+fn add_sub(fmt: Format, a: u64, b: u64, sub: bool, fpscr: &mut Fpscr) -> u64 {
+    let (ua, mut ub) = (unpack(fmt, a, fpscr), unpack(fmt, b, fpscr));
+    if let Some(nan) = process_nans(fmt, (a, ua), (b, ub), fpscr) {
+        return nan;
+    }
+    ub.sign ^= sub;
+    match (ua.class, ub.class) {
+        (Class::Infinity, Class::Infinity) if ua.sign != ub.sign => invalid(fmt, fpscr),
+        (Class::Infinity, _) => fmt.infinity(ua.sign),
+        (_, Class::Infinity) => fmt.infinity(ub.sign),
+        (Class::Zero, Class::Zero) if ua.sign == ub.sign => fmt.zero(ua.sign),
+        _ => {
+            let (hi, lo) = if ua.exp >= ub.exp { (ua, ub) } else { (ub, ua) };
+            // Anything more than 64 bits below the other operand only
+            // matters as a sticky bit.
+            let (lo_mant, shift) = match (lo.mant, hi.exp - lo.exp) {
+                (0, _) => (0, 0),
+                (_, diff) if diff > 64 => (1, 64),
+                (mant, diff) => (mant as u128, diff),
+            };
+            let hi_mant = (hi.mant as u128) << shift;
+            let (sign, mant) = match (hi.sign == lo.sign, hi_mant >= lo_mant) {
+                (true, _) => (hi.sign, hi_mant + lo_mant),
+                (false, true) => (hi.sign, hi_mant - lo_mant),
+                (false, false) => (lo.sign, lo_mant - hi_mant),
+            };
+            // An exact zero is positive, except when rounding to -inf
+            if mant == 0 {
+                return fmt.zero(fpscr.rounding() == Rounding::MinusInf);
+            }
+            round(fmt, sign, mant, hi.exp - shift, fpscr)
+        },
+    }
+}
+
+pub fn add(fmt: Format, a: u64, b: u64, fpscr: &mut Fpscr) -> u64 {
+    add_sub(fmt, a, b, false, fpscr)
+}
+
+pub fn sub(fmt: Format, a: u64, b: u64, fpscr: &mut Fpscr) -> u64 {
+    add_sub(fmt, a, b, true, fpscr)
+}
+
+pub fn mul(fmt: Format, a: u64, b: u64, fpscr: &mut Fpscr) -> u64 {
+    let (ua, ub) = (unpack(fmt, a, fpscr), unpack(fmt, b, fpscr));
+    if let Some(nan) = process_nans(fmt, (a, ua), (b, ub), fpscr) {
+        return nan;
+    }
+    let sign = ua.sign ^ ub.sign;
+    match (ua.class, ub.class) {
+        (Class::Infinity, Class::Zero) | (Class::Zero, Class::Infinity) => invalid(fmt, fpscr),
+        (Class::Infinity, _) | (_, Class::Infinity) => fmt.infinity(sign),
+        (Class::Zero, _) | (_, Class::Zero) => fmt.zero(sign),
+        _ => round(fmt, sign, ua.mant as u128 * ub.mant as u128, ua.exp + ub.exp, fpscr),
+    }
+}
+
+pub fn div(fmt: Format, a: u64, b: u64, fpscr: &mut Fpscr) -> u64 {
+    let (ua, ub) = (unpack(fmt, a, fpscr), unpack(fmt, b, fpscr));
+    if let Some(nan) = process_nans(fmt, (a, ua), (b, ub), fpscr) {
+        return nan;
+    }
+    let sign = ua.sign ^ ub.sign;
+    match (ua.class, ub.class) {
+        (Class::Infinity, Class::Infinity) | (Class::Zero, Class::Zero) => invalid(fmt, fpscr),
+        (Class::Infinity, _) => fmt.infinity(sign),
+        (_, Class::Zero) => {
+            fpscr.raise(FPSCR_DZC);
+            fmt.infinity(sign)
+        },
+        (Class::Zero, _) | (_, Class::Infinity) => fmt.zero(sign),
+        _ => {
+            // With both significands normalized, the quotient has at least
+            // 64 bits, and the remainder becomes a sticky bit.
+            let ((ma, ea), (mb, eb)) = (normalize(fmt, ua), normalize(fmt, ub));
+            let num = ma << 64;
+            let q = (num / mb) | ((num % mb) != 0) as u128;
+            round(fmt, sign, q, ea - eb - 64, fpscr)
+        },
+    }
+}
+
+/// The integer square root of a nonzero value, and whether it's inexact.
+fn isqrt(mut x: u128) -> (u128, bool) {
+    let mut root = 0;
+    let mut bit = 1u128 << ((127 - x.leading_zeros()) & !1);
+    while bit != 0 {
+        if x >= root + bit {
+            x -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    (root, x != 0)
+}
+
+pub fn sqrt(fmt: Format, x: u64, fpscr: &mut Fpscr) -> u64 {
+    let u = unpack(fmt, x, fpscr);
+    match u.class {
+        Class::QNaN | Class::SNaN => process_nan(fmt, x, u, fpscr),
+        Class::Zero => fmt.zero(u.sign),
+        _ if u.sign => invalid(fmt, fpscr),
+        Class::Infinity => fmt.infinity(false),
+        Class::Finite => {
+            let (mut mant, mut exp) = (u.mant as u128, u.exp);
+            if (exp & 1) != 0 {
+                mant <<= 1;
+                exp -= 1;
+            }
+            // Scale by an even power of two, so the root has plenty of
+            // bits below the rounding point.
+            let shift = (116 - (128 - mant.leading_zeros())) & !1;
+            let (root, inexact) = isqrt(mant << shift);
+            round(fmt, false, root | inexact as u128, (exp - shift as i32) / 2, fpscr)
+        },
+    }
+}
+
+/// Compare two values. NaNs are unordered, and raise an invalid operation
+/// if they're signalling (or for any NaN, if `signal` is set).
+pub fn compare(fmt: Format, a: u64, b: u64, signal: bool, fpscr: &mut Fpscr)
+    -> Option<Ordering>
+{
+    let (ua, ub) = (unpack(fmt, a, fpscr), unpack(fmt, b, fpscr));
+    if ua.is_nan() || ub.is_nan() {
+        if signal || ua.class == Class::SNaN || ub.class == Class::SNaN {
+            fpscr.raise(FPSCR_IOC);
+        }
+        return None;
+    }
+    // Both zeros (including flushed denormals) compare equal
+    let key = |u: Unpacked, x: u64| match (u.class, u.sign) {
+        (Class::Zero, _) => 0,
+        (_, true) => -(fmt.abs(x) as i128),
+        (_, false) => fmt.abs(x) as i128,
+    };
+    Some(key(ua, a).cmp(&key(ub, b)))
+}
+
+/// Convert between formats. NaN payloads keep their top bits.
+pub fn convert(from: Format, to: Format, x: u64, fpscr: &mut Fpscr) -> u64 {
+    let u = unpack(from, x, fpscr);
+    match u.class {
+        Class::QNaN | Class::SNaN => {
+            if u.class == Class::SNaN {
+                fpscr.raise(FPSCR_IOC);
+            }
+            if fpscr.default_nan() {
+                return to.default_nan();
+            }
+            let frac = x & from.frac_mask();
+            let frac = match to.frac_bits > from.frac_bits {
+                true => frac << (to.frac_bits - from.frac_bits),
+                false => frac >> (from.frac_bits - to.frac_bits),
+            };
+            to.infinity(u.sign) | to.quiet_bit() | frac
+        },
+        Class::Infinity => to.infinity(u.sign),
+        Class::Zero => to.zero(u.sign),
+        Class::Finite => round(to, u.sign, u.mant as u128, u.exp, fpscr),
+    }
+}
+
+/// Convert a 32-bit integer to floating-point.
+pub fn from_int(fmt: Format, x: u32, signed: bool, fpscr: &mut Fpscr) -> u64 {
+    let sign = signed && (x as i32) < 0;
+    let mag = if sign { (x as i32).unsigned_abs() } else { x };
+    if mag == 0 {
+        return fmt.zero(false);
+    }
+    round(fmt, sign, mag as u128, 0, fpscr)
+}
+
+/// Convert to a 32-bit integer with some rounding mode. NaNs convert to
+/// zero, and out-of-range values saturate, raising an invalid operation.
+pub fn to_int(fmt: Format, x: u64, signed: bool, rounding: Rounding, fpscr: &mut Fpscr)
+    -> u32
+{
+    let u = unpack(fmt, x, fpscr);
+    let (mag, loss) = match u.class {
+        Class::QNaN | Class::SNaN => {
+            fpscr.raise(FPSCR_IOC);
+            return 0;
+        },
+        Class::Zero => return 0,
+        Class::Infinity => (u128::MAX, Loss::Exact),
+        // Anything shifted up by more than 64 bits saturates anyway
+        Class::Finite if u.exp >= 0 => ((u.mant as u128) << u.exp.min(64), Loss::Exact),
+        Class::Finite => split(u.mant as u128, u.exp.unsigned_abs()),
+    };
+    let mag = mag + round_up(rounding, u.sign, loss, (mag & 1) != 0) as u128;
+    let limit = match (signed, u.sign) {
+        (true, true) => 0x8000_0000,
+        (true, false) => 0x7fff_ffff,
+        (false, true) => 0,
+        (false, false) => 0xffff_ffff,
+    };
+    if mag > limit {
+        fpscr.raise(FPSCR_IOC);
+        return if u.sign { (limit as u32).wrapping_neg() } else { limit as u32 };
+    }
+    if loss != Loss::Exact {
+        fpscr.raise(FPSCR_IXC);
+    }
+    if u.sign { (mag as u32).wrapping_neg() } else { mag as u32 }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Interesting single-precision values: zeros, denormals, the extremes
+    /// of the normal range, values near one and some NaNs.
+    const F32_VALUES: [u32; 24] = [
+        0x0000_0000, 0x8000_0000, 0x0000_0001, 0x807f_ffff, 0x0040_0000,
+        0x0080_0000, 0x8080_0001, 0x7f7f_ffff, 0xff7f_fffe, 0x7f00_0000,
+        0x3f80_0000, 0xbf80_0001, 0x3f7f_ffff, 0x4040_0000, 0x3eaa_aaab,
+        0x4b80_0001, 0xcb7f_ffff, 0x3400_0000, 0x2000_0001, 0x5f80_0000,
+        0x7f80_0000, 0xff80_0000, 0x7fc0_1234, 0x7f80_0001,
+    ];
+
+    const F64_VALUES: [u64; 20] = [
+        0x0000_0000_0000_0000, 0x8000_0000_0000_0000, 0x0000_0000_0000_0001,
+        0x800f_ffff_ffff_ffff, 0x0010_0000_0000_0000, 0x7fef_ffff_ffff_ffff,
+        0xffef_ffff_ffff_fffe, 0x3ff0_0000_0000_0000, 0xbff0_0000_0000_0001,
+        0x3fef_ffff_ffff_ffff, 0x4008_0000_0000_0000, 0x3fd5_5555_5555_5555,
+        0x4340_0000_0000_0001, 0x3ca0_0000_0000_0000, 0x2000_0000_0000_0001,
+        0x7fe0_0000_0000_0000, 0x7ff0_0000_0000_0000, 0xfff0_0000_0000_0000,
+        0x7ff8_0000_0000_1234, 0x7ff0_0000_0000_0001,
+    ];
+
+    /// Check an operation against the host in round-to-nearest. NaN
+    /// payloads aren't compared, since the host's don't follow the ARM
+    /// rules.
+    fn check32(res: u64, host: f32) {
+        if host.is_nan() {
+            assert!(f32::from_bits(res as u32).is_nan());
+        } else {
+            assert_eq!(res as u32, host.to_bits());
+        }
+    }
+    fn check64(res: u64, host: f64) {
+        if host.is_nan() {
+            assert!(f64::from_bits(res).is_nan());
+        } else {
+            assert_eq!(res, host.to_bits());
+        }
+    }
+
+    #[test]
+    fn host_arithmetic() {
+        let mut fpscr = Fpscr::default();
+        for &a in F32_VALUES.iter() {
+            let x = f32::from_bits(a);
+            for &b in F32_VALUES.iter() {
+                let y = f32::from_bits(b);
+                let (a, b) = (a as u64, b as u64);
+                check32(add(F32, a, b, &mut fpscr), x + y);
+                check32(sub(F32, a, b, &mut fpscr), x - y);
+                check32(mul(F32, a, b, &mut fpscr), x * y);
+                check32(div(F32, a, b, &mut fpscr), x / y);
+            }
+            check32(sqrt(F32, a as u64, &mut fpscr), x.sqrt());
+            check64(convert(F32, F64, a as u64, &mut fpscr), x as f64);
+            if !x.is_nan() {
+                assert_eq!(to_int(F32, a as u64, true, Rounding::Zero, &mut fpscr),
+                    x as i32 as u32);
+                assert_eq!(to_int(F32, a as u64, false, Rounding::Zero, &mut fpscr),
+                    x as u32);
+            }
+        }
+        for &a in F64_VALUES.iter() {
+            let x = f64::from_bits(a);
+            for &b in F64_VALUES.iter() {
+                let y = f64::from_bits(b);
+                check64(add(F64, a, b, &mut fpscr), x + y);
+                check64(sub(F64, a, b, &mut fpscr), x - y);
+                check64(mul(F64, a, b, &mut fpscr), x * y);
+                check64(div(F64, a, b, &mut fpscr), x / y);
+            }
+            check64(sqrt(F64, a, &mut fpscr), x.sqrt());
+            check32(convert(F64, F32, a, &mut fpscr), x as f32);
+            if !x.is_nan() {
+                assert_eq!(to_int(F64, a, true, Rounding::Zero, &mut fpscr), x as i32 as u32);
+                assert_eq!(to_int(F64, a, false, Rounding::Zero, &mut fpscr), x as u32);
+            }
+        }
+        for x in [0, 1, 0x7fff_ffff, 0x8000_0000, 0xffff_ffff, 0x0100_0001, 0x8765_4321] {
+            check32(from_int(F32, x, false, &mut fpscr), x as f32);
+            check32(from_int(F32, x, true, &mut fpscr), x as i32 as f32);
+            check64(from_int(F64, x, false, &mut fpscr), x as f64);
+            check64(from_int(F64, x, true, &mut fpscr), x as i32 as f64);
+        }
+    }
+
+    #[test]
+    fn rounding_modes() {
+        let fpscr = |mode: u32| Fpscr(mode << 22);
+        let one = 0x3f80_0000;
+        let third = |mode| div(F32, one, 0x4040_0000, &mut fpscr(mode));
+        assert_eq!([third(0), third(1), third(2), third(3)],
+            [0x3eaa_aaab, 0x3eaa_aaab, 0x3eaa_aaaa, 0x3eaa_aaaa]);
+        let neg_third = |mode| div(F32, 0xbf80_0000, 0x4040_0000, &mut fpscr(mode));
+        assert_eq!([neg_third(0), neg_third(1), neg_third(2), neg_third(3)],
+            [0xbeaa_aaab, 0xbeaa_aaaa, 0xbeaa_aaab, 0xbeaa_aaaa]);
+
+        // Ties go to even in round-to-nearest
+        assert_eq!(add(F32, 0x4b80_0000, one, &mut fpscr(0)), 0x4b80_0000);
+        assert_eq!(add(F32, 0x4b80_0001, one, &mut fpscr(0)), 0x4b80_0002);
+
+        // x - x is -0 only when rounding towards -inf
+        assert_eq!(sub(F64, 0x3ff0_0000_0000_0000, 0x3ff0_0000_0000_0000,
+            &mut fpscr(2)), 0x8000_0000_0000_0000);
+        assert_eq!(sub(F64, 0x3ff0_0000_0000_0000, 0x3ff0_0000_0000_0000,
+            &mut fpscr(1)), 0);
+
+        // Overflow gives infinity or the largest normal number
+        let mut f = fpscr(3);
+        assert_eq!(mul(F32, 0x7f7f_ffff, 0x4000_0000, &mut f), 0x7f7f_ffff);
+        assert_eq!(f.0 & 0x9f, FPSCR_OFC | FPSCR_IXC);
+        assert_eq!(mul(F32, 0xff7f_ffff, 0x4000_0000, &mut fpscr(1)), 0xff7f_ffff);
+        assert_eq!(mul(F32, 0xff7f_ffff, 0x4000_0000, &mut fpscr(2)), 0xff80_0000);
+
+        // A tiny difference only moves the result in the directed modes
+        let tiny = 0x0000_0000_0000_0001;
+        assert_eq!(sub(F64, 0x3ff0_0000_0000_0000, tiny, &mut fpscr(0)),
+            0x3ff0_0000_0000_0000);
+        assert_eq!(sub(F64, 0x3ff0_0000_0000_0000, tiny, &mut fpscr(3)),
+            0x3fef_ffff_ffff_ffff);
+        assert_eq!(add(F64, 0x3ff0_0000_0000_0000, tiny, &mut fpscr(1)),
+            0x3ff0_0000_0000_0001);
+
+        // Conversions to integers
+        let cvt = |x, mode| to_int(F32, x, true, Rounding::from_bits(mode), &mut Fpscr(0));
+        assert_eq!([cvt(0x4020_0000, 0), cvt(0x4060_0000, 0), cvt(0xc020_0000, 0)],
+            [2, 4, -2i32 as u32]);
+        assert_eq!([cvt(0x4020_0000, 1), cvt(0xc020_0000, 2), cvt(0xc020_0000, 3)],
+            [3, -3i32 as u32, -2i32 as u32]);
+        assert_eq!(from_int(F32, 0x0100_0001, false, &mut fpscr(1)), 0x4b80_0001);
+    }
+
+    #[test]
+    fn exceptions() {
+        let mut f = Fpscr::default();
+        assert_eq!(div(F32, 0x3f80_0000, 0x8000_0000, &mut f), 0xff80_0000);
+        assert_eq!(f.0, FPSCR_DZC);
+
+        // Signalling NaNs are quieted, and the first operand wins
+        let mut f = Fpscr::default();
+        assert_eq!(add(F32, 0x7fc0_0001, 0xff80_0002, &mut f), 0xffc0_0002);
+        assert_eq!(f.0, FPSCR_IOC);
+        let mut f = Fpscr::default();
+        assert_eq!(mul(F64, 0x7ff8_0000_0000_0001, 0x7ff8_0000_0000_0002, &mut f),
+            0x7ff8_0000_0000_0001);
+        assert_eq!(f.0, 0);
+        assert_eq!(convert(F64, F32, 0xfff0_0000_2000_0000, &mut f), 0xffc0_0001);
+        assert_eq!(f.0, FPSCR_IOC);
+
+        // Invalid operations give the default NaN
+        let mut f = Fpscr::default();
+        assert_eq!(mul(F32, 0x7f80_0000, 0x8000_0000, &mut f), 0x7fc0_0000);
+        assert_eq!(sqrt(F64, 0xbff0_0000_0000_0000, &mut f), 0x7ff8_0000_0000_0000);
+        assert_eq!(sqrt(F64, 0x8000_0000_0000_0000, &mut f), 0x8000_0000_0000_0000);
+        assert_eq!(to_int(F32, 0x4f80_0000, true, Rounding::Zero, &mut f), 0x7fff_ffff);
+        assert_eq!(to_int(F32, 0xbf80_0000, false, Rounding::Zero, &mut f), 0);
+        assert_eq!(f.0, FPSCR_IOC);
+
+        // With default NaN mode, NaN operands are replaced too
+        let mut f = Fpscr(FPSCR_DN);
+        assert_eq!(add(F32, 0x7f80_0001, 0x3f80_0000, &mut f), 0x7fc0_0000);
+        assert_eq!(f.0 & 0x9f, FPSCR_IOC);
+
+        // Underflow only happens for tiny, inexact results
+        let mut f = Fpscr::default();
+        assert_eq!(mul(F32, 0x0080_0000, 0x3f00_0000, &mut f), 0x0040_0000);
+        assert_eq!(f.0, 0);
+        assert_eq!(mul(F32, 0x0080_0001, 0x3f00_0000, &mut f), 0x0040_0000);
+        assert_eq!(f.0, FPSCR_UFC | FPSCR_IXC);
+        assert_eq!(to_int(F64, 0x3fe0_0000_0000_0001, true, Rounding::Nearest, &mut f), 1);
+
+        let ordered = |a, b, f: &mut Fpscr| compare(F32, a, b, false, f);
+        let mut f = Fpscr::default();
+        assert_eq!(ordered(0x0000_0000, 0x8000_0000, &mut f), Some(Ordering::Equal));
+        assert_eq!(ordered(0xbf80_0000, 0x0000_0001, &mut f), Some(Ordering::Less));
+        assert_eq!(ordered(0x7f80_0000, 0x7f7f_ffff, &mut f), Some(Ordering::Greater));
+        assert_eq!(ordered(0x7fc0_0000, 0x3f80_0000, &mut f), None);
+        assert_eq!(f.0, 0);
+        assert_eq!(compare(F32, 0x7fc0_0000, 0, true, &mut f), None);
+        assert_eq!(f.0, FPSCR_IOC);
+    }
+
+    #[test]
+    fn flush_to_zero() {
+        let mut f = Fpscr(FPSCR_FZ);
+        // Denormal inputs are flushed (keeping their sign)
+        assert_eq!(add(F32, 0x8000_0001, 0x8000_0000, &mut f), 0x8000_0000);
+        assert_eq!(f.0 & 0x9f, FPSCR_IDC);
+        assert_eq!(compare(F64, 1, 0, false, &mut f), Some(Ordering::Equal));
+
+        // Tiny results are flushed before rounding, without inexact
+        let mut f = Fpscr(FPSCR_FZ);
+        assert_eq!(mul(F32, 0x0080_0000, 0xbf7f_ffff, &mut f), 0x8000_0000);
+        assert_eq!(f.0 & 0x9f, FPSCR_UFC);
+        let mut f = Fpscr(FPSCR_FZ);
+        assert_eq!(convert(F64, F32, 0x3800_0000_0000_0000, &mut f), 0);
+        assert_eq!(f.0 & 0x9f, FPSCR_UFC);
+        let mut f = Fpscr(FPSCR_FZ);
+        assert_eq!(mul(F32, 0x0080_0000, 0x3f80_0000, &mut f), 0x0080_0000);
+        assert_eq!(f.0 & 0x9f, 0);
+    }
+}
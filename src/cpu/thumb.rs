@@ -0,0 +1,546 @@
+//! Execution handlers for Thumb instructions.
+//!
+//! Most data-processing instructions are executed as the equivalent ARM
+//! instruction with the S bit set.
+
+use crate::newtype::*;
+use crate::traits::*;
+
+use super::*;
+use super::alu::*;
+use super::arm::{dp, reglist};
+
+/// Sign extend to some number of bits
+#[inline(always)]
+fn sign_extend(x: u32, bits: u32) -> u32 {
+    (((x << (32 - bits)) as i32) >> (32 - bits)) as u32
+}
+
+/// Shorthand for a register selected by a 3-bit field and a high bit.
+fn hireg(x: u16, h: bool) -> u32 {
+    ((h as u32) << 3) | x as u32
+}
+
+/// The PC as seen by PC-relative loads and address generation.
+fn aligned_pc<B: Bus>(cpu: &Cpu<B>) -> u32 {
+    cpu.reg(15) & !3
+}
+
+/// ARM data-processing opcodes used by the Thumb instructions.
+const AND: u32 = 0b0000;
+const EOR: u32 = 0b0001;
+const SUB: u32 = 0b0010;
+const RSB: u32 = 0b0011;
+const ADD: u32 = 0b0100;
+const ADC: u32 = 0b0101;
+const SBC: u32 = 0b0110;
+const TST: u32 = 0b1000;
+const CMP: u32 = 0b1010;
+const CMN: u32 = 0b1011;
+const ORR: u32 = 0b1100;
+const MOV: u32 = 0b1101;
+const BIC: u32 = 0b1110;
+const MVN: u32 = 0b1111;
+
+
+pub fn add_reg1<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt1Bf) -> Exec {
+    let (a, b) = (cpu.reg(op.rn() as u32), cpu.reg(op.rm() as u32));
+    dp(cpu, ADD, true, op.rd() as u32, a, (b, false))
+}
+pub fn sub_reg<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt1Bf) -> Exec {
+    let (a, b) = (cpu.reg(op.rn() as u32), cpu.reg(op.rm() as u32));
+    dp(cpu, SUB, true, op.rd() as u32, a, (b, false))
+}
+pub fn add_imm1<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt2Bf) -> Exec {
+    let a = cpu.reg(op.rn() as u32);
+    dp(cpu, ADD, true, op.rd() as u32, a, (op.imm3() as u32, false))
+}
+pub fn sub_imm1<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt2Bf) -> Exec {
+    let a = cpu.reg(op.rn() as u32);
+    dp(cpu, SUB, true, op.rd() as u32, a, (op.imm3() as u32, false))
+}
+
+/// MOV/CMP/ADD/SUB with an 8-bit immediate.
+pub fn dp_fmt3<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt3Bf) -> Exec {
+    let opcd = match op.op3() {
+        0b00 => MOV,
+        0b01 => CMP,
+        0b10 => ADD,
+        _ => SUB,
+    };
+    let (a, c) = (cpu.reg(op.rn() as u32), cpu.regs.flag(Psr::C));
+    dp(cpu, opcd, true, op.rd() as u32, a, (op.imm8() as u32, c))
+}
+
+/// Shifts by an immediate.
+pub fn dp_fmt4<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt4Bf) -> Exec {
+    let b = shift_imm(cpu.reg(op.rm() as u32), op.op4() as u32,
+        op.shift_imm() as u32, cpu.regs.flag(Psr::C));
+    dp(cpu, MOV, true, op.rd() as u32, 0, b)
+}
+
+/// Register-to-register data-processing operations.
+pub fn dp_fmt5<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt5Bf) -> Exec {
+    let rd = op.rd() as u32;
+    let (a, b) = (cpu.reg(rd), cpu.reg(op.rm() as u32));
+    let c = cpu.regs.flag(Psr::C);
+    let (opcd, a, b) = match op.op5() {
+        0b0000 => (AND, a, (b, c)),
+        0b0001 => (EOR, a, (b, c)),
+        0b0010 => (MOV, 0, shift_reg(a, 0, b, c)),
+        0b0011 => (MOV, 0, shift_reg(a, 1, b, c)),
+        0b0100 => (MOV, 0, shift_reg(a, 2, b, c)),
+        0b0101 => (ADC, a, (b, c)),
+        0b0110 => (SBC, a, (b, c)),
+        0b0111 => (MOV, 0, shift_reg(a, 3, b, c)),
+        0b1000 => (TST, a, (b, c)),
+        0b1001 => (RSB, b, (0, c)),
+        0b1010 => (CMP, a, (b, c)),
+        0b1011 => (CMN, a, (b, c)),
+        0b1100 => (ORR, a, (b, c)),
+        // MUL leaves the C flag unchanged on ARMv5
+        0b1101 => (MOV, 0, (a.wrapping_mul(b), c)),
+        0b1110 => (BIC, a, (b, c)),
+        _ => (MVN, 0, (b, c)),
+    };
+    dp(cpu, opcd, true, rd, a, b)
+}
+
+/// Address generation relative to the PC or SP.
+pub fn dp_fmt6<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt6Bf) -> Exec {
+    let base = if op.reg() { cpu.reg(13) } else { aligned_pc(cpu) };
+    cpu.set_reg(op.rd() as u32, base.wrapping_add(op.imm8() as u32 * 4));
+    Ok(())
+}
+
+/// Adjusting the SP.
+pub fn dp_fmt7<B: Bus>(cpu: &mut Cpu<B>, op: &DpFmt7Bf) -> Exec {
+    let imm = op.imm7() as u32 * 4;
+    let sp = cpu.reg(13);
+    let sp = if op.op6() == 0 { sp.wrapping_add(imm) } else { sp.wrapping_sub(imm) };
+    cpu.set_reg(13, sp);
+    Ok(())
+}
+
+/// ADD/CMP/MOV on high registers. Only CMP sets the flags.
+pub fn dp_special<B: Bus>(cpu: &mut Cpu<B>, op: &DpSpecialBf) -> Exec {
+    let rd = hireg(op.rd(), op.h1());
+    let (a, b) = (cpu.reg(rd), cpu.reg(hireg(op.rm(), op.h2())));
+    match op.opcd() {
+        0b00 => cpu.set_reg(rd, a.wrapping_add(b)),
+        0b01 => return dp(cpu, CMP, true, rd, a, (b, false)),
+        _ => cpu.set_reg(rd, b),
+    }
+    Ok(())
+}
+
+
+pub fn bx<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbBranchExchangeBf) -> Exec {
+    cpu.branch_exchange(cpu.reg(hireg(op.rm(), op.h2())));
+    Ok(())
+}
+pub fn blx_reg<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbBranchExchangeBf) -> Exec {
+    let target = cpu.reg(hireg(op.rm(), op.h2()));
+    cpu.set_reg(14, cpu.regs.pc().wrapping_add(2) | 1);
+    cpu.branch_exchange(target);
+    Ok(())
+}
+
+/// Branch targets are relative to the PC (the address of the instruction
+/// plus 4).
+pub fn b_cond<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbCondBranchBf) -> Exec {
+    if cond_passed(op.cond() as u32, cpu.regs.cpsr()) {
+        let off = sign_extend(op.simm8() as u32, 8) << 1;
+        cpu.branch(cpu.reg(15).wrapping_add(off));
+    }
+    Ok(())
+}
+pub fn b<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbBranchLinkBf) -> Exec {
+    let off = sign_extend(op.imm11() as u32, 11) << 1;
+    cpu.branch(cpu.reg(15).wrapping_add(off));
+    Ok(())
+}
+
+/// The first half of a BL/BLX pair, which stages the upper part of the
+/// offset in LR.
+pub fn bl_prefix<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbBranchLinkBf) -> Exec {
+    let off = sign_extend(op.imm11() as u32, 11) << 12;
+    cpu.set_reg(14, cpu.reg(15).wrapping_add(off));
+    Ok(())
+}
+
+/// The second half of a BL pair.
+pub fn bl<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbBranchLinkBf) -> Exec {
+    let target = cpu.reg(14).wrapping_add((op.imm11() as u32) << 1);
+    cpu.set_reg(14, cpu.regs.pc().wrapping_add(2) | 1);
+    cpu.branch(target);
+    Ok(())
+}
+
+/// The second half of a BLX pair, which switches to ARM state.
+pub fn blx<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbBranchLinkBf) -> Exec {
+    if (op.imm11() & 1) != 0 {
+        return Err(Trap::Undefined(op.0 as u32));
+    }
+    let target = cpu.reg(14).wrapping_add((op.imm11() as u32) << 1);
+    cpu.set_reg(14, cpu.regs.pc().wrapping_add(2) | 1);
+    cpu.branch_exchange(target & !3);
+    Ok(())
+}
+
+
+/// Load or store a single register.
+fn ls<B: Bus>(cpu: &mut Cpu<B>, l: bool, kind: Kind, rd: u32, addr: u32) -> Exec {
+    if l {
+        let val = match kind {
+            Kind::Word => cpu.load32(addr)?,
+            Kind::Byte => cpu.load8(addr)? as u32,
+            Kind::Half => cpu.load16(addr)? as u32,
+            Kind::SignedByte => cpu.load8(addr)? as i8 as u32,
+            Kind::SignedHalf => cpu.load16(addr)? as i16 as u32,
+        };
+        cpu.set_reg(rd, val);
+        Ok(())
+    } else {
+        let val = cpu.reg(rd);
+        match kind {
+            Kind::Word => cpu.store32(addr, val),
+            Kind::Half => cpu.store16(addr, val as u16),
+            _ => cpu.store8(addr, val as u8),
+        }
+    }
+}
+
+/// The size and signedness of a single load/store.
+#[derive(Clone, Copy)]
+enum Kind { Word, Byte, Half, SignedByte, SignedHalf }
+
+/// Load/store with a scaled 5-bit immediate offset.
+pub fn ls_fmt1<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbLsFmt1Bf) -> Exec {
+    let (l, kind, scale) = match op.opcd1() {
+        0b01100 => (false, Kind::Word, 4),
+        0b01101 => (true, Kind::Word, 4),
+        0b01110 => (false, Kind::Byte, 1),
+        0b01111 => (true, Kind::Byte, 1),
+        0b10000 => (false, Kind::Half, 2),
+        _ => (true, Kind::Half, 2),
+    };
+    let addr = cpu.reg(op.rn() as u32).wrapping_add(op.imm5() as u32 * scale);
+    ls(cpu, l, kind, op.rd() as u32, addr)
+}
+
+/// Load/store with a register offset.
+pub fn ls_fmt2<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbLsFmt2Bf) -> Exec {
+    let (l, kind) = match op.opcd2() {
+        0b0101000 => (false, Kind::Word),
+        0b0101001 => (false, Kind::Half),
+        0b0101010 => (false, Kind::Byte),
+        0b0101011 => (true, Kind::SignedByte),
+        0b0101100 => (true, Kind::Word),
+        0b0101101 => (true, Kind::Half),
+        0b0101110 => (true, Kind::Byte),
+        _ => (true, Kind::SignedHalf),
+    };
+    let addr = cpu.reg(op.rn() as u32).wrapping_add(cpu.reg(op.rm() as u32));
+    ls(cpu, l, kind, op.rd() as u32, addr)
+}
+
+pub fn ldr_lit<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbLdrLitBf) -> Exec {
+    let addr = aligned_pc(cpu).wrapping_add(op.imm8() as u32 * 4);
+    ls(cpu, true, Kind::Word, op.rd() as u32, addr)
+}
+
+/// SP-relative load/store.
+pub fn ls_fmt4<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbLsFmt4Bf) -> Exec {
+    let addr = cpu.reg(13).wrapping_add(op.imm8() as u32 * 4);
+    ls(cpu, op.l(), Kind::Word, op.rd() as u32, addr)
+}
+
+
+/// Load registers from consecutive words. Returns the values, indexed by
+/// register number.
+fn load_multi<B: Bus>(cpu: &mut Cpu<B>, start: u32, list: u32)
+    -> Result<[u32; 16], Trap>
+{
+    let mut vals = [0; 16];
+    for (i, idx) in reglist(list).enumerate() {
+        vals[idx as usize] = cpu.read32(start.wrapping_add(i as u32 * 4))?;
+    }
+    Ok(vals)
+}
+
+/// Store registers to consecutive words.
+fn store_multi<B: Bus>(cpu: &mut Cpu<B>, start: u32, list: u32) -> Exec {
+    for (i, idx) in reglist(list).enumerate() {
+        cpu.store32(start.wrapping_add(i as u32 * 4), cpu.reg(idx))?;
+    }
+    Ok(())
+}
+
+pub fn ldmia<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbLsMultiBf) -> Exec {
+    let (rn, list) = (op.rn() as u32, op.reglist() as u32);
+    let base = cpu.reg(rn);
+    let vals = load_multi(cpu, base, list)?;
+    // A loaded base register takes priority over writeback
+    cpu.set_reg(rn, base.wrapping_add(list.count_ones() * 4));
+    for idx in reglist(list) {
+        cpu.set_reg(idx, vals[idx as usize]);
+    }
+    Ok(())
+}
+pub fn stmia<B: Bus>(cpu: &mut Cpu<B>, op: &ThumbLsMultiBf) -> Exec {
+    let (rn, list) = (op.rn() as u32, op.reglist() as u32);
+    let base = cpu.reg(rn);
+    store_multi(cpu, base, list)?;
+    cpu.set_reg(rn, base.wrapping_add(list.count_ones() * 4));
+    Ok(())
+}
+
+/// PUSH/POP, where the R bit adds LR (for PUSH) or the PC (for POP) to the
+/// register list. Popping the PC interworks.
+pub fn push_pop<B: Bus>(cpu: &mut Cpu<B>, op: &PushPopBf) -> Exec {
+    let mut list = op.reglist() as u32;
+    if op.r() {
+        list |= if op.l() { 1 << 15 } else { 1 << 14 };
+    }
+    let size = list.count_ones() * 4;
+    let sp = cpu.reg(13);
+    if op.l() {
+        let vals = load_multi(cpu, sp, list)?;
+        cpu.set_reg(13, sp.wrapping_add(size));
+        for idx in reglist(list & 0xff) {
+            cpu.set_reg(idx, vals[idx as usize]);
+        }
+        if op.r() {
+            cpu.branch_exchange(vals[15]);
+        }
+    } else {
+        store_multi(cpu, sp.wrapping_sub(size), list)?;
+        cpu.set_reg(13, sp.wrapping_sub(size));
+    }
+    Ok(())
+}
+
+
+pub fn swi<B: Bus>(_cpu: &mut Cpu<B>, op: &ThumbExcepBf) -> Exec {
+    Err(Trap::Swi(op.imm8() as u32))
+}
+pub fn bkpt<B: Bus>(_cpu: &mut Cpu<B>, op: &ThumbExcepBf) -> Exec {
+    Err(Trap::Bkpt(op.imm8() as u32))
+}
+pub fn undef<B: Bus>(_cpu: &mut Cpu<B>, x: u16) -> Exec {
+    Err(Trap::Undefined(x as u32))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::psr::*;
+
+    /// An interpreter in Thumb state with some halfwords at an address.
+    fn thumb(pc: u32, code: &[u16]) -> Interpreter<Ram> {
+        let mut interp = Interpreter::new(Ram::new(0, 0x1000));
+        for (i, x) in code.iter().enumerate() {
+            interp.cpu.bus.write16(pc + i as u32 * 2, *x).unwrap();
+        }
+        interp.cpu.regs.set_flag(Psr::T, true);
+        interp.cpu.regs.set_pc(pc);
+        interp
+    }
+
+    /// Step through the test corpus, checking r0 and the flags after each
+    /// data-processing instruction.
+    #[test]
+    fn corpus() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let bin = std::fs::read(format!("{}/testsuite/thumb_decode_test.bin", dir))
+            .unwrap();
+        let code: Vec<u16> = bin.chunks(2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .collect();
+        let mut interp = thumb(0, &code);
+        for (idx, val) in [(0, 0x10), (1, 3), (2, 5), (10, 0x8000_0000), (11, 7), (13, 0x800)] {
+            interp.cpu.regs.set(idx, val);
+        }
+
+        // (pc, r0, nzcv) after each instruction
+        let dataproc = [
+            (0x00, 0x13, 0b0000),       // adcs  r0, r1
+            (0x02, 3, 0b0000),          // adds  r0, r1, #0
+            (0x04, 10, 0b0000),         // adds  r0, r1, #7
+            (0x06, 0x109, 0b0000),      // adds  r0, #255
+            (0x08, 0x109, 0b0000),      // adds  r0, #0
+            (0x0a, 8, 0b0000),          // adds  r0, r1, r2
+            (0x0c, 15, 0b0000),         // add   r0, r11
+            (0x0e, 0x100, 0b0000),      // adr   r0, #240
+            (0x10, 0x8f0, 0b0000),      // add   r0, sp, #240
+            (0x12, 0x8f0, 0b0000),      // add   sp, #240
+            (0x14, 0, 0b0100),          // ands  r0, r1
+            (0x16, 0, 0b0100),          // asrs  r0, r1, #32
+            (0x18, 0, 0b0100),          // asrs  r0, r1
+            (0x1a, 0, 0b0100),          // bics  r0, r1
+            (0x1c, 0, 0b0000),          // cmn   r0, r1
+            (0x1e, 0, 0b1000),          // cmp   r0, #255
+            (0x20, 0, 0b1000),          // cmp   r0, r1
+            (0x22, 0, 0b1001),          // cmp   r0, r10
+            (0x24, 3, 0b0001),          // eors  r0, r1
+            (0x26, 0x8000_0000, 0b1011), // lsls  r0, r1, #31
+            (0x28, 0, 0b0101),          // lsls  r0, r1
+            (0x2a, 0, 0b0101),          // lsrs  r0, r1, #31
+            (0x2c, 0, 0b0101),          // lsrs  r0, r1
+            (0x2e, 0xff, 0b0001),       // movs  r0, #255
+            (0x30, 3, 0b0001),          // movs  r0, r1
+            (0x32, 0x8000_0000, 0b0001), // mov   r0, r10
+            (0x34, 0x8000_0000, 0b1001), // muls  r0, r1, r0
+            (0x36, 0xffff_fffc, 0b1001), // mvns  r0, r1
+            (0x38, 0xffff_fffd, 0b1000), // rsbs  r0, r1, #0
+            (0x3a, 0xffff_ffff, 0b1000), // orrs  r0, r1
+            (0x3c, 0xffff_ffff, 0b1010), // rors  r0, r1
+            (0x3e, 0xffff_fffc, 0b1010), // sbcs  r0, r1
+            (0x40, 0xffff_fffc, 0b1000), // subs  r0, r1, #7
+            (0x42, 0xffff_fefd, 0b1010), // subs  r0, #255
+            (0x44, 0xffff_fffe, 0b1000), // subs  r0, r1, r2
+            (0x46, 0xffff_fffe, 0b1000), // sub   sp, #128
+            (0x48, 0xffff_fffe, 0b0000), // tst   r0, r1
+        ];
+        for (pc, r0, nzcv) in dataproc.iter() {
+            assert_eq!(interp.cpu.regs.pc(), *pc);
+            interp.step().unwrap();
+            let regs = &interp.cpu.regs;
+            assert_eq!((regs.get(0), regs.cpsr() >> 28), (*r0, *nzcv), "{:02x}", pc);
+        }
+        assert_eq!(interp.cpu.regs.get(13), 0x870);
+        assert_eq!(interp.step(), Err(Trap::Bkpt(255)));
+        interp.cpu.regs.set_pc(0x4c);
+        assert_eq!(interp.step(), Err(Trap::Swi(255)));
+
+        // loadstoremult
+        let bus = &mut interp.cpu.bus;
+        for i in 0..8 {
+            bus.write32(0x200 + i * 4, if i == 7 { 0x240 } else { 0 }).unwrap();
+            let v = match i { 0 => 0x300, 7 => 0x380, _ => 0x11 * i };
+            bus.write32(0x240 + i * 4, v).unwrap();
+        }
+        interp.cpu.regs.set(0, 0x200);
+        interp.cpu.regs.set_pc(0x4e);
+        interp.run_until(0x56, 4).unwrap();
+        let regs: Vec<u32> = (0..8).map(|i| interp.cpu.regs.get(i)).collect();
+        assert_eq!(regs, vec![0x31c, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x39c]);
+        let bus = &mut interp.cpu.bus;
+        let words = |bus: &mut Ram, addr: u32| -> Vec<u32> {
+            (0..7).map(|i| bus.read32(addr + i * 4).unwrap()).collect()
+        };
+        assert_eq!(words(bus, 0x300), vec![0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x380]);
+        assert_eq!(words(bus, 0x380), vec![0x31c, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+
+        // loadstore, with each byte in 0x400-0x4ff holding its address
+        for addr in 0x400..0x500 {
+            bus.write8(addr, addr as u8).unwrap();
+        }
+        bus.write32(0x9f8, 0xdead_beef).unwrap();
+        for (idx, val) in [(1, 0x400), (2, 0x88), (13, 0x600)] {
+            interp.cpu.regs.set(idx, val);
+        }
+        let loads = [
+            (0x56, 0x7b7a_7978),    // ldr   r0, [r1, #120]
+            (0x58, 0x8b8a_8988),    // ldr   r0, [r1, r2]
+            (0x5a, 0x5756_5554),    // ldr   r0, [pc, #1016]
+            (0x5c, 0xdead_beef),    // ldr   r0, [sp, #1016]
+            (0x5e, 0x1f),           // ldrb  r0, [r1, #31]
+            (0x60, 0x88),           // ldrb  r0, [r1, r2]
+            (0x62, 0x1918),         // ldrh  r0, [r1, #24]
+            (0x64, 0x8988),         // ldrh  r0, [r1, r2]
+            (0x66, 0xffff_ff88),    // ldrsb r0, [r1, r2]
+            (0x68, 0xffff_8988),    // ldrsh r0, [r1, r2]
+        ];
+        for (pc, r0) in loads.iter() {
+            assert_eq!(interp.cpu.regs.pc(), *pc);
+            interp.step().unwrap();
+            assert_eq!(interp.cpu.regs.get(0), *r0, "{:02x}", pc);
+        }
+        interp.run_until(0x78, 7).unwrap();
+        let bus = &mut interp.cpu.bus;
+        assert_eq!(bus.read32(0x478).unwrap(), 0xffff_8988);
+        assert_eq!(bus.read32(0x488).unwrap(), 0xffff_8988);
+        assert_eq!(bus.read32(0x9f8).unwrap(), 0xffff_8988);
+        assert_eq!(bus.read32(0x41c).unwrap(), 0x881e_1d1c);
+        assert_eq!(bus.read32(0x418).unwrap(), 0x1b1a_8988);
+
+        // pushpop
+        for i in 0..8 {
+            bus.write32(0x600 + i * 4, 0x1000 + i).unwrap();
+        }
+        interp.step().unwrap();
+        assert_eq!(interp.cpu.regs.get(13), 0x620);
+        assert_eq!(interp.cpu.regs.get(7), 0x1007);
+        interp.cpu.bus.write32(0x600, 0).unwrap();
+        interp.step().unwrap();
+        assert_eq!(interp.cpu.regs.get(13), 0x600);
+        assert_eq!(interp.cpu.bus.read32(0x600).unwrap(), 0x1000);
+        assert_eq!(interp.cpu.regs.pc(), 0x7c);
+    }
+
+    #[test]
+    fn branches() {
+        // BL, with the prefix staging the offset in LR
+        let mut interp = thumb(0x100, &[0xf000, 0xf87e]);
+        interp.step().unwrap();
+        assert_eq!((interp.cpu.regs.pc(), interp.cpu.regs.get(14)), (0x102, 0x104));
+        interp.step().unwrap();
+        assert_eq!((interp.cpu.regs.pc(), interp.cpu.regs.get(14)), (0x200, 0x105));
+        assert!(interp.cpu.regs.thumb());
+
+        // BLX to ARM code
+        let mut interp = thumb(0x202, &[0xf000, 0xe87e]);
+        interp.run_until(0x300, 2).unwrap();
+        assert_eq!(interp.cpu.regs.get(14), 0x207);
+        assert!(!interp.cpu.regs.thumb());
+        let mut interp = thumb(0x200, &[0xf000, 0xe87f]);
+        interp.step().unwrap();
+        assert_eq!(interp.step(), Err(Trap::Undefined(0xe87f)));
+
+        let step = |regs: &[(u32, u32)], x: u16| {
+            let mut interp = thumb(0x400, &[x]);
+            for (idx, val) in regs {
+                interp.cpu.regs.set(*idx, *val);
+            }
+            interp.step().unwrap();
+            let regs = &interp.cpu.regs;
+            (regs.pc(), regs.get(14), regs.thumb())
+        };
+        assert_eq!(step(&[(3, 0x501)], 0x4718), (0x500, 0, true));     // bx   r3
+        assert_eq!(step(&[(3, 0x600)], 0x4718), (0x600, 0, false));    // bx   r3
+        assert_eq!(step(&[(10, 0x701)], 0x47d0), (0x700, 0x403, true)); // blx  r10
+        assert_eq!(step(&[(1, 0x503)], 0x468f), (0x502, 0, true));     // mov  pc, r1
+        assert_eq!(step(&[], 0xe67e), (0x100, 0, true));               // b    0x100
+        assert_eq!(step(&[], 0xd0fc), (0x402, 0, true));               // beq  0x3fc
+
+        let mut interp = thumb(0x400, &[0xd0fc]);
+        interp.cpu.regs.set_flag(Psr::Z, true);
+        interp.step().unwrap();
+        assert_eq!(interp.cpu.regs.pc(), 0x3fc);
+    }
+
+    #[test]
+    fn hi_registers_and_push_pop() {
+        let mut interp = thumb(0x402, &[
+            0x44f9, // add   r9, pc
+            0xb510, // push  {r4, lr}
+            0xbd10, // pop   {r4, pc}
+        ]);
+        for (idx, val) in [(9, 1), (4, 4), (13, 0x800), (14, 0x123)] {
+            interp.cpu.regs.set(idx, val);
+        }
+        interp.step().unwrap();
+        assert_eq!(interp.cpu.regs.get(9), 0x407);
+        interp.step().unwrap();
+        assert_eq!(interp.cpu.regs.get(13), 0x7f8);
+        assert_eq!(interp.cpu.bus.read32(0x7fc).unwrap(), 0x123);
+        interp.cpu.bus.write32(0x7fc, 0x200).unwrap();
+        interp.cpu.regs.set(4, 0);
+        interp.step().unwrap();
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(4), regs.get(13), regs.pc(), regs.thumb()), (4, 0x800, 0x200, false));
+    }
+}
@@ -0,0 +1,469 @@
+//! The VFPv2 floating-point coprocessor (cp10 and cp11).
+//!
+//! Arithmetic is done in software by the [softfloat](super::softfloat)
+//! module, so results are bit-exact regardless of the host. Exceptions are
+//! always handled with their default results: the trap enable bits in the
+//! FPSCR read as zero, as on implementations without trapped exceptions,
+//! so the support code is never entered.
+
+use std::cmp::Ordering;
+
+use crate::fields::VfpSysRegister;
+use crate::inst::VfpInst;
+use crate::newtype::*;
+use crate::traits::*;
+
+use super::*;
+use super::softfloat::*;
+
+/// Floating-point system ID register of the VFP9-S (VFPv2).
+pub const FPSID_VFP9: u32 = 0x4101_01a0;
+
+/// FPEXC bits.
+pub const FPEXC_EX: u32 = 1 << 31;
+pub const FPEXC_EN: u32 = 1 << 30;
+
+/// FPSCR bits.
+pub const FPSCR_N: u32 = 1 << 31;
+pub const FPSCR_Z: u32 = 1 << 30;
+pub const FPSCR_C: u32 = 1 << 29;
+pub const FPSCR_V: u32 = 1 << 28;
+pub const FPSCR_DN: u32 = 1 << 25;
+pub const FPSCR_FZ: u32 = 1 << 24;
+pub const FPSCR_RMODE: u32 = 0b11 << 22;
+pub const FPSCR_STRIDE: u32 = 0b11 << 20;
+pub const FPSCR_LEN: u32 = 0b111 << 16;
+pub const FPSCR_IDC: u32 = 1 << 7;
+pub const FPSCR_IXC: u32 = 1 << 4;
+pub const FPSCR_UFC: u32 = 1 << 3;
+pub const FPSCR_OFC: u32 = 1 << 2;
+pub const FPSCR_DZC: u32 = 1 << 1;
+pub const FPSCR_IOC: u32 = 1 << 0;
+
+/// FPSCR bits which can be written.
+const FPSCR_WRITABLE: u32 = FPSCR_N | FPSCR_Z | FPSCR_C | FPSCR_V | FPSCR_DN |
+    FPSCR_FZ | FPSCR_RMODE | FPSCR_STRIDE | FPSCR_LEN | FPSCR_IDC | FPSCR_IXC |
+    FPSCR_UFC | FPSCR_OFC | FPSCR_DZC | FPSCR_IOC;
+
+/// The floating-point status and control register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Fpscr(pub u32);
+impl Fpscr {
+    pub fn rounding(self) -> Rounding { Rounding::from_bits(self.0 >> 22) }
+    pub fn flush_to_zero(self) -> bool { (self.0 & FPSCR_FZ) != 0 }
+    pub fn default_nan(self) -> bool { (self.0 & FPSCR_DN) != 0 }
+
+    /// The number of elements in a short vector (1-8).
+    pub fn vector_len(self) -> u32 { ((self.0 & FPSCR_LEN) >> 16) + 1 }
+
+    /// The distance between the registers of a short vector (1 or 2).
+    pub fn vector_stride(self) -> u32 {
+        if (self.0 & FPSCR_STRIDE) == FPSCR_STRIDE { 2 } else { 1 }
+    }
+
+    /// Set some cumulative exception flags.
+    pub fn raise(&mut self, flags: u32) { self.0 |= flags }
+}
+
+/// The VFP register state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vfp {
+    /// The register bank, as single-precision registers. Double-precision
+    /// register Dn is S(2n+1):S(2n).
+    pub regs: [u32; 32],
+    pub fpscr: Fpscr,
+    pub fpsid: u32,
+    pub fpexc: u32,
+    pub fpinst: u32,
+    pub fpinst2: u32,
+}
+
+impl Default for Vfp {
+    fn default() -> Self { Self::new() }
+}
+
+impl Vfp {
+    /// The VFP in its reset state, which is disabled.
+    pub fn new() -> Self {
+        Vfp {
+            regs: [0; 32],
+            fpscr: Fpscr(0),
+            fpsid: FPSID_VFP9,
+            fpexc: 0,
+            fpinst: 0,
+            fpinst2: 0,
+        }
+    }
+
+    pub fn enabled(&self) -> bool { (self.fpexc & FPEXC_EN) != 0 }
+
+    pub fn s(&self, idx: u32) -> u32 { self.regs[idx as usize] }
+    pub fn set_s(&mut self, idx: u32, val: u32) { self.regs[idx as usize] = val }
+
+    pub fn d(&self, idx: u32) -> u64 {
+        ((self.s(idx * 2 + 1) as u64) << 32) | self.s(idx * 2) as u64
+    }
+    pub fn set_d(&mut self, idx: u32, val: u64) {
+        self.set_s(idx * 2, val as u32);
+        self.set_s(idx * 2 + 1, (val >> 32) as u32);
+    }
+
+    /// Read a single- or double-precision register.
+    fn get(&self, double: bool, idx: u32) -> u64 {
+        if double { self.d(idx) } else { self.s(idx) as u64 }
+    }
+    fn set(&mut self, double: bool, idx: u32, val: u64) {
+        if double { self.set_d(idx, val) } else { self.set_s(idx, val as u32) }
+    }
+
+    /// Returns true if a system register can be accessed. FPEXC and the
+    /// exceptional state registers are privileged, and FPSCR is only
+    /// accessible while the VFP is enabled.
+    pub fn accessible(&self, reg: VfpSysRegister, privileged: bool) -> bool {
+        match reg {
+            VfpSysRegister::fpsid => true,
+            VfpSysRegister::fpscr => self.enabled(),
+            _ => privileged,
+        }
+    }
+
+    /// Read a system register with `fmrx`.
+    pub fn read_sys(&self, reg: VfpSysRegister) -> u32 {
+        match reg {
+            VfpSysRegister::fpsid => self.fpsid,
+            VfpSysRegister::fpscr => self.fpscr.0,
+            VfpSysRegister::fpexc => self.fpexc,
+            VfpSysRegister::fpinst => self.fpinst,
+            VfpSysRegister::fpinst2 => self.fpinst2,
+        }
+    }
+
+    /// Write a system register with `fmxr`. FPSID is read-only.
+    pub fn write_sys(&mut self, reg: VfpSysRegister, val: u32) {
+        match reg {
+            VfpSysRegister::fpsid => {},
+            VfpSysRegister::fpscr => self.fpscr = Fpscr(val & FPSCR_WRITABLE),
+            VfpSysRegister::fpexc => self.fpexc = val & (FPEXC_EX | FPEXC_EN),
+            VfpSysRegister::fpinst => self.fpinst = val,
+            VfpSysRegister::fpinst2 => self.fpinst2 = val,
+        }
+    }
+
+    /// Execute a data-processing instruction (including compares and
+    /// conversions) on the register bank.
+    pub fn data_processing(&mut self, inst: VfpInst, op: &VfpBf) {
+        use VfpInst::*;
+        let double = inst.is_double();
+        let fmt = if double { F64 } else { F32 };
+        let mut fpscr = self.fpscr;
+        let (d, n, m) = match double {
+            true => (op.dd(), op.dn(), op.dm()),
+            false => (op.sd(), op.sn(), op.sm()),
+        };
+
+        match inst {
+            Fcmps | Fcmpd | Fcmpes | Fcmped | Fcmpzs | Fcmpzd | Fcmpezs | Fcmpezd => {
+                let zero = matches!(inst, Fcmpzs | Fcmpzd | Fcmpezs | Fcmpezd);
+                let signal = matches!(inst, Fcmpes | Fcmped | Fcmpezs | Fcmpezd);
+                let b = if zero { 0 } else { self.get(double, m) };
+                let flags = match compare(fmt, self.get(double, d), b, signal, &mut fpscr) {
+                    Some(Ordering::Less) => FPSCR_N,
+                    Some(Ordering::Equal) => FPSCR_Z | FPSCR_C,
+                    Some(Ordering::Greater) => FPSCR_C,
+                    Option::None => FPSCR_C | FPSCR_V,
+                };
+                fpscr.0 = (fpscr.0 & 0x0fff_ffff) | flags;
+            },
+            Fcvtds => {
+                let res = convert(F32, F64, self.s(op.sm()) as u64, &mut fpscr);
+                self.set_d(op.dd(), res);
+            },
+            Fcvtsd => {
+                let res = convert(F64, F32, self.d(op.dm()), &mut fpscr);
+                self.set_s(op.sd(), res as u32);
+            },
+            Fuitos | Fuitod | Fsitos | Fsitod => {
+                let signed = matches!(inst, Fsitos | Fsitod);
+                let res = from_int(fmt, self.s(op.sm()), signed, &mut fpscr);
+                self.set(double, d, res);
+            },
+            Ftouis | Ftouid | Ftouizs | Ftouizd | Ftosis | Ftosid | Ftosizs | Ftosizd => {
+                let signed = matches!(inst, Ftosis | Ftosid | Ftosizs | Ftosizd);
+                let rounding = match inst {
+                    Ftouizs | Ftouizd | Ftosizs | Ftosizd => Rounding::Zero,
+                    _ => fpscr.rounding(),
+                };
+                let res = to_int(fmt, self.get(double, m), signed, rounding, &mut fpscr);
+                self.set_s(op.sd(), res);
+            },
+            _ => {
+                // Short vectors wrap around within a bank of registers. A
+                // destination in the first bank makes the operation scalar,
+                // and an Fm in the first bank is reused for every element.
+                let bank = if double { 4 } else { 8 };
+                let len = if d < bank { 1 } else { fpscr.vector_len() };
+                let stride = fpscr.vector_stride();
+                let step = |r: u32, i: u32| (r & !(bank - 1)) | ((r + i * stride) & (bank - 1));
+                for i in 0..len {
+                    let dd = step(d, i);
+                    let mm = if m < bank { m } else { step(m, i) };
+                    let a = self.get(double, dd);
+                    let b = self.get(double, step(n, i));
+                    let c = self.get(double, mm);
+                    let f = &mut fpscr;
+                    let res = match inst {
+                        Fmacs | Fmacd => add(fmt, a, mul(fmt, b, c, f), f),
+                        Fnmacs | Fnmacd => add(fmt, a, fmt.neg(mul(fmt, b, c, f)), f),
+                        Fmscs | Fmscd => add(fmt, fmt.neg(a), mul(fmt, b, c, f), f),
+                        Fnmscs | Fnmscd => {
+                            add(fmt, fmt.neg(a), fmt.neg(mul(fmt, b, c, f)), f)
+                        },
+                        Fmuls | Fmuld => mul(fmt, b, c, f),
+                        Fnmuls | Fnmuld => fmt.neg(mul(fmt, b, c, f)),
+                        Fadds | Faddd => add(fmt, b, c, f),
+                        Fsubs | Fsubd => sub(fmt, b, c, f),
+                        Fdivs | Fdivd => div(fmt, b, c, f),
+                        Fcpys | Fcpyd => c,
+                        Fabss | Fabsd => fmt.abs(c),
+                        Fnegs | Fnegd => fmt.neg(c),
+                        Fsqrts | Fsqrtd => sqrt(fmt, c, f),
+                        _ => unreachable!("{:?} isn't a data-processing instruction", inst),
+                    };
+                    self.set(double, dd, res);
+                }
+            },
+        }
+        self.fpscr = fpscr;
+    }
+}
+
+
+/// Execute an instruction in the VFP space.
+///
+/// While the VFP is disabled, only the system registers can be accessed.
+pub fn execute<B: Bus>(cpu: &mut Cpu<B>, op: &VfpBf) -> Exec {
+    use VfpInst::*;
+    let inst = VfpInst::decode(op.0);
+    let sys = VfpSysRegister::from_u32;
+    let allowed = match inst {
+        None => false,
+        Fmxr | Fmrx => cpu.vfp.accessible(sys(op.crn()), cpu.regs.privileged()),
+        _ => cpu.vfp.enabled(),
+    };
+    if !allowed {
+        return Err(Trap::Undefined(op.0));
+    }
+
+    let (rd, rn) = (cpu.reg(op.rd()), cpu.reg(op.rn()));
+    match inst {
+        Flds | Fldd | Fsts | Fstd => return ls(cpu, inst, op),
+        Fldmias | Fldmiad | Fldmiax | Fldmdbs | Fldmdbd | Fldmdbx |
+        Fstmias | Fstmiad | Fstmiax | Fstmdbs | Fstmdbd | Fstmdbx => {
+            return ls_multi(cpu, inst, op);
+        },
+
+        Fmsr => cpu.vfp.set_s(op.sn(), rd),
+        Fmdlr => cpu.vfp.set_s(op.dn() * 2, rd),
+        Fmdhr => cpu.vfp.set_s(op.dn() * 2 + 1, rd),
+        Fmrs | Fmrdl | Fmrdh | Fmrx => {
+            let val = match inst {
+                Fmrs => cpu.vfp.s(op.sn()),
+                Fmrdl => cpu.vfp.s(op.dn() * 2),
+                Fmrdh => cpu.vfp.s(op.dn() * 2 + 1),
+                _ => cpu.vfp.read_sys(sys(op.crn())),
+            };
+            cpu.set_reg(op.rd(), val);
+        },
+        Fmxr => cpu.vfp.write_sys(sys(op.crn()), rd),
+        Fmstat => {
+            let cpsr = cpu.regs.cpsr();
+            cpu.regs.set_cpsr((cpsr & 0x0fff_ffff) | (cpu.vfp.fpscr.0 & 0xf000_0000));
+        },
+
+        // The first ARM register holds S(m) or the low half of D(m)
+        Fmsrr | Fmdrr => {
+            let first = if inst == Fmsrr { op.sm() } else { op.dm() * 2 };
+            cpu.vfp.set_s(first, rd);
+            cpu.vfp.set_s(first + 1, rn);
+        },
+        Fmrrs | Fmrrd => {
+            let first = if inst == Fmrrs { op.sm() } else { op.dm() * 2 };
+            let (lo, hi) = (cpu.vfp.s(first), cpu.vfp.s(first + 1));
+            cpu.set_reg(op.rd(), lo);
+            cpu.set_reg(op.rn(), hi);
+        },
+
+        _ => cpu.vfp.data_processing(inst, op),
+    }
+    Ok(())
+}
+
+/// Single-register loads and stores. Double-precision values are stored
+/// as two words, with the low half first.
+fn ls<B: Bus>(cpu: &mut Cpu<B>, inst: VfpInst, op: &VfpBf) -> Exec {
+    let base = cpu.reg(op.rn());
+    let offset = op.imm8() * 4;
+    let addr = if op.u() { base.wrapping_add(offset) } else { base.wrapping_sub(offset) };
+    match inst {
+        VfpInst::Flds => {
+            let val = cpu.read32(addr)?;
+            cpu.vfp.set_s(op.sd(), val);
+        },
+        VfpInst::Fldd => {
+            let lo = cpu.read32(addr)?;
+            let hi = cpu.read32(addr.wrapping_add(4))?;
+            cpu.vfp.set_d(op.dd(), ((hi as u64) << 32) | lo as u64);
+        },
+        VfpInst::Fsts => cpu.store32(addr, cpu.vfp.s(op.sd()))?,
+        _ => {
+            let val = cpu.vfp.d(op.dd());
+            cpu.store32(addr, val as u32)?;
+            cpu.store32(addr.wrapping_add(4), (val >> 32) as u32)?;
+        },
+    }
+    Ok(())
+}
+
+/// Load and store multiple. The offset counts words, so the X format
+/// transfers one word less than its offset (the extra word of a store
+/// isn't written).
+fn ls_multi<B: Bus>(cpu: &mut Cpu<B>, inst: VfpInst, op: &VfpBf) -> Exec {
+    let words = op.imm8();
+    let base = cpu.reg(op.rn());
+    let start = if op.p() { base.wrapping_sub(words * 4) } else { base };
+    let (first, count) = match inst.is_double() {
+        true => (op.dd() * 2, words & !1),
+        false => (op.sd(), words),
+    };
+    for i in 0..count {
+        let addr = start.wrapping_add(i * 4);
+        if op.l() {
+            let val = cpu.read32(addr)?;
+            cpu.vfp.set_s(first + i, val);
+        } else {
+            cpu.store32(addr, cpu.vfp.s(first + i))?;
+        }
+    }
+    if op.w() {
+        let end = if op.p() { start } else { base.wrapping_add(words * 4) };
+        cpu.set_reg(op.rn(), end);
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::test::exec;
+
+    #[test]
+    fn short_vectors() {
+        let mut vfp = Vfp::new();
+        for (i, reg) in vfp.regs.iter_mut().enumerate() {
+            *reg = (i as f32).to_bits();
+        }
+        let s = |vfp: &Vfp, idx: u32| f32::from_bits(vfp.s(idx));
+
+        // LEN=4, with Fd wrapping around from s14 to s8 and a scalar Fm
+        vfp.fpscr = Fpscr(0x0003_0000);
+        vfp.data_processing(VfpInst::Fadds, &VfpBf(0xee387a01)); // vadd.f32 s14, s16, s2
+        assert_eq!([s(&vfp, 14), s(&vfp, 15), s(&vfp, 8), s(&vfp, 9)],
+            [18.0, 19.0, 20.0, 21.0]);
+        assert_eq!(s(&vfp, 10), 10.0);
+
+        // LEN=2 with STRIDE=2, on double-precision registers
+        vfp.fpscr = Fpscr(0x0031_0000);
+        vfp.set_d(12, 1.5f64.to_bits());
+        vfp.set_d(14, 2.5f64.to_bits());
+        vfp.data_processing(VfpInst::Fnegd, &VfpBf(0xeeb14b4c)); // vneg.f64 d4, d12
+        assert_eq!([vfp.d(4), vfp.d(6)], [(-1.5f64).to_bits(), (-2.5f64).to_bits()]);
+
+        // A destination in the first bank is always scalar
+        vfp.fpscr = Fpscr(0x0001_0000);
+        vfp.data_processing(VfpInst::Fcpys, &VfpBf(0xeeb00a48)); // vmov.f32 s0, s16
+        assert_eq!([s(&vfp, 0), s(&vfp, 1)], [16.0, 1.0]);
+
+        // Compares are always scalar, and set the FPSCR flags
+        vfp.data_processing(VfpInst::Fcmpes, &VfpBf(0xeeb48ac5)); // vcmpe.f32 s16, s10
+        assert_eq!(vfp.fpscr.0, 0x2001_0000);
+    }
+
+    #[test]
+    fn accumulate() {
+        let mut vfp = Vfp::new();
+        let (a, b, c) = (1.0f64, 3.0f64, 1.0f64 / 3.0);
+        vfp.set_d(0, a.to_bits());
+        vfp.set_d(1, b.to_bits());
+        vfp.set_d(2, c.to_bits());
+        // The product is rounded before the addition, as b * c is exactly
+        // one in double precision.
+        vfp.data_processing(VfpInst::Fnmacd, &VfpBf(0xee010b42)); // vmls.f64 d0, d1, d2
+        assert_eq!(vfp.d(0), 0);
+        assert_eq!(vfp.fpscr.0, FPSCR_IXC);
+
+        vfp.set_d(0, a.to_bits());
+        vfp.data_processing(VfpInst::Fnmscd, &VfpBf(0xee110b42)); // vnmla.f64 d0, d1, d2
+        assert_eq!(f64::from_bits(vfp.d(0)), -2.0);
+        vfp.data_processing(VfpInst::Fmscd, &VfpBf(0xee110b02)); // vnmls.f64 d0, d1, d2
+        assert_eq!(f64::from_bits(vfp.d(0)), 3.0);
+
+        // Conversions between integers and floats
+        vfp.set_s(6, (-7i32) as u32);
+        vfp.data_processing(VfpInst::Fsitod, &VfpBf(0xeeb82bc3)); // vcvt.f64.s32 d2, s6
+        assert_eq!(f64::from_bits(vfp.d(2)), -7.0);
+        vfp.set_d(2, 2.5f64.to_bits());
+        vfp.data_processing(VfpInst::Ftosid, &VfpBf(0xeebd3b42)); // vcvtr.s32.f64 s6, d2
+        assert_eq!(vfp.s(6), 2);
+        vfp.data_processing(VfpInst::Ftouizd, &VfpBf(0xeebc3bc2)); // vcvt.u32.f64 s6, d2
+        assert_eq!(vfp.s(6), 2);
+    }
+
+    #[test]
+    fn interpreter() {
+        let interp = exec(&[], &[
+            0xe3a00101, // mov    r0, #0x40000000
+            0xeee80a10, // vmsr   fpexc, r0
+            0xe3a005ff, // mov    r0, #0x3fc00000
+            0xe3a01101, // mov    r1, #0x40000000
+            0xee000a10, // vmov   s0, r0
+            0xee001a90, // vmov   s1, r1
+            0xee201a20, // vmul.f32 s2, s0, s1
+            0xeeb72ac1, // vcvt.f64.f32 d2, s2
+            0xeeb13bc2, // vsqrt.f64 d3, d2
+            0xec532b13, // vmov   r2, r3, d3
+            0xeeb40a60, // vcmp.f32 s0, s1
+            0xeef1fa10, // vmrs   APSR_nzcv, fpscr
+            0x43a04001, // movmi  r4, #1
+            0xe3a05b02, // mov    r5, #0x800
+            0xeca50a02, // vstmia r5!, {s0, s1}
+            0xed158a02, // vldr   s16, [r5, #-8]
+            0xed558a01, // vldr   s17, [r5, #-4]
+            0xe3a00801, // mov    r0, #0x10000
+            0xeee10a10, // vmsr   fpscr, r0
+            0xee384a00, // vadd.f32 s8, s16, s0
+            0xec576a14, // vmov   r6, r7, s8, s9
+            0xeebd5ac4, // vcvt.s32.f32 s10, s8
+            0xee158a10, // vmov   r8, s10
+        ]);
+        let regs = &interp.cpu.regs;
+        assert_eq!((regs.get(2), regs.get(3)), (0xe858_4caa, 0x3ffb_b67a));
+        assert_eq!((regs.get(4), regs.get(5)), (1, 0x808));
+        assert_eq!((regs.get(6), regs.get(7)), (3.0f32.to_bits(), 3.5f32.to_bits()));
+        assert_eq!(regs.get(8), 3);
+        // Writing the FPSCR replaced the flags from the compare and vsqrt
+        assert_eq!(interp.cpu.vfp.fpscr.0, 0x0001_0000);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut cpu = Cpu::new(Ram::new(0, 0x20));
+        // vadd.f32 s0, s0, s2
+        assert_eq!(execute(&mut cpu, &VfpBf(0xee300a01)), Err(Trap::Undefined(0xee300a01)));
+        // FPSID is always readable, but FPEXC is privileged
+        execute(&mut cpu, &VfpBf(0xeef01a10)).unwrap(); // vmrs r1, fpsid
+        assert_eq!(cpu.regs.get(1), FPSID_VFP9);
+        cpu.regs.set_cpsr(Mode::Usr.bits());
+        assert!(execute(&mut cpu, &VfpBf(0xeef81a10)).is_err()); // vmrs r1, fpexc
+    }
+}
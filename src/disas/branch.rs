@@ -0,0 +1,42 @@
+use crate::newtype::*;
+use crate::traits::*;
+
+use super::{DisasCtx, Reg, CondName};
+
+/// Sign extend to some number of bits
+#[inline(always)]
+pub fn sign_extend(x: i32, bits: i32) -> i32 {
+    if ((x >> (bits - 1)) & 1) != 0 {
+        return x | !0 << bits
+    }
+    x
+}
+
+/// Compute the target address of a branch (relative to the PC, which is
+/// the address of the instruction plus 8).
+fn target(ctx: &DisasCtx, op: &BranchBf) -> u32 {
+    let imm24 = sign_extend(op.imm24() as i32, 24) << 2;
+    ctx.offset.wrapping_add(8).wrapping_add(imm24 as u32)
+}
+
+
+pub fn blx_imm(ctx: &mut DisasCtx, op: &BranchBf) -> String {
+    // The H bit selects a halfword-aligned Thumb target
+    let h = (op.link() as u32) << 1;
+    format!("blx\t0x{:x}", target(ctx, op).wrapping_add(h))
+}
+pub fn blx_reg(_ctx: &mut DisasCtx, op: &BxBf) -> String {
+    format!("blx{}\t{}", CondName::from_u32(op.cond()), Reg::from_u32(op.rm()))
+}
+
+pub fn bx(_ctx: &mut DisasCtx, op: &BxBf) -> String {
+    format!("bx{}\t{}", CondName::from_u32(op.cond()), Reg::from_u32(op.rm()))
+}
+
+pub fn bl(ctx: &mut DisasCtx, op: &BranchBf) -> String {
+    format!("bl{}\t0x{:x}", CondName::from_u32(op.cond()), target(ctx, op))
+}
+
+pub fn b(ctx: &mut DisasCtx, op: &BranchBf) -> String {
+    format!("b{}\t0x{:x}", CondName::from_u32(op.cond()), target(ctx, op))
+}
@@ -0,0 +1,130 @@
+use crate::newtype::*;
+use crate::traits::*;
+use crate::fields::*;
+use crate::sysreg::{Dir, SysReg};
+use crate::inst::{FpaInst, VfpInst};
+
+use super::{DisasCtx, Reg, CondName, CReg};
+
+/// The floating-point coprocessors: the VFP claims the conditional
+/// instructions on cp10 and cp11, and the FPA those on cp1 and cp2 which it
+/// defines. Anything else is left to the generic coprocessor syntax.
+fn fp(op: &CoprocBf) -> Option<String> {
+    if VfpInst::is_vfp(op.0) {
+        return Some(super::vfp::vfp(&VfpBf(op.0)));
+    }
+    (FpaInst::decode(op.0) != FpaInst::None).then(|| super::fpa::fpa(&FpaBf(op.0)))
+}
+
+/// Mnemonic for a coprocessor instruction. The unconditional encodings
+/// (cond=1111) are the ARMv5 "2" variants.
+fn mnemonic(name: &'static str, cond: u32) -> String {
+    match cond {
+        0b1111 => format!("{}2", name),
+        _ => format!("{}{}", name, CondName::from_u32(cond)),
+    }
+}
+
+fn reg_transfer(ctx: &DisasCtx, op: &CoprocBf, name: &'static str, dir: Dir)
+    -> String
+{
+    if let Some(res) = fp(op) {
+        return res;
+    }
+    let mut res = format!("{}\t{}, #{}, {}, {}, {}, #{}",
+        mnemonic(name, op.cond()),
+        CoprocNumber::from_u32(op.cp_num()),
+        op.opcd1_rt(),
+        Reg::from_u32(op.rd()),
+        CReg::from_u32(op.crn()),
+        CReg::from_u32(op.crm()),
+        op.opcd2(),
+    );
+    // The unconditional forms are entirely coprocessor-defined.
+    if let (Some(core), false) = (ctx.sysregs, op.cond() == 0b1111) {
+        if let Some(reg) = SysReg::from_inst(op).name(core, dir) {
+            res.push_str(&format!("\t@ {}", reg));
+        }
+    }
+    res
+}
+pub fn mrc(ctx: &mut DisasCtx, op: &CoprocBf) -> String {
+    reg_transfer(ctx, op, "mrc", Dir::Read)
+}
+pub fn mcr(ctx: &mut DisasCtx, op: &CoprocBf) -> String {
+    reg_transfer(ctx, op, "mcr", Dir::Write)
+}
+
+pub fn cdp(_ctx: &mut DisasCtx, op: &CoprocBf) -> String {
+    if let Some(res) = fp(op) {
+        return res;
+    }
+    format!("{}\t{}, #{}, {}, {}, {}, #{}",
+        mnemonic("cdp", op.cond()),
+        CoprocNumber::from_u32(op.cp_num()),
+        op.opcd1(),
+        CReg::from_u32(op.crd()),
+        CReg::from_u32(op.crn()),
+        CReg::from_u32(op.crm()),
+        op.opcd2(),
+    )
+}
+
+/// Two-register transfers (MCRR/MRRC).
+fn reg_transfer2(op: &CoprocBf, name: &'static str) -> String {
+    if let Some(res) = fp(op) {
+        return res;
+    }
+    format!("{}\t{}, #{}, {}, {}, {}",
+        mnemonic(name, op.cond()),
+        CoprocNumber::from_u32(op.cp_num()),
+        op.opcd_rr(),
+        Reg::from_u32(op.rd()),
+        Reg::from_u32(op.rn()),
+        CReg::from_u32(op.crm()),
+    )
+}
+pub fn mrrc(_ctx: &mut DisasCtx, op: &CoprocBf) -> String {
+    reg_transfer2(op, "mrrc")
+}
+pub fn mcrr(_ctx: &mut DisasCtx, op: &CoprocBf) -> String {
+    reg_transfer2(op, "mcrr")
+}
+
+/// The address operand of a coprocessor load/store (addressing mode 5).
+pub(super) fn address<T: CoprocBits + LsBits + ImmBits + RegBits>(op: &T) -> String {
+    let rn = Reg::from_u32(op.rn());
+    let sign = if op.u() { "" } else { "-" };
+    let imm = op.imm8() * 4;
+    match (op.p(), op.w()) {
+        (true, false) if op.u() && imm == 0 => format!("[{}]", rn),
+        (true, false) => format!("[{}, #{}{}]", rn, sign, imm),
+        (true, true) => format!("[{}, #{}{}]!", rn, sign, imm),
+        (false, true) => format!("[{}], #{}{}", rn, sign, imm),
+        (false, false) => format!("[{}], {{{}}}", rn, op.option()),
+    }
+}
+
+/// LDC/STC.
+fn coproc_ls(op: &CoprocBf, name: &'static str) -> String {
+    if let Some(res) = fp(op) {
+        return res;
+    }
+    // The long flag follows the "2" but precedes a condition: ldc2l, ldcleq.
+    let long = if op.n() { "l" } else { "" };
+    let mnemonic = match op.cond() {
+        0b1111 => format!("{}2{}", name, long),
+        cond => format!("{}{}{}", name, long, CondName::from_u32(cond)),
+    };
+    format!("{}\t{}, {}, {}", mnemonic,
+        CoprocNumber::from_u32(op.cp_num()),
+        CReg::from_u32(op.crd()),
+        address(op),
+    )
+}
+pub fn ldc(_ctx: &mut DisasCtx, op: &CoprocBf) -> String {
+    coproc_ls(op, "ldc")
+}
+pub fn stc(_ctx: &mut DisasCtx, op: &CoprocBf) -> String {
+    coproc_ls(op, "stc")
+}
@@ -0,0 +1,74 @@
+use crate::newtype::*;
+use crate::traits::*;
+
+use super::{DisasCtx, mod_imm, Reg, CondName};
+
+pub fn svc(_ctx: &mut DisasCtx, op: &SwiBf) -> String {
+    format!("svc{}\t#{}", CondName::from_u32(op.cond()), op.imm24())
+}
+
+pub fn bkpt(_ctx: &mut DisasCtx, op: &BkptBf) -> String {
+    format!("bkpt\t#{}", (op.imm12_hi() << 4) | op.imm4())
+}
+
+pub fn mrs(_ctx: &mut DisasCtx, op: &StatusBf) -> String {
+    let sr_name = if op.r() { "spsr" } else { "apsr" };
+    format!("mrs{}\t{}, {}", CondName::from_u32(op.cond()),
+        Reg::from_u32(op.rd()), sr_name
+    )
+}
+
+/// Format the status register and field mask for MSR.
+///
+/// Writes to the CPSR which only touch the flags are printed with the
+/// application-level (APSR) names.
+fn msr_fields(op: &StatusBf) -> String {
+    if !op.r() {
+        match op.field_mask() {
+            0b0100 => return "APSR_g".to_string(),
+            0b1000 => return "APSR_nzcvq".to_string(),
+            0b1100 => return "APSR_nzcvqg".to_string(),
+            _ => {},
+        }
+    }
+    let sr_name = if op.r() { "SPSR" } else { "CPSR" };
+    let mut fields_str = std::string::String::new();
+    for (idx, bit) in [(3, "f"), (2, "s"), (1, "x"), (0, "c")].iter() {
+        if (op.field_mask() & (1 << idx)) != 0 {
+            fields_str.push_str(bit);
+        }
+    }
+    format!("{}_{}", sr_name, fields_str)
+}
+
+pub fn msr_imm(_ctx: &mut DisasCtx, op: &StatusBf) -> String {
+    format!("msr{}\t{}, {}", CondName::from_u32(op.cond()), msr_fields(op),
+        mod_imm(op.imm8(), op.rot_imm(), true),
+    )
+}
+
+pub fn msr_reg(_ctx: &mut DisasCtx, op: &StatusBf) -> String {
+    format!("msr{}\t{}, {}", CondName::from_u32(op.cond()), msr_fields(op),
+        Reg::from_u32(op.rm()),
+    )
+}
+
+
+fn sat_generic(op: &SatBf, name: &'static str) -> String {
+    format!("{}{}\t{}, {}, {}", name,
+        CondName::from_u32(op.cond()),
+        Reg::from_u32(op.rd()),
+        Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rn()),
+    )
+}
+pub fn qadd(_ctx: &mut DisasCtx, op: &SatBf) -> String { sat_generic(op, "qadd") }
+pub fn qdadd(_ctx: &mut DisasCtx, op: &SatBf) -> String { sat_generic(op, "qdadd") }
+pub fn qsub(_ctx: &mut DisasCtx, op: &SatBf) -> String { sat_generic(op, "qsub") }
+pub fn qdsub(_ctx: &mut DisasCtx, op: &SatBf) -> String { sat_generic(op, "qdsub") }
+
+pub fn clz(_ctx: &mut DisasCtx, op: &ClzBf) -> String {
+    format!("clz{}\t{}, {}", CondName::from_u32(op.cond()),
+        Reg::from_u32(op.rd()), Reg::from_u32(op.rm()),
+    )
+}
@@ -0,0 +1,94 @@
+use crate::newtype::*;
+use crate::traits::*;
+use crate::fields::*;
+
+use super::{DisasCtx, mod_imm, shifted_reg, Reg, CondName};
+
+/// Mnemonic for a data-processing instruction (including the S bit, if it
+/// isn't implied by the opcode).
+fn mnemonic(name: &dyn std::fmt::Display, opcd: &Opcode, s: bool, cond: u32)
+    -> String
+{
+    let s = match opcd {
+        Opcode::Tst | Opcode::Teq | Opcode::Cmp | Opcode::Cmn => "",
+        _ => if s { "s" } else { "" },
+    };
+    format!("{}{}{}", name, s, CondName::from_u32(cond))
+}
+
+pub fn rot_imm(_ctx: &mut DisasCtx, op: &DpRotImmBf) -> String {
+    let opcd = Opcode::from_u32(op.opcd());
+    let rd = Reg::from_u32(op.rd());
+    let rn = Reg::from_u32(op.rn());
+    let name = mnemonic(&opcd, &opcd, op.s(), op.cond());
+
+    // Moves to the PC are printed unsigned
+    let imm = mod_imm(op.imm8(), op.rot_imm(),
+        matches!(opcd, Opcode::Mov) && op.rd() == 15);
+
+    match opcd {
+        Opcode::Tst | Opcode::Teq | Opcode::Cmp | Opcode::Cmn => {
+            format!("{}\t{}, {}", name, rn, imm)
+        },
+        Opcode::Mov | Opcode::Mvn => format!("{}\t{}, {}", name, rd, imm),
+
+        // PC-relative address generation
+        Opcode::Add | Opcode::Sub if op.rn() == 15 && !op.s() => {
+            let val = op.imm8().rotate_right(op.rot_imm() * 2);
+            let sign = if matches!(opcd, Opcode::Sub) { "-" } else { "" };
+            format!("adr{}\t{}, #{}{}", CondName::from_u32(op.cond()), rd,
+                sign, val)
+        },
+        _ => format!("{}\t{}, {}, {}", name, rd, rn, imm),
+    }
+}
+
+pub fn shift_imm(_ctx: &mut DisasCtx, op: &DpShiftBf) -> String {
+    let opcd = Opcode::from_u32(op.opcd());
+    let rd = Reg::from_u32(op.rd());
+    let rn = Reg::from_u32(op.rn());
+    let operand = shifted_reg(op.rm(), op.shift(), op.shift_imm());
+    let name = mnemonic(&opcd, &opcd, op.s(), op.cond());
+
+    match opcd {
+        Opcode::Tst | Opcode::Teq | Opcode::Cmp | Opcode::Cmn => {
+            format!("{}\t{}, {}", name, rn, operand)
+        },
+        // Shifted moves are printed as the shift itself
+        Opcode::Mov if op.shift() == 0b11 && op.shift_imm() == 0 => {
+            let name = mnemonic(&"rrx", &opcd, op.s(), op.cond());
+            format!("{}\t{}, {}", name, rd, Reg::from_u32(op.rm()))
+        },
+        Opcode::Mov if op.shift() != 0 || op.shift_imm() != 0 => {
+            let st = ShifterType::from_u32(op.shift());
+            let name = mnemonic(&st, &opcd, op.s(), op.cond());
+            let imm = if op.shift_imm() == 0 { 32 } else { op.shift_imm() };
+            format!("{}\t{}, {}, #{}", name, rd, Reg::from_u32(op.rm()),
+                imm)
+        },
+        Opcode::Mov | Opcode::Mvn => format!("{}\t{}, {}", name, rd, operand),
+        _ => format!("{}\t{}, {}, {}", name, rd, rn, operand),
+    }
+}
+
+pub fn shift_reg(_ctx: &mut DisasCtx, op: &DpShiftBf) -> String {
+    let opcd = Opcode::from_u32(op.opcd());
+    let rd = Reg::from_u32(op.rd());
+    let rn = Reg::from_u32(op.rn());
+    let rm = Reg::from_u32(op.rm());
+    let rs = Reg::from_u32(op.rs());
+    let st = ShifterType::from_u32(op.shift());
+    let name = mnemonic(&opcd, &opcd, op.s(), op.cond());
+
+    match opcd {
+        Opcode::Tst | Opcode::Teq | Opcode::Cmp | Opcode::Cmn => {
+            format!("{}\t{}, {}, {} {}", name, rn, rm, st, rs)
+        },
+        Opcode::Mov => {
+            let name = mnemonic(&st, &opcd, op.s(), op.cond());
+            format!("{}\t{}, {}, {}", name, rd, rm, rs)
+        },
+        Opcode::Mvn => format!("{}\t{}, {}, {} {}", name, rd, rm, st, rs),
+        _ => format!("{}\t{}, {}, {}, {} {}", name, rd, rn, rm, st, rs),
+    }
+}
@@ -0,0 +1,83 @@
+use crate::newtype::*;
+use crate::traits::*;
+use crate::fields::*;
+use crate::inst::FpaInst;
+
+use super::{CondName, Reg};
+use super::cp::address;
+
+/// The constants which can be used in place of Fm.
+const CONSTANTS: [&str; 8] = ["0.0", "1.0", "2.0", "3.0", "4.0", "5.0", "0.5", "10.0"];
+
+/// The Fm operand of a data-processing instruction or compare.
+fn fm(op: &FpaBf) -> String {
+    match op.fm_imm() {
+        true => format!("#{}", CONSTANTS[op.fm() as usize]),
+        false => FpaRegister::from_u32(op.fm()).to_string(),
+    }
+}
+
+/// The mnemonic with its condition, precision and rounding suffixes (which
+/// follow the condition, as in pre-UAL syntax).
+fn mnemonic(inst: FpaInst, op: &FpaBf) -> String {
+    format!("{}{}{}{}", name(inst), CondName::from_u32(op.cond()),
+        FpaPrecision::from_u32(op.prec()), FpaRounding::from_u32(op.rounding()))
+}
+
+fn name(inst: FpaInst) -> String {
+    format!("{:?}", inst).to_lowercase()
+}
+
+/// Disassemble an instruction in the FPA space.
+pub fn fpa(op: &FpaBf) -> String {
+    use FpaInst::*;
+    let inst = FpaInst::decode(op.0);
+    let cond = CondName::from_u32(op.cond());
+    let fd = FpaRegister::from_u32(op.fd());
+    let fn_ = FpaRegister::from_u32(op.fn_());
+    let rd = Reg::from_u32(op.rd());
+    match inst {
+        None => "<unknown>".to_string(),
+
+        Ldf | Stf => format!("{}{}{}\t{}, {}", name(inst), cond,
+            FpaPrecision::from_u32(op.ls_prec()), fd, address(op)),
+        // Transfers of four registers are encoded as zero
+        Lfm | Sfm => format!("{}{}\t{}, {}, {}", name(inst), cond, fd,
+            [4, 1, 2, 3][op.ls_prec() as usize], address(op)),
+
+        _ if inst.is_dyadic() => {
+            format!("{}\t{}, {}, {}", mnemonic(inst, op), fd, fn_, fm(op))
+        },
+        _ if inst.is_monadic() => format!("{}\t{}, {}", mnemonic(inst, op), fd, fm(op)),
+
+        Flt => format!("{}\t{}, {}", mnemonic(inst, op), fn_, rd),
+        Fix => format!("fix{}{}\t{}, {}", cond, FpaRounding::from_u32(op.rounding()),
+            rd, FpaRegister::from_u32(op.fm())),
+        Wfs | Rfs | Wfc | Rfc => format!("{}{}\t{}", name(inst), cond, rd),
+        _ => format!("{}{}\t{}, {}", name(inst), cond, fn_, fm(op)),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn operands() {
+        let dis = |x: u32| fpa(&FpaBf(x));
+        assert_eq!(dis(0xed918102), "ldfd\tf0, [r1, #8]");
+        assert_eq!(dis(0xed6d1103), "stfe\tf1, [sp, #-12]!");
+        assert_eq!(dis(0xece2f101), "stfp\tf7, [r2], #4");
+        assert_eq!(dis(0xecbd420c), "lfm\tf4, 4, [sp], #48");
+        assert_eq!(dis(0xedc08200), "sfm\tf0, 3, [r0]");
+        assert_eq!(dis(0x0e000100), "adfeqs\tf0, f0, f0");
+        assert_eq!(dis(0xee1c316f), "mufez\tf3, f4, #10.0");
+        assert_eq!(dis(0xee40a1a7), "sqtdp\tf2, f7");
+        assert_eq!(dis(0xee013190), "fltd\tf1, r3");
+        assert_eq!(dis(0xee100172), "fixz\tr0, f2");
+        assert_eq!(dis(0x1e502110), "rfcne\tr2");
+        assert_eq!(dis(0xeef3f118), "cnfe\tf3, #0.0");
+        assert_eq!(dis(0xeed00100), "<unknown>");
+    }
+}
@@ -0,0 +1,143 @@
+use crate::newtype::*;
+use crate::traits::*;
+
+use super::{DisasCtx, reglist, shifted_reg, Reg, CondName};
+
+/// Mnemonic for a word/byte load/store. Post-indexed forms with the W bit
+/// set are the user-mode ("translated") variants.
+fn ls_mnemonic(name: &'static str, p: bool, w: bool, cond: u32) -> String {
+    let t = if !p && w { "t" } else { "" };
+    format!("{}{}{}", name, t, CondName::from_u32(cond))
+}
+
+/// Format an addressing mode with an immediate offset.
+fn imm_addr(rn: u32, p: bool, u: bool, w: bool, imm: u32) -> String {
+    let rn = Reg::from_u32(rn);
+    let sign = if u { "" } else { "-" };
+    match (p, w) {
+        (true, false) if u && imm == 0 => format!("[{}]", rn),
+        (true, false) => format!("[{}, #{}{}]", rn, sign, imm),
+        (true, true) => format!("[{}, #{}{}]!", rn, sign, imm),
+        (false, _) => format!("[{}], #{}{}", rn, sign, imm),
+    }
+}
+
+/// Format an addressing mode with a register offset.
+fn reg_addr(rn: u32, p: bool, u: bool, w: bool, offset: String) -> String {
+    let rn = Reg::from_u32(rn);
+    let sign = if u { "" } else { "-" };
+    match (p, w) {
+        (true, false) => format!("[{}, {}{}]", rn, sign, offset),
+        (true, true) => format!("[{}, {}{}]!", rn, sign, offset),
+        (false, _) => format!("[{}], {}{}", rn, sign, offset),
+    }
+}
+
+
+pub fn ls_imm(op: &LsImmBf, name: &'static str) -> String {
+    let rd = Reg::from_u32(op.rd());
+
+    // Single-register push/pop
+    if op.rn() == 13 && !op.b() && op.imm12() == 4 {
+        match (op.l(), op.p(), op.u(), op.w()) {
+            (false, true, false, true) => return format!("push{}\t{{{}}}",
+                CondName::from_u32(op.cond()), rd),
+            (true, false, true, false) => return format!("pop{}\t{{{}}}",
+                CondName::from_u32(op.cond()), rd),
+            _ => {},
+        }
+    }
+    format!("{}\t{}, {}", ls_mnemonic(name, op.p(), op.w(), op.cond()), rd,
+        imm_addr(op.rn(), op.p(), op.u(), op.w(), op.imm12()))
+}
+pub fn ldrb_imm(_ctx: &mut DisasCtx, op: &LsImmBf) -> String { ls_imm(op, "ldrb") }
+pub fn strb_imm(_ctx: &mut DisasCtx, op: &LsImmBf) -> String { ls_imm(op, "strb") }
+pub fn ldr_imm(_ctx: &mut DisasCtx, op: &LsImmBf) -> String { ls_imm(op, "ldr") }
+pub fn str_imm(_ctx: &mut DisasCtx, op: &LsImmBf) -> String { ls_imm(op, "str") }
+
+
+pub fn ls_reg(op: &LsShiftBf, name: &'static str) -> String {
+    let offset = shifted_reg(op.rm(), op.shift(), op.shift_imm());
+    format!("{}\t{}, {}", ls_mnemonic(name, op.p(), op.w(), op.cond()),
+        Reg::from_u32(op.rd()),
+        reg_addr(op.rn(), op.p(), op.u(), op.w(), offset))
+}
+pub fn ldrb_reg(_ctx: &mut DisasCtx, op: &LsShiftBf) -> String { ls_reg(op, "ldrb") }
+pub fn strb_reg(_ctx: &mut DisasCtx, op: &LsShiftBf) -> String { ls_reg(op, "strb") }
+pub fn ldr_reg(_ctx: &mut DisasCtx, op: &LsShiftBf) -> String { ls_reg(op, "ldr") }
+pub fn str_reg(_ctx: &mut DisasCtx, op: &LsShiftBf) -> String { ls_reg(op, "str") }
+
+
+pub fn ls_multi(op: &LsMultiBf, name: &'static str, mode: &'static str)
+    -> String
+{
+    let cond = CondName::from_u32(op.cond());
+    let user = if op.s() { " ^" } else { "" };
+
+    // Multi-register push/pop
+    if op.rn() == 13 && op.w() && !op.s() && op.reglist().count_ones() > 1 {
+        match (name, mode) {
+            ("stm", "db") => return format!("push{}\t{}", cond,
+                reglist(op.reglist())),
+            ("ldm", "") => return format!("pop{}\t{}", cond,
+                reglist(op.reglist())),
+            _ => {},
+        }
+    }
+    let wb = if op.w() { "!" } else { "" };
+    format!("{}{}{}\t{}{}, {}{}", name, mode, cond,
+        Reg::from_u32(op.rn()), wb, reglist(op.reglist()), user)
+}
+pub fn ldmib(_ctx: &mut DisasCtx, op: &LsMultiBf) -> String { ls_multi(op, "ldm", "ib") }
+pub fn ldmia(_ctx: &mut DisasCtx, op: &LsMultiBf) -> String { ls_multi(op, "ldm", "") }
+pub fn ldmdb(_ctx: &mut DisasCtx, op: &LsMultiBf) -> String { ls_multi(op, "ldm", "db") }
+pub fn ldmda(_ctx: &mut DisasCtx, op: &LsMultiBf) -> String { ls_multi(op, "ldm", "da") }
+pub fn stmib(_ctx: &mut DisasCtx, op: &LsMultiBf) -> String { ls_multi(op, "stm", "ib") }
+pub fn stmia(_ctx: &mut DisasCtx, op: &LsMultiBf) -> String { ls_multi(op, "stm", "") }
+pub fn stmdb(_ctx: &mut DisasCtx, op: &LsMultiBf) -> String { ls_multi(op, "stm", "db") }
+pub fn stmda(_ctx: &mut DisasCtx, op: &LsMultiBf) -> String { ls_multi(op, "stm", "da") }
+
+
+/// Destination register(s) for halfword/signed/doubleword transfers.
+fn ls_misc_regs(op: &LsMiscBf, name: &'static str) -> String {
+    match name {
+        "ldrd" | "strd" => format!("{}, {}", Reg::from_u32(op.rd()),
+            Reg::from_u32((op.rd() + 1) & 0xf)),
+        _ => format!("{}", Reg::from_u32(op.rd())),
+    }
+}
+
+pub fn ls_misc_imm(op: &LsMiscBf, name: &'static str) -> String {
+    let imm = (op.off_hi() << 4) | op.off_lo();
+    format!("{}{}\t{}, {}", name, CondName::from_u32(op.cond()),
+        ls_misc_regs(op, name), imm_addr(op.rn(), op.p(), op.u(), op.w(), imm))
+}
+pub fn ldrh_imm(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_imm(op, "ldrh") }
+pub fn strh_imm(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_imm(op, "strh") }
+pub fn strd_imm(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_imm(op, "strd") }
+pub fn ldrd_imm(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_imm(op, "ldrd") }
+pub fn ldrsh_imm(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_imm(op, "ldrsh") }
+pub fn ldrsb_imm(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_imm(op, "ldrsb") }
+
+
+pub fn ls_misc_reg(op: &LsMiscBf, name: &'static str) -> String {
+    let rm = Reg::from_u32(op.rm()).to_string();
+    format!("{}{}\t{}, {}", name, CondName::from_u32(op.cond()),
+        ls_misc_regs(op, name), reg_addr(op.rn(), op.p(), op.u(), op.w(), rm))
+}
+pub fn ldrh_reg(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_reg(op, "ldrh") }
+pub fn strh_reg(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_reg(op, "strh") }
+pub fn strd_reg(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_reg(op, "strd") }
+pub fn ldrd_reg(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_reg(op, "ldrd") }
+pub fn ldrsh_reg(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_reg(op, "ldrsh") }
+pub fn ldrsb_reg(_ctx: &mut DisasCtx, op: &LsMiscBf) -> String { ls_misc_reg(op, "ldrsb") }
+
+
+pub fn swp_generic(op: &SwpBf, name: &'static str) -> String {
+    format!("{}{}\t{}, {}, [{}]", name, CondName::from_u32(op.cond()),
+        Reg::from_u32(op.rd()), Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rn()),
+    )
+}
+pub fn swp(_ctx: &mut DisasCtx, op: &SwpBf) -> String { swp_generic(op, "swp") }
+pub fn swpb(_ctx: &mut DisasCtx, op: &SwpBf) -> String { swp_generic(op, "swpb") }
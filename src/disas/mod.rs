@@ -0,0 +1,512 @@
+//! A text disassembler built on top of the lookup tables.
+//!
+//! Output follows the syntax used by `llvm-objdump` (UAL mnemonics, decimal
+//! immediates, absolute branch targets) so that it can be compared against
+//! reference listings line-by-line.
+
+use crate::inst::*;
+use crate::lut::*;
+use crate::fields::*;
+use crate::sysreg::Core;
+
+use std::fmt;
+
+pub mod mul;
+pub mod ctrl;
+pub mod branch;
+pub mod ls;
+pub mod dp;
+pub mod cp;
+pub mod vfp;
+pub mod fpa;
+pub mod thumb;
+
+/// An instruction set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Isa { Arm, Thumb }
+
+/// State carried between calls to the disassembly handlers.
+pub struct DisasCtx {
+    /// Offset of the current instruction.
+    pub offset: u32,
+    /// Core used to name system registers in comments, if any.
+    pub sysregs: Option<Core>,
+}
+
+/// The undefined instruction handler.
+pub fn undef_instr(_ctx: &mut DisasCtx, _x: u32) -> String {
+    "<unknown>".to_string()
+}
+pub fn undef_instr_thumb(_ctx: &mut DisasCtx, _x: u16) -> String {
+    "<unknown>".to_string()
+}
+
+/// A core register as llvm-objdump spells it, i.e. `r12` rather than the
+/// `ip` used by [Register].
+#[derive(Clone, Copy)]
+pub(crate) struct Reg(u32);
+impl Reg {
+    pub(crate) fn from_u32(x: u32) -> Self { Reg(x) }
+}
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            12 => write!(f, "r12"),
+            x => write!(f, "{}", Register::from_u32(x)),
+        }
+    }
+}
+
+/// A condition code suffix as llvm-objdump spells it, i.e. `hs` and `lo`
+/// rather than the `cs` and `cc` used by [Cond].
+#[derive(Clone, Copy)]
+pub(crate) struct CondName(u32);
+impl CondName {
+    pub(crate) fn from_u32(x: u32) -> Self { CondName(x) }
+}
+impl fmt::Display for CondName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match Cond::from_u32(self.0) {
+            Cond::Cs => write!(f, "hs"),
+            Cond::Cc => write!(f, "lo"),
+            cond => write!(f, "{}", cond),
+        }
+    }
+}
+
+/// A coprocessor register as llvm-objdump spells it, i.e. `c7` rather than
+/// the `cr7` used by [CoprocRegister].
+#[derive(Clone, Copy)]
+pub(crate) struct CReg(u32);
+impl CReg {
+    pub(crate) fn from_u32(x: u32) -> Self { CReg(x) }
+}
+impl fmt::Display for CReg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "c{}", self.0)
+    }
+}
+
+/// Format a register list, i.e. `{r0, r1, lr}`.
+pub(crate) fn reglist(list: u32) -> String {
+    let regs: Vec<String> = (0..16).filter(|idx| (list & (1 << idx)) != 0)
+        .map(|idx| Reg::from_u32(idx).to_string())
+        .collect();
+    format!("{{{}}}", regs.join(", "))
+}
+
+/// Format a register with an immediate shift (as in data-processing and
+/// load/store register operands).
+pub(crate) fn shifted_reg(rm: u32, shift: u32, shift_imm: u32) -> String {
+    let rm = Reg::from_u32(rm);
+    match (ShifterType::from_u32(shift), shift_imm) {
+        (ShifterType::Lsl, 0) => format!("{}", rm),
+        (ShifterType::Ror, 0) => format!("{}, rrx", rm),
+        (st, 0) => format!("{}, {} #32", rm, st),
+        (st, imm) => format!("{}, {} #{}", rm, st, imm),
+    }
+}
+
+/// Format a "modified immediate" (an 8-bit value rotated right by twice the
+/// 4-bit rotate field).
+///
+/// Values are printed in decimal when the encoding uses the smallest possible
+/// rotation, and as `#imm8, #rot` otherwise (so that they can be re-assembled
+/// into the same encoding).
+pub(crate) fn mod_imm(imm8: u32, rot_imm: u32, unsigned: bool) -> String {
+    let rot = rot_imm * 2;
+    let val = imm8.rotate_right(rot);
+    if canonical_rot(val) == Some(rot) {
+        if unsigned {
+            format!("#{}", val)
+        } else {
+            format!("#{}", val as i32)
+        }
+    } else {
+        format!("#{}, #{}", imm8, rot)
+    }
+}
+
+/// Find the rotation an assembler would pick when encoding some value as a
+/// modified immediate.
+fn canonical_rot(val: u32) -> Option<u32> {
+    if val & !0xff == 0 {
+        return Some(0);
+    }
+    let mut rot = (32 - (val.trailing_zeros() & !1)) & 31;
+    if val.rotate_left(rot) & !0xff != 0 && (val & 0x3f) != 0 {
+        rot = (32 - ((val & !0x3f).trailing_zeros() & !1)) & 31;
+    }
+    if val.rotate_left(rot) & !0xff != 0 {
+        return None;
+    }
+    Some(rot)
+}
+
+
+impl ThumbHandlers<String> for DisasCtx {
+    fn thumb_handler(inst: ThumbInst) -> ThumbFn<Self, String> {
+        match inst {
+            ThumbInst::AddReg1 =>   ThumbFn::DpFmt1(thumb::add_reg1),
+            ThumbInst::SubReg =>    ThumbFn::DpFmt1(thumb::sub_reg),
+            ThumbInst::AddImm1 =>   ThumbFn::DpFmt2(thumb::add_imm1),
+            ThumbInst::SubImm1 =>   ThumbFn::DpFmt2(thumb::sub_imm1),
+            ThumbInst::MovImm =>    ThumbFn::DpFmt3(thumb::dp_fmt3),
+            ThumbInst::CmpImm =>    ThumbFn::DpFmt3(thumb::dp_fmt3),
+            ThumbInst::AddImm2 =>   ThumbFn::DpFmt3(thumb::dp_fmt3),
+            ThumbInst::SubImm2 =>   ThumbFn::DpFmt3(thumb::dp_fmt3),
+            ThumbInst::LslImm =>    ThumbFn::DpFmt4(thumb::dp_fmt4),
+            ThumbInst::LsrImm =>    ThumbFn::DpFmt4(thumb::dp_fmt4),
+            ThumbInst::AsrImm =>    ThumbFn::DpFmt4(thumb::dp_fmt4),
+
+            ThumbInst::AndReg | ThumbInst::EorReg | ThumbInst::LslReg |
+            ThumbInst::LsrReg | ThumbInst::AsrReg | ThumbInst::AdcReg |
+            ThumbInst::SbcReg | ThumbInst::RorReg | ThumbInst::TstReg |
+            ThumbInst::RsbImm | ThumbInst::CmpReg1 | ThumbInst::CmnReg |
+            ThumbInst::OrrReg | ThumbInst::MulReg | ThumbInst::BicReg |
+            ThumbInst::MvnReg => ThumbFn::DpFmt5(thumb::dp_fmt5),
+
+            ThumbInst::AddImmPc =>  ThumbFn::DpFmt6(thumb::dp_fmt6),
+            ThumbInst::AddImmSp =>  ThumbFn::DpFmt6(thumb::dp_fmt6),
+            ThumbInst::AddImmSp7 => ThumbFn::DpFmt7(thumb::dp_fmt7),
+            ThumbInst::SubImmSp7 => ThumbFn::DpFmt7(thumb::dp_fmt7),
+            ThumbInst::AddReg2 =>   ThumbFn::DpSpecial(thumb::dp_special),
+            ThumbInst::CmpReg2 =>   ThumbFn::DpSpecial(thumb::dp_special),
+            ThumbInst::MovReg =>    ThumbFn::DpSpecial(thumb::dp_special),
+
+            ThumbInst::Bx =>        ThumbFn::BranchExchange(thumb::bx),
+            ThumbInst::BlxReg =>    ThumbFn::BranchExchange(thumb::blx_reg),
+            ThumbInst::BranchCond =>   ThumbFn::CondBranch(thumb::b_cond),
+            ThumbInst::BranchUncond => ThumbFn::BranchLink(thumb::b),
+
+            ThumbInst::LdrImm1 =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::StrImm1 =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::LdrbImm =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::StrbImm =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::LdrhImm =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+            ThumbInst::StrhImm =>   ThumbFn::LsFmt1(thumb::ls_fmt1),
+
+            ThumbInst::LdrReg | ThumbInst::StrReg | ThumbInst::LdrbReg |
+            ThumbInst::StrbReg | ThumbInst::LdrhReg | ThumbInst::StrhReg |
+            ThumbInst::LdrsbReg | ThumbInst::LdrshReg =>
+                ThumbFn::LsFmt2(thumb::ls_fmt2),
+
+            ThumbInst::LdrLit =>    ThumbFn::LdrLit(thumb::ldr_lit),
+            ThumbInst::LdrImm2 =>   ThumbFn::LsFmt4(thumb::ls_fmt4),
+            ThumbInst::StrImm2 =>   ThumbFn::LsFmt4(thumb::ls_fmt4),
+            ThumbInst::Ldmia =>     ThumbFn::LsMulti(thumb::ldmia),
+            ThumbInst::Stmia =>     ThumbFn::LsMulti(thumb::stmia),
+            ThumbInst::Push =>      ThumbFn::PushPop(thumb::push_pop),
+            ThumbInst::Pop =>       ThumbFn::PushPop(thumb::push_pop),
+
+            ThumbInst::Swi =>       ThumbFn::Excep(thumb::svc),
+            ThumbInst::Bkpt =>      ThumbFn::Excep(thumb::bkpt),
+
+            // BL/BLX are only meaningful as a pair of halfwords; see
+            // [Disassembler::thumb_bl].
+            _ => ThumbFn::Raw(undef_instr_thumb),
+        }
+    }
+}
+
+
+/// A map from ArmInst to some handler.
+impl ArmHandlers<String> for DisasCtx {
+    fn arm_handler(inst: ArmInst) -> ArmFn<Self, String> {
+        match inst {
+            ArmInst::MsrReg =>      ArmFn::Status(ctrl::msr_reg),
+            ArmInst::MsrImm =>      ArmFn::Status(ctrl::msr_imm),
+            ArmInst::Mrs =>         ArmFn::Status(ctrl::mrs),
+            ArmInst::Swi =>         ArmFn::Swi(ctrl::svc),
+            ArmInst::Bkpt =>        ArmFn::Bkpt(ctrl::bkpt),
+            ArmInst::Clz =>         ArmFn::Clz(ctrl::clz),
+            ArmInst::Qadd =>        ArmFn::Sat(ctrl::qadd),
+            ArmInst::Qsub =>        ArmFn::Sat(ctrl::qsub),
+            ArmInst::QdAdd =>       ArmFn::Sat(ctrl::qdadd),
+            ArmInst::QdSub =>       ArmFn::Sat(ctrl::qdsub),
+
+            ArmInst::Mrc =>         ArmFn::Coproc(cp::mrc),
+            ArmInst::Mcr =>         ArmFn::Coproc(cp::mcr),
+            ArmInst::Mrrc =>        ArmFn::Coproc(cp::mrrc),
+            ArmInst::Mcrr =>        ArmFn::Coproc(cp::mcrr),
+            ArmInst::Ldc =>         ArmFn::Coproc(cp::ldc),
+            ArmInst::Stc =>         ArmFn::Coproc(cp::stc),
+            ArmInst::Cdp =>         ArmFn::Coproc(cp::cdp),
+            ArmInst::Mrc2 =>        ArmFn::Coproc(cp::mrc),
+            ArmInst::Mcr2 =>        ArmFn::Coproc(cp::mcr),
+            ArmInst::Ldc2 =>        ArmFn::Coproc(cp::ldc),
+            ArmInst::Stc2 =>        ArmFn::Coproc(cp::stc),
+            ArmInst::Cdp2 =>        ArmFn::Coproc(cp::cdp),
+
+            ArmInst::B =>           ArmFn::Branch(branch::b),
+            ArmInst::Bl =>          ArmFn::Branch(branch::bl),
+            ArmInst::Bx =>          ArmFn::Bx(branch::bx),
+            ArmInst::BlxReg =>      ArmFn::Bx(branch::blx_reg),
+            ArmInst::BlxImm =>      ArmFn::Branch(branch::blx_imm),
+
+            ArmInst::LdrsbReg =>    ArmFn::LsMisc(ls::ldrsb_reg),
+            ArmInst::LdrshReg =>    ArmFn::LsMisc(ls::ldrsh_reg),
+            ArmInst::LdrsbImm =>    ArmFn::LsMisc(ls::ldrsb_imm),
+            ArmInst::LdrshImm =>    ArmFn::LsMisc(ls::ldrsh_imm),
+            ArmInst::StrdReg =>     ArmFn::LsMisc(ls::strd_reg),
+            ArmInst::LdrdReg =>     ArmFn::LsMisc(ls::ldrd_reg),
+            ArmInst::StrdImm =>     ArmFn::LsMisc(ls::strd_imm),
+            ArmInst::LdrdImm =>     ArmFn::LsMisc(ls::ldrd_imm),
+            ArmInst::StrhImm =>     ArmFn::LsMisc(ls::strh_imm),
+            ArmInst::LdrhImm =>     ArmFn::LsMisc(ls::ldrh_imm),
+            ArmInst::StrhReg =>     ArmFn::LsMisc(ls::strh_reg),
+            ArmInst::LdrhReg =>     ArmFn::LsMisc(ls::ldrh_reg),
+            ArmInst::Stmia =>       ArmFn::LsMulti(ls::stmia),
+            ArmInst::Stmib =>       ArmFn::LsMulti(ls::stmib),
+            ArmInst::Stmda =>       ArmFn::LsMulti(ls::stmda),
+            ArmInst::Stmdb =>       ArmFn::LsMulti(ls::stmdb),
+            ArmInst::Ldmia =>       ArmFn::LsMulti(ls::ldmia),
+            ArmInst::Ldmib =>       ArmFn::LsMulti(ls::ldmib),
+            ArmInst::Ldmda =>       ArmFn::LsMulti(ls::ldmda),
+            ArmInst::Ldmdb =>       ArmFn::LsMulti(ls::ldmdb),
+            ArmInst::StrImm =>      ArmFn::LsImm(ls::str_imm),
+            ArmInst::LdrImm =>      ArmFn::LsImm(ls::ldr_imm),
+            ArmInst::StrbImm =>     ArmFn::LsImm(ls::strb_imm),
+            ArmInst::LdrbImm =>     ArmFn::LsImm(ls::ldrb_imm),
+            ArmInst::StrReg =>      ArmFn::LsShift(ls::str_reg),
+            ArmInst::LdrReg =>      ArmFn::LsShift(ls::ldr_reg),
+            ArmInst::StrbReg =>     ArmFn::LsShift(ls::strb_reg),
+            ArmInst::LdrbReg =>     ArmFn::LsShift(ls::ldrb_reg),
+            ArmInst::Swp =>         ArmFn::Swp(ls::swp),
+            ArmInst::Swpb =>        ArmFn::Swp(ls::swpb),
+
+            ArmInst::Mul =>         ArmFn::Mul(mul::mul),
+            ArmInst::Mla =>         ArmFn::Mul(mul::mla),
+            ArmInst::Umull =>       ArmFn::Mul(mul::umull),
+            ArmInst::Umlal =>       ArmFn::Mul(mul::umlal),
+            ArmInst::Smull =>       ArmFn::Mul(mul::smull),
+            ArmInst::Smlal =>       ArmFn::Mul(mul::smlal),
+            ArmInst::SmlaXy =>      ArmFn::Mul(mul::smla_xy),
+            ArmInst::SmulwY =>      ArmFn::Mul(mul::smulw_y),
+            ArmInst::SmlawY =>      ArmFn::Mul(mul::smlaw_y),
+            ArmInst::SmlalXy =>     ArmFn::Mul(mul::smlal_xy),
+            ArmInst::SmulXy =>      ArmFn::Mul(mul::smul_xy),
+
+            ArmInst::AndRotImm | ArmInst::EorRotImm | ArmInst::SubRotImm |
+            ArmInst::RsbRotImm | ArmInst::AddRotImm | ArmInst::AdcRotImm |
+            ArmInst::SbcRotImm | ArmInst::RscRotImm | ArmInst::OrrRotImm |
+            ArmInst::BicRotImm | ArmInst::TstRotImm | ArmInst::TeqRotImm |
+            ArmInst::CmpRotImm | ArmInst::CmnRotImm | ArmInst::MovRotImm |
+            ArmInst::MvnRotImm => ArmFn::DpRotImm(dp::rot_imm),
+
+            ArmInst::AndShiftImm | ArmInst::EorShiftImm |
+            ArmInst::SubShiftImm | ArmInst::RsbShiftImm |
+            ArmInst::AddShiftImm | ArmInst::AdcShiftImm |
+            ArmInst::SbcShiftImm | ArmInst::RscShiftImm |
+            ArmInst::OrrShiftImm | ArmInst::BicShiftImm |
+            ArmInst::TstShiftImm | ArmInst::TeqShiftImm |
+            ArmInst::CmpShiftImm | ArmInst::CmnShiftImm |
+            ArmInst::MovShiftImm | ArmInst::MvnShiftImm =>
+                ArmFn::DpShift(dp::shift_imm),
+
+            ArmInst::AndShiftReg | ArmInst::EorShiftReg |
+            ArmInst::SubShiftReg | ArmInst::RsbShiftReg |
+            ArmInst::AddShiftReg | ArmInst::AdcShiftReg |
+            ArmInst::SbcShiftReg | ArmInst::RscShiftReg |
+            ArmInst::OrrShiftReg | ArmInst::BicShiftReg |
+            ArmInst::TstShiftReg | ArmInst::TeqShiftReg |
+            ArmInst::CmpShiftReg | ArmInst::CmnShiftReg |
+            ArmInst::MovShiftReg | ArmInst::MvnShiftReg =>
+                ArmFn::DpShift(dp::shift_reg),
+
+            ArmInst::None =>        ArmFn::Raw(undef_instr),
+        }
+    }
+}
+
+
+/// Disassembles single instructions into text.
+///
+/// This holds a lookup table for each instruction set, so it should be
+/// built once and reused.
+pub struct Disassembler {
+    arm: ArmLut<ArmFn<DisasCtx, String>, ArmCondIndex>,
+    thumb: ThumbLut<ThumbFn<DisasCtx, String>>,
+    ctx: DisasCtx,
+}
+impl Default for Disassembler {
+    fn default() -> Self { Self::new() }
+}
+impl Disassembler {
+    pub fn new() -> Self {
+        Disassembler {
+            arm: ArmLut::new(),
+            thumb: ThumbLut::new(),
+            ctx: DisasCtx { offset: 0, sysregs: None },
+        }
+    }
+
+    /// Add comments naming the CP14/CP15 registers accessed by `mcr` and
+    /// `mrc`, for some core.
+    pub fn with_sysregs(mut self, core: Core) -> Self {
+        self.ctx.sysregs = Some(core);
+        self
+    }
+
+    /// Disassemble an ARM instruction at some address.
+    pub fn arm(&mut self, offset: u32, x: u32) -> String {
+        self.ctx.offset = offset;
+        self.arm.dispatch(&mut self.ctx, x)
+    }
+
+    /// Disassemble a 16-bit Thumb instruction at some address.
+    pub fn thumb(&mut self, offset: u32, x: u16) -> String {
+        self.ctx.offset = offset;
+        self.thumb.dispatch(&mut self.ctx, x)
+    }
+
+    /// Disassemble a Thumb BL/BLX pair at some address. Returns [None] if
+    /// the halfwords don't form a BL/BLX pair.
+    pub fn thumb_bl(&mut self, offset: u32, hi: u16, lo: u16) -> Option<String> {
+        thumb::bl_pair(offset, hi, lo)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn mod_imm() {
+        assert_eq!(super::mod_imm(0x02, 1, false), "#-2147483648");
+        assert_eq!(super::mod_imm(0x02, 1, true), "#2147483648");
+        assert_eq!(super::mod_imm(0x01, 0xc, false), "#256");
+        assert_eq!(super::mod_imm(0xe0, 3, false), "#224, #6");
+        assert_eq!(super::mod_imm(0x3f, 0xf, false), "#63, #30");
+        assert_eq!(super::mod_imm(0xff, 0, false), "#255");
+    }
+
+    /// A line from an `objdump -d` listing.
+    struct RefLine {
+        offset: u32,
+        bytes: Vec<u8>,
+        text: String,
+    }
+
+    /// Pull the instructions out of an `objdump -d` listing. Data (`.word`,
+    /// `.short`, ...) and symbol lines are skipped.
+    fn parse_listing(listing: &str) -> Vec<RefLine> {
+        let mut res = Vec::new();
+        for line in listing.lines() {
+            let (offset, rest) = match line.trim_start().split_once(':') {
+                Some((off, rest)) => match u32::from_str_radix(off, 16) {
+                    Ok(off) => (off, rest),
+                    Err(_) => continue,
+                },
+                None => continue,
+            };
+            let mut fields = rest.trim_start().splitn(2, '\t');
+            let bytes: Vec<u8> = fields.next().unwrap()
+                .split_whitespace()
+                .map(|b| u8::from_str_radix(b, 16).unwrap())
+                .collect();
+            let text = normalize(fields.next().unwrap_or(""));
+            if text.starts_with('.') {
+                continue;
+            }
+            res.push(RefLine { offset, bytes, text });
+        }
+        res
+    }
+
+    /// Drop symbolic annotations/comments and collapse whitespace.
+    fn normalize(text: &str) -> String {
+        let text = text.split('@').next().unwrap();
+        let text = match (text.find('<'), text.rfind('>')) {
+            (Some(l), Some(r)) if text != "<unknown>" => {
+                format!("{}{}", &text[..l], &text[r + 1..])
+            },
+            _ => text.to_string(),
+        };
+        text.split_whitespace().collect::<Vec<&str>>().join(" ")
+    }
+
+    /// Our output for the encodings that the reference disassembler prints
+    /// as `<unknown>`. llvm-objdump only accepts the ARMv6 forms of MUL and
+    /// friends, so it rejects them when targeting ARM926EJ-S.
+    const UNKNOWN: &[(u32, &str)] = &[
+        (0xe0030594, "mul r3, r4, r5"),
+        (0xe0236594, "mla r3, r4, r5, r6"),
+        (0xe0843695, "umull r3, r4, r5, r6"),
+        (0xe0a43695, "umlal r3, r4, r5, r6"),
+        (0xe0e43695, "smlal r3, r4, r5, r6"),
+        (0xe0c43695, "smull r3, r4, r5, r6"),
+    ];
+
+    /// Disassemble a corpus binary and compare each instruction against the
+    /// reference listing, or against [UNKNOWN] where the reference gave up.
+    /// Any differences are reported as a diff.
+    fn check_corpus(name: &str, thumb: bool) {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/testsuite/");
+        let bin = std::fs::read(format!("{}{}.bin", dir, name)).unwrap();
+        let lst = std::fs::read_to_string(format!("{}{}.lst", dir, name))
+            .unwrap();
+
+        let mut d = Disassembler::new();
+        let mut diff = String::new();
+        let mut compared = 0;
+        for line in parse_listing(&lst) {
+            let off = line.offset as usize;
+            let raw = &bin[off..off + line.bytes.len()];
+
+            // The listing is from a little-endian build of the same source,
+            // and the binary is BE-32.
+            let (x, word, ours) = match (thumb, line.bytes.len()) {
+                (false, 4) => {
+                    let x = u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]);
+                    let le = u32::from_le_bytes([line.bytes[0], line.bytes[1],
+                        line.bytes[2], line.bytes[3]]);
+                    assert_eq!(x, le, "{}: encoding differs at {:x}", name, off);
+                    (x, format!("{:08x}", x), d.arm(line.offset, x))
+                },
+                (true, 2) => {
+                    let x = u16::from_be_bytes([raw[0], raw[1]]);
+                    (x as u32, format!("{:04x}", x), d.thumb(line.offset, x))
+                },
+                (true, 4) => {
+                    let hi = u16::from_be_bytes([raw[0], raw[1]]);
+                    let lo = u16::from_be_bytes([raw[2], raw[3]]);
+                    ((hi as u32) << 16 | lo as u32, format!("{:04x} {:04x}", hi, lo),
+                        d.thumb_bl(line.offset, hi, lo).unwrap_or_default())
+                },
+                _ => panic!("{}: unexpected instruction size at {:x}", name, off),
+            };
+            let ours = normalize(&ours);
+            let expected = match line.text.as_str() {
+                "<unknown>" => match UNKNOWN.iter().find(|(y, _)| *y == x) {
+                    Some((_, text)) => text.to_string(),
+                    None => panic!("{}: no expected text for {} at {:x}",
+                        name, word, off),
+                },
+                text => text.to_string(),
+            };
+
+            compared += 1;
+            if expected != ours {
+                writeln!(diff, "- {:6x}: {:9}  {}", off, word, expected).unwrap();
+                writeln!(diff, "+ {:6x}: {:9}  {}", off, word, ours).unwrap();
+            }
+        }
+        assert!(compared > 0);
+        assert!(diff.is_empty(), "{}: lines differ from the reference:\n\
+            --- {}.lst\n+++ armbf\n{}", name, name, diff);
+    }
+
+    #[test]
+    fn corpus_arm_decode_test() { check_corpus("arm_decode_test", false); }
+    #[test]
+    fn corpus_arm_misc_test() { check_corpus("arm_misc_test", false); }
+    #[test]
+    fn corpus_arm_test() { check_corpus("arm_test", false); }
+    #[test]
+    fn corpus_thumb_decode_test() { check_corpus("thumb_decode_test", true); }
+    #[test]
+    fn corpus_vfp_test() { check_corpus("vfp_test", false); }
+}
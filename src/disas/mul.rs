@@ -0,0 +1,99 @@
+use armbf_prim::*;
+
+use crate::newtype::*;
+use crate::traits::*;
+
+use super::{DisasCtx, Reg, CondName};
+
+/// Mnemonic for a multiply with an optional S bit.
+fn mnemonic(name: &'static str, op: &MulBf) -> String {
+    let s = if get_s!(op.0) { "s" } else { "" };
+    format!("{}{}{}", name, s, CondName::from_u32(op.cond()))
+}
+
+/// Mnemonic for a signed halfword multiply, i.e. `smla<x><y>`.
+fn mnemonic_xy(name: &'static str, op: &MulBf) -> String {
+    let x = if op.x() { "t" } else { "b" };
+    let y = if op.y() { "t" } else { "b" };
+    format!("{}{}{}{}", name, x, y, CondName::from_u32(op.cond()))
+}
+
+/// Mnemonic for a signed word-by-halfword multiply, i.e. `smulw<y>`.
+fn mnemonic_y(name: &'static str, op: &MulBf) -> String {
+    let y = if op.y() { "t" } else { "b" };
+    format!("{}{}{}", name, y, CondName::from_u32(op.cond()))
+}
+
+
+pub fn mul(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    format!("{}\t{}, {}, {}", mnemonic("mul", op),
+        Reg::from_u32(op.rd_hi()),
+        Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rs()),
+    )
+}
+pub fn mla(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    format!("{}\t{}, {}, {}, {}", mnemonic("mla", op),
+        Reg::from_u32(op.rd_hi()),
+        Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rs()),
+        Reg::from_u32(op.rd_lo()),
+    )
+}
+
+
+fn mul_long(op: &MulBf, name: &str) -> String {
+    format!("{}\t{}, {}, {}, {}", name,
+        Reg::from_u32(op.rd_lo()),
+        Reg::from_u32(op.rd_hi()),
+        Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rs()),
+    )
+}
+pub fn umull(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    mul_long(op, &mnemonic("umull", op))
+}
+pub fn umlal(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    mul_long(op, &mnemonic("umlal", op))
+}
+pub fn smull(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    mul_long(op, &mnemonic("smull", op))
+}
+pub fn smlal(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    mul_long(op, &mnemonic("smlal", op))
+}
+pub fn smlal_xy(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    mul_long(op, &mnemonic_xy("smlal", op))
+}
+
+
+pub fn smla_xy(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    format!("{}\t{}, {}, {}, {}", mnemonic_xy("smla", op),
+        Reg::from_u32(op.rd_hi()),
+        Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rs()),
+        Reg::from_u32(op.rd_lo()),
+    )
+}
+pub fn smlaw_y(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    format!("{}\t{}, {}, {}, {}", mnemonic_y("smlaw", op),
+        Reg::from_u32(op.rd_hi()),
+        Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rs()),
+        Reg::from_u32(op.rd_lo()),
+    )
+}
+pub fn smul_xy(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    format!("{}\t{}, {}, {}", mnemonic_xy("smul", op),
+        Reg::from_u32(op.rd_hi()),
+        Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rs()),
+    )
+}
+pub fn smulw_y(_ctx: &mut DisasCtx, op: &MulBf) -> String {
+    format!("{}\t{}, {}, {}", mnemonic_y("smulw", op),
+        Reg::from_u32(op.rd_hi()),
+        Reg::from_u32(op.rm()),
+        Reg::from_u32(op.rs()),
+    )
+}
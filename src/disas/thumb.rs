@@ -0,0 +1,230 @@
+use crate::newtype::*;
+use crate::traits::*;
+
+use super::{DisasCtx, reglist, Reg, CondName};
+
+/// Sign extend to some number of bits
+#[inline(always)]
+fn sign_extend(x: i32, bits: i32) -> i32 {
+    if ((x >> (bits - 1)) & 1) != 0 {
+        return x | !0 << bits
+    }
+    x
+}
+
+/// Shorthand for a low register.
+fn reg(x: u16) -> Reg { Reg::from_u32(x as u32) }
+
+/// Shorthand for a register selected by a 3-bit field and a high bit.
+fn hireg(x: u16, h: bool) -> Reg {
+    Reg::from_u32(((h as u32) << 3) | x as u32)
+}
+
+
+pub fn add_reg1(_ctx: &mut DisasCtx, op: &DpFmt1Bf) -> String {
+    format!("adds\t{}, {}, {}", reg(op.rd()), reg(op.rn()), reg(op.rm()))
+}
+pub fn sub_reg(_ctx: &mut DisasCtx, op: &DpFmt1Bf) -> String {
+    format!("subs\t{}, {}, {}", reg(op.rd()), reg(op.rn()), reg(op.rm()))
+}
+pub fn add_imm1(_ctx: &mut DisasCtx, op: &DpFmt2Bf) -> String {
+    format!("adds\t{}, {}, #{}", reg(op.rd()), reg(op.rn()), op.imm3())
+}
+pub fn sub_imm1(_ctx: &mut DisasCtx, op: &DpFmt2Bf) -> String {
+    format!("subs\t{}, {}, #{}", reg(op.rd()), reg(op.rn()), op.imm3())
+}
+
+/// MOV/CMP/ADD/SUB with an 8-bit immediate.
+pub fn dp_fmt3(_ctx: &mut DisasCtx, op: &DpFmt3Bf) -> String {
+    let name = match op.op3() {
+        0b00 => "movs",
+        0b01 => "cmp",
+        0b10 => "adds",
+        0b11 => "subs",
+        _ => unreachable!(),
+    };
+    format!("{}\t{}, #{}", name, reg(op.rd()), op.imm8())
+}
+
+/// Shifts by an immediate.
+pub fn dp_fmt4(_ctx: &mut DisasCtx, op: &DpFmt4Bf) -> String {
+    let imm = op.shift_imm();
+    match (op.op4(), imm) {
+        (0b00, 0) => format!("movs\t{}, {}", reg(op.rd()), reg(op.rm())),
+        (0b00, _) => format!("lsls\t{}, {}, #{}", reg(op.rd()), reg(op.rm()), imm),
+        (0b01, _) | (0b10, _) => {
+            let name = if op.op4() == 0b01 { "lsrs" } else { "asrs" };
+            let imm = if imm == 0 { 32 } else { imm };
+            format!("{}\t{}, {}, #{}", name, reg(op.rd()), reg(op.rm()), imm)
+        },
+        _ => unreachable!(),
+    }
+}
+
+/// Register-to-register data-processing operations.
+pub fn dp_fmt5(_ctx: &mut DisasCtx, op: &DpFmt5Bf) -> String {
+    let (rd, rm) = (reg(op.rd()), reg(op.rm()));
+    let name = match op.op5() {
+        0b0000 => "ands",
+        0b0001 => "eors",
+        0b0010 => "lsls",
+        0b0011 => "lsrs",
+        0b0100 => "asrs",
+        0b0101 => "adcs",
+        0b0110 => "sbcs",
+        0b0111 => "rors",
+        0b1000 => "tst",
+        0b1001 => return format!("rsbs\t{}, {}, #0", rd, rm),
+        0b1010 => "cmp",
+        0b1011 => "cmn",
+        0b1100 => "orrs",
+        0b1101 => return format!("muls\t{}, {}, {}", rd, rm, rd),
+        0b1110 => "bics",
+        0b1111 => "mvns",
+        _ => unreachable!(),
+    };
+    format!("{}\t{}, {}", name, rd, rm)
+}
+
+/// Address generation relative to the PC or SP.
+pub fn dp_fmt6(_ctx: &mut DisasCtx, op: &DpFmt6Bf) -> String {
+    let imm = op.imm8() as u32 * 4;
+    if op.reg() {
+        format!("add\t{}, sp, #{}", reg(op.rd()), imm)
+    } else {
+        format!("adr\t{}, #{}", reg(op.rd()), imm)
+    }
+}
+
+/// Adjusting the SP.
+pub fn dp_fmt7(_ctx: &mut DisasCtx, op: &DpFmt7Bf) -> String {
+    let name = if op.op6() == 0 { "add" } else { "sub" };
+    format!("{}\tsp, #{}", name, op.imm7() as u32 * 4)
+}
+
+/// ADD/CMP/MOV on high registers. An ADD of the SP is written with three
+/// operands, as in `add r0, sp, r0`.
+pub fn dp_special(_ctx: &mut DisasCtx, op: &DpSpecialBf) -> String {
+    let name = match op.opcd() {
+        0b00 => "add",
+        0b01 => "cmp",
+        0b10 => "mov",
+        _ => unreachable!(),
+    };
+    let (rd, rm) = (hireg(op.rd(), op.h1()), hireg(op.rm(), op.h2()));
+    match (op.opcd(), op.h2(), op.rm()) {
+        (0b00, true, 0b101) => format!("add\t{}, sp, {}", rd, rd),
+        _ => format!("{}\t{}, {}", name, rd, rm),
+    }
+}
+
+
+pub fn bx(_ctx: &mut DisasCtx, op: &ThumbBranchExchangeBf) -> String {
+    format!("bx\t{}", hireg(op.rm(), op.h2()))
+}
+pub fn blx_reg(_ctx: &mut DisasCtx, op: &ThumbBranchExchangeBf) -> String {
+    format!("blx\t{}", hireg(op.rm(), op.h2()))
+}
+
+/// Branch targets are relative to the PC (the address of the instruction
+/// plus 4).
+pub fn b_cond(ctx: &mut DisasCtx, op: &ThumbCondBranchBf) -> String {
+    let off = sign_extend(op.simm8() as i32, 8) << 1;
+    format!("b{}\t0x{:x}", CondName::from_u32(op.cond() as u32),
+        ctx.offset.wrapping_add(4).wrapping_add(off as u32))
+}
+pub fn b(ctx: &mut DisasCtx, op: &ThumbBranchLinkBf) -> String {
+    let off = sign_extend(op.imm11() as i32, 11) << 1;
+    format!("b\t0x{:x}", ctx.offset.wrapping_add(4).wrapping_add(off as u32))
+}
+
+/// Disassemble a BL/BLX prefix/suffix pair. Returns [None] if the halfwords
+/// aren't a BL/BLX pair.
+pub fn bl_pair(offset: u32, hi: u16, lo: u16) -> Option<String> {
+    let (hi, lo) = (ThumbBranchLinkBf(hi), ThumbBranchLinkBf(lo));
+    if (hi.0 >> 13) != 0b111 || (lo.0 >> 13) != 0b111 || hi.h() != 0b10 {
+        return None;
+    }
+    let off = (sign_extend(hi.imm11() as i32, 11) << 12)
+        | ((lo.imm11() as i32) << 1);
+    let target = offset.wrapping_add(4).wrapping_add(off as u32);
+    match lo.h() {
+        0b11 => Some(format!("bl\t0x{:x}", target)),
+        0b01 => Some(format!("blx\t0x{:x}", target & !3)),
+        _ => None,
+    }
+}
+
+
+/// Load/store with a scaled 5-bit immediate offset.
+pub fn ls_fmt1(_ctx: &mut DisasCtx, op: &ThumbLsFmt1Bf) -> String {
+    let (name, scale) = match op.opcd1() {
+        0b01100 => ("str", 4),
+        0b01101 => ("ldr", 4),
+        0b01110 => ("strb", 1),
+        0b01111 => ("ldrb", 1),
+        0b10000 => ("strh", 2),
+        0b10001 => ("ldrh", 2),
+        _ => unreachable!(),
+    };
+    match op.imm5() as u32 * scale {
+        0 => format!("{}\t{}, [{}]", name, reg(op.rd()), reg(op.rn())),
+        imm => format!("{}\t{}, [{}, #{}]", name, reg(op.rd()), reg(op.rn()), imm),
+    }
+}
+
+/// Load/store with a register offset.
+pub fn ls_fmt2(_ctx: &mut DisasCtx, op: &ThumbLsFmt2Bf) -> String {
+    let name = match op.opcd2() {
+        0b0101000 => "str",
+        0b0101001 => "strh",
+        0b0101010 => "strb",
+        0b0101011 => "ldrsb",
+        0b0101100 => "ldr",
+        0b0101101 => "ldrh",
+        0b0101110 => "ldrb",
+        0b0101111 => "ldrsh",
+        _ => unreachable!(),
+    };
+    format!("{}\t{}, [{}, {}]", name, reg(op.rd()), reg(op.rn()), reg(op.rm()))
+}
+
+pub fn ldr_lit(_ctx: &mut DisasCtx, op: &ThumbLdrLitBf) -> String {
+    format!("ldr\t{}, [pc, #{}]", reg(op.rd()), op.imm8() as u32 * 4)
+}
+
+/// SP-relative load/store.
+pub fn ls_fmt4(_ctx: &mut DisasCtx, op: &ThumbLsFmt4Bf) -> String {
+    let name = if op.l() { "ldr" } else { "str" };
+    match op.imm8() as u32 * 4 {
+        0 => format!("{}\t{}, [sp]", name, reg(op.rd())),
+        imm => format!("{}\t{}, [sp, #{}]", name, reg(op.rd()), imm),
+    }
+}
+
+
+pub fn ldmia(_ctx: &mut DisasCtx, op: &ThumbLsMultiBf) -> String {
+    // There's no writeback when the base register is also loaded
+    let wb = if (op.reglist() & (1 << op.rn())) != 0 { "" } else { "!" };
+    format!("ldm\t{}{}, {}", reg(op.rn()), wb, reglist(op.reglist() as u32))
+}
+pub fn stmia(_ctx: &mut DisasCtx, op: &ThumbLsMultiBf) -> String {
+    format!("stm\t{}!, {}", reg(op.rn()), reglist(op.reglist() as u32))
+}
+
+pub fn push_pop(_ctx: &mut DisasCtx, op: &PushPopBf) -> String {
+    let mut list = op.reglist() as u32;
+    if op.r() {
+        list |= if op.l() { 1 << 15 } else { 1 << 14 };
+    }
+    let name = if op.l() { "pop" } else { "push" };
+    format!("{}\t{}", name, reglist(list))
+}
+
+
+pub fn svc(_ctx: &mut DisasCtx, op: &ThumbExcepBf) -> String {
+    format!("svc\t#{}", op.imm8())
+}
+pub fn bkpt(_ctx: &mut DisasCtx, op: &ThumbExcepBf) -> String {
+    format!("bkpt\t#{}", op.imm8())
+}
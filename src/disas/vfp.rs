@@ -0,0 +1,219 @@
+use crate::newtype::*;
+use crate::traits::*;
+use crate::fields::*;
+use crate::inst::VfpInst;
+
+use super::{CondName, Reg};
+
+/// The Fd operand of a data-processing instruction.
+fn fd(inst: VfpInst, op: &VfpBf) -> String {
+    match inst.d_double() {
+        true => DoubleRegister::from_u32(op.dd()).to_string(),
+        false => SingleRegister::from_u32(op.sd()).to_string(),
+    }
+}
+
+/// The Fn operand of a data-processing instruction.
+fn fn_(inst: VfpInst, op: &VfpBf) -> String {
+    match inst.is_double() {
+        true => DoubleRegister::from_u32(op.dn()).to_string(),
+        false => SingleRegister::from_u32(op.sn()).to_string(),
+    }
+}
+
+/// The Fm operand of a data-processing instruction.
+fn fm(inst: VfpInst, op: &VfpBf) -> String {
+    match inst.m_double() {
+        true => DoubleRegister::from_u32(op.dm()).to_string(),
+        false => SingleRegister::from_u32(op.sm()).to_string(),
+    }
+}
+
+fn precision(inst: VfpInst) -> &'static str {
+    if inst.is_double() { "f64" } else { "f32" }
+}
+
+/// Format a list of consecutive registers, i.e. `{d0, d1, d2}`.
+fn vreglist(first: u32, count: u32, double: bool) -> String {
+    let regs: Vec<String> = (first..first + count).map(|idx| match double {
+        true => DoubleRegister::from_u32(idx).to_string(),
+        false => SingleRegister::from_u32(idx).to_string(),
+    }).collect();
+    format!("{{{}}}", regs.join(", "))
+}
+
+fn dp(inst: VfpInst, op: &VfpBf, name: &'static str) -> String {
+    format!("{}{}.{}\t{}, {}, {}", name, CondName::from_u32(op.cond()),
+        precision(inst), fd(inst, op), fn_(inst, op), fm(inst, op))
+}
+
+fn ext(inst: VfpInst, op: &VfpBf, name: &'static str) -> String {
+    format!("{}{}.{}\t{}, {}", name, CondName::from_u32(op.cond()),
+        precision(inst), fd(inst, op), fm(inst, op))
+}
+
+fn cmp_zero(inst: VfpInst, op: &VfpBf, name: &'static str) -> String {
+    format!("{}{}.{}\t{}, #0", name, CondName::from_u32(op.cond()),
+        precision(inst), fd(inst, op))
+}
+
+/// Conversions are named after the destination and source types.
+fn cvt(inst: VfpInst, op: &VfpBf, name: &'static str, types: &'static str)
+    -> String
+{
+    format!("{}{}.{}\t{}, {}", name, CondName::from_u32(op.cond()), types,
+        fd(inst, op), fm(inst, op))
+}
+
+fn ls(inst: VfpInst, op: &VfpBf, name: &'static str) -> String {
+    let rn = Reg::from_u32(op.rn());
+    let imm = op.imm8() * 4;
+    let addr = match (op.u(), imm) {
+        (true, 0) => format!("[{}]", rn),
+        (true, _) => format!("[{}, #{}]", rn, imm),
+        (false, _) => format!("[{}, #-{}]", rn, imm),
+    };
+    let fd = match inst.is_double() {
+        true => DoubleRegister::from_u32(op.dd()).to_string(),
+        false => SingleRegister::from_u32(op.sd()).to_string(),
+    };
+    format!("{}{}\t{}, {}", name, CondName::from_u32(op.cond()), fd, addr)
+}
+
+fn ls_multi(inst: VfpInst, op: &VfpBf, name: &'static str) -> String {
+    let cond = CondName::from_u32(op.cond());
+    let double = inst.is_double();
+    let list = match double {
+        true => vreglist(op.dd(), op.imm8() / 2, true),
+        false => vreglist(op.sd(), op.imm8(), false),
+    };
+    // Push/pop, except for the FLDMX/FSTMX format
+    if op.rn() == 13 && op.w() && !matches!(inst, VfpInst::Fldmiax | VfpInst::Fstmdbx) {
+        match name {
+            "vldmia" => return format!("vpop{}\t{}", cond, list),
+            "vstmdb" => return format!("vpush{}\t{}", cond, list),
+            _ => {},
+        }
+    }
+    let wb = if op.w() { "!" } else { "" };
+    format!("{}{}\t{}{}, {}", name, cond, Reg::from_u32(op.rn()), wb, list)
+}
+
+/// Transfers between an ARM register and a single-precision register, or
+/// half of a double-precision register.
+fn rt(op: &VfpBf, reg: String, to_arm: bool, suffix: &'static str) -> String {
+    let rd = Reg::from_u32(op.rd());
+    match to_arm {
+        true => format!("vmov{}{}\t{}, {}", CondName::from_u32(op.cond()), suffix, rd, reg),
+        false => format!("vmov{}{}\t{}, {}", CondName::from_u32(op.cond()), suffix, reg, rd),
+    }
+}
+
+/// Transfers between two ARM registers and a pair of single-precision
+/// registers, or a double-precision register.
+fn rt2(op: &VfpBf, regs: String, to_arm: bool) -> String {
+    let (rd, rn) = (Reg::from_u32(op.rd()), Reg::from_u32(op.rn()));
+    match to_arm {
+        true => format!("vmov{}\t{}, {}, {}", CondName::from_u32(op.cond()), rd, rn, regs),
+        false => format!("vmov{}\t{}, {}, {}", CondName::from_u32(op.cond()), regs, rd, rn),
+    }
+}
+
+/// Disassemble an instruction in the VFP space.
+pub fn vfp(op: &VfpBf) -> String {
+    use VfpInst::*;
+    let inst = VfpInst::decode(op.0);
+    let cond = CondName::from_u32(op.cond());
+    match inst {
+        None => "<unknown>".to_string(),
+
+        Fmacs | Fmacd => dp(inst, op, "vmla"),
+        Fnmacs | Fnmacd => dp(inst, op, "vmls"),
+        Fmscs | Fmscd => dp(inst, op, "vnmls"),
+        Fnmscs | Fnmscd => dp(inst, op, "vnmla"),
+        Fmuls | Fmuld => dp(inst, op, "vmul"),
+        Fnmuls | Fnmuld => dp(inst, op, "vnmul"),
+        Fadds | Faddd => dp(inst, op, "vadd"),
+        Fsubs | Fsubd => dp(inst, op, "vsub"),
+        Fdivs | Fdivd => dp(inst, op, "vdiv"),
+
+        Fcpys | Fcpyd => ext(inst, op, "vmov"),
+        Fabss | Fabsd => ext(inst, op, "vabs"),
+        Fnegs | Fnegd => ext(inst, op, "vneg"),
+        Fsqrts | Fsqrtd => ext(inst, op, "vsqrt"),
+        Fcmps | Fcmpd => ext(inst, op, "vcmp"),
+        Fcmpes | Fcmped => ext(inst, op, "vcmpe"),
+        Fcmpzs | Fcmpzd => cmp_zero(inst, op, "vcmp"),
+        Fcmpezs | Fcmpezd => cmp_zero(inst, op, "vcmpe"),
+
+        Fcvtds => cvt(inst, op, "vcvt", "f64.f32"),
+        Fcvtsd => cvt(inst, op, "vcvt", "f32.f64"),
+        Fuitos => cvt(inst, op, "vcvt", "f32.u32"),
+        Fuitod => cvt(inst, op, "vcvt", "f64.u32"),
+        Fsitos => cvt(inst, op, "vcvt", "f32.s32"),
+        Fsitod => cvt(inst, op, "vcvt", "f64.s32"),
+        // Without the Z suffix, these round using the FPSCR
+        Ftouis => cvt(inst, op, "vcvtr", "u32.f32"),
+        Ftouid => cvt(inst, op, "vcvtr", "u32.f64"),
+        Ftouizs => cvt(inst, op, "vcvt", "u32.f32"),
+        Ftouizd => cvt(inst, op, "vcvt", "u32.f64"),
+        Ftosis => cvt(inst, op, "vcvtr", "s32.f32"),
+        Ftosid => cvt(inst, op, "vcvtr", "s32.f64"),
+        Ftosizs => cvt(inst, op, "vcvt", "s32.f32"),
+        Ftosizd => cvt(inst, op, "vcvt", "s32.f64"),
+
+        Flds | Fldd => ls(inst, op, "vldr"),
+        Fsts | Fstd => ls(inst, op, "vstr"),
+
+        Fldmias | Fldmiad => ls_multi(inst, op, "vldmia"),
+        Fldmdbs | Fldmdbd => ls_multi(inst, op, "vldmdb"),
+        Fstmias | Fstmiad => ls_multi(inst, op, "vstmia"),
+        Fstmdbs | Fstmdbd => ls_multi(inst, op, "vstmdb"),
+        Fldmiax => ls_multi(inst, op, "fldmiax"),
+        Fldmdbx => ls_multi(inst, op, "fldmdbx"),
+        Fstmiax => ls_multi(inst, op, "fstmiax"),
+        Fstmdbx => ls_multi(inst, op, "fstmdbx"),
+
+        Fmsr | Fmrs => rt(op, SingleRegister::from_u32(op.sn()).to_string(),
+            inst == Fmrs, ""),
+        Fmdlr | Fmrdl => rt(op, format!("{}[0]", DoubleRegister::from_u32(op.dn())),
+            inst == Fmrdl, ".32"),
+        Fmdhr | Fmrdh => rt(op, format!("{}[1]", DoubleRegister::from_u32(op.dn())),
+            inst == Fmrdh, ".32"),
+        Fmxr => format!("vmsr{}\t{}, {}", cond,
+            VfpSysRegister::from_u32(op.crn()), Reg::from_u32(op.rd())),
+        Fmrx => format!("vmrs{}\t{}, {}", cond,
+            Reg::from_u32(op.rd()), VfpSysRegister::from_u32(op.crn())),
+        Fmstat => format!("vmrs{}\tAPSR_nzcv, fpscr", cond),
+
+        Fmsrr | Fmrrs => rt2(op, format!("{}, {}",
+            SingleRegister::from_u32(op.sm()), SingleRegister::from_u32(op.sm() + 1)),
+            inst == Fmrrs),
+        Fmdrr | Fmrrd => rt2(op, DoubleRegister::from_u32(op.dm()).to_string(),
+            inst == Fmrrd),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn operands() {
+        let dis = |x: u32| vfp(&VfpBf(x));
+        // Every extension bit set
+        assert_eq!(dis(0xee421aef), "vmls.f32\ts3, s5, s31");
+        assert_eq!(dis(0xeeb70ac0), "vcvt.f64.f32\td0, s0");
+        assert_eq!(dis(0xeeb70bc0), "vcvt.f32.f64\ts0, d0");
+        assert_eq!(dis(0xec410a1f), "vmov\ts30, s31, r0, r1");
+        assert_eq!(dis(0xed010a00), "vstr\ts0, [r1, #-0]");
+        assert_eq!(dis(0x0ee00a10), "vmsreq\tfpsid, r0");
+        assert_eq!(dis(0xeef0fa10), "vmrs\tpc, fpsid");
+        assert_eq!(dis(0xecbd0a01), "vpop\t{s0}");
+        assert_eq!(dis(0xed2d0b05), "fstmdbx\tsp!, {d0, d1}");
+        // Double-precision registers with an extension bit set
+        assert_eq!(dis(0xee300b22), "<unknown>");
+        assert_eq!(dis(0xedd00b01), "<unknown>");
+    }
+}
@@ -0,0 +1,548 @@
+//! A reader for 32-bit ARM ELF files.
+//!
+//! Only the parts needed for disassembly are read: the file header, section
+//! headers, program headers and the symbol table. Both little- and big-endian
+//! files are supported. For big-endian files, the `EF_ARM_BE8` flag decides
+//! whether code is stored little-endian (BE-8) or big-endian (BE-32).
+//!
+//! ARM mapping symbols (`$a`, `$t`, `$d`) mark the start of ARM code, Thumb
+//! code and data within a section; [Elf::sweep] uses them (and the function
+//! symbols) to set up a [LinearSweep] over a section.
+
+use std::fmt;
+
+use crate::disas::Isa;
+use crate::stream::*;
+use crate::sweep::*;
+
+pub const EM_ARM: u16 = 40;
+
+pub const ET_REL: u16 = 1;
+pub const ET_EXEC: u16 = 2;
+pub const ET_DYN: u16 = 3;
+
+pub const SHT_PROGBITS: u32 = 1;
+pub const SHT_SYMTAB: u32 = 2;
+pub const SHT_STRTAB: u32 = 3;
+pub const SHT_RELA: u32 = 4;
+pub const SHT_NOBITS: u32 = 8;
+pub const SHT_REL: u32 = 9;
+
+pub const SHF_WRITE: u32 = 0x1;
+pub const SHF_ALLOC: u32 = 0x2;
+pub const SHF_EXECINSTR: u32 = 0x4;
+
+pub const PT_LOAD: u32 = 1;
+
+pub const EF_ARM_EABIMASK: u32 = 0xff00_0000;
+pub const EF_ARM_BE8: u32 = 0x0080_0000;
+pub const EF_ARM_ABI_FLOAT_SOFT: u32 = 0x0000_0200;
+pub const EF_ARM_ABI_FLOAT_HARD: u32 = 0x0000_0400;
+
+/// Error returned when a file can't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElfError {
+    /// The file doesn't start with the ELF magic number.
+    BadMagic,
+    /// The file isn't a 32-bit ARM ELF file.
+    Unsupported(&'static str),
+    /// Some structure extends past the end of the file.
+    Truncated { offset: usize },
+}
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfError::BadMagic => write!(f, "not an ELF file"),
+            ElfError::Unsupported(what) => write!(f, "unsupported ELF file: {}", what),
+            ElfError::Truncated { offset } => {
+                write!(f, "ELF file truncated at offset 0x{:x}", offset)
+            },
+        }
+    }
+}
+impl std::error::Error for ElfError {}
+
+
+/// A section header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Index in the section header table.
+    pub index: usize,
+    pub name: String,
+    /// `sh_type`
+    pub kind: u32,
+    pub flags: u32,
+    /// Address when loaded (zero in relocatable objects).
+    pub addr: u32,
+    pub offset: u32,
+    pub size: u32,
+    pub link: u32,
+    pub info: u32,
+    pub entsize: u32,
+}
+impl Section {
+    /// Returns true if this section contains code.
+    pub fn is_exec(&self) -> bool {
+        (self.flags & SHF_EXECINSTR) != 0
+    }
+    /// Returns true if this section occupies memory at runtime.
+    pub fn is_alloc(&self) -> bool {
+        (self.flags & SHF_ALLOC) != 0
+    }
+    /// Returns true if `addr` is inside this section.
+    pub fn contains(&self, addr: u32) -> bool {
+        addr >= self.addr && ((addr - self.addr) as u64) < self.size as u64
+    }
+}
+
+/// A program header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// `p_type`
+    pub kind: u32,
+    pub offset: u32,
+    pub vaddr: u32,
+    pub paddr: u32,
+    pub filesz: u32,
+    pub memsz: u32,
+    pub flags: u32,
+}
+
+/// The type of a symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    NoType,
+    Object,
+    Func,
+    Section,
+    File,
+    Other(u8),
+}
+impl From<u8> for SymbolKind {
+    fn from(x: u8) -> Self {
+        match x {
+            0 => SymbolKind::NoType,
+            1 => SymbolKind::Object,
+            2 => SymbolKind::Func,
+            3 => SymbolKind::Section,
+            4 => SymbolKind::File,
+            x => SymbolKind::Other(x),
+        }
+    }
+}
+
+/// An entry in the symbol table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// `st_value`; bit 0 is set for Thumb functions.
+    pub value: u32,
+    pub size: u32,
+    pub kind: SymbolKind,
+    pub global: bool,
+    /// Index of the section this symbol is defined in.
+    pub shndx: u16,
+}
+impl Symbol {
+    /// Returns the region if this is a mapping symbol.
+    pub fn mapping(&self) -> Option<Region> {
+        match self.name.split('.').next().unwrap() {
+            "$a" => Some(Region::Code(Isa::Arm)),
+            "$t" => Some(Region::Code(Isa::Thumb)),
+            "$d" => Some(Region::Data),
+            _ => None,
+        }
+    }
+    /// Returns the address of this symbol (without the Thumb bit).
+    pub fn addr(&self) -> u32 {
+        match self.kind {
+            SymbolKind::Func => self.value & !1,
+            _ => self.value,
+        }
+    }
+    /// Returns the instruction set if this is a function.
+    pub fn isa(&self) -> Option<Isa> {
+        match self.kind {
+            SymbolKind::Func if (self.value & 1) != 0 => Some(Isa::Thumb),
+            SymbolKind::Func => Some(Isa::Arm),
+            _ => None,
+        }
+    }
+}
+
+/// A relocation entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reloc {
+    /// Address of the relocated field.
+    pub offset: u32,
+    /// `ELF32_R_TYPE(r_info)`
+    pub kind: u32,
+    /// Index of the symbol in [Elf::symbols].
+    pub sym: usize,
+    /// Explicit addend (only for `SHT_RELA` sections).
+    pub addend: Option<i32>,
+}
+
+
+/// A parsed ELF file.
+pub struct Elf<'a> {
+    buf: &'a [u8],
+    pub big_endian: bool,
+    /// `e_type`
+    pub kind: u16,
+    pub machine: u16,
+    pub entry: u32,
+    pub flags: u32,
+    pub sections: Vec<Section>,
+    pub segments: Vec<Segment>,
+    pub symbols: Vec<Symbol>,
+    /// Index of the first symbol from each symbol table section.
+    symtabs: Vec<(usize, usize)>,
+}
+
+/// Reads fields from some part of the file.
+struct Reader<'a> {
+    buf: &'a [u8],
+    big_endian: bool,
+}
+impl<'a> Reader<'a> {
+    fn bytes(&self, off: usize, len: usize) -> Result<&'a [u8], ElfError> {
+        off.checked_add(len).and_then(|end| self.buf.get(off..end))
+            .ok_or(ElfError::Truncated { offset: off })
+    }
+    fn u8(&self, off: usize) -> Result<u8, ElfError> {
+        Ok(self.bytes(off, 1)?[0])
+    }
+    fn u16(&self, off: usize) -> Result<u16, ElfError> {
+        let b = self.bytes(off, 2)?;
+        let b = [b[0], b[1]];
+        Ok(if self.big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) })
+    }
+    fn u32(&self, off: usize) -> Result<u32, ElfError> {
+        let b = self.bytes(off, 4)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Ok(if self.big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    }
+    /// Read a NUL-terminated string.
+    fn str(&self, off: usize) -> Result<String, ElfError> {
+        let rest = self.buf.get(off..).ok_or(ElfError::Truncated { offset: off })?;
+        let len = rest.iter().position(|b| *b == 0)
+            .ok_or(ElfError::Truncated { offset: self.buf.len() })?;
+        Ok(String::from_utf8_lossy(&rest[..len]).to_string())
+    }
+}
+
+impl<'a> Elf<'a> {
+    /// Parse an ELF file.
+    pub fn parse(buf: &'a [u8]) -> Result<Self, ElfError> {
+        if buf.len() < 16 || &buf[..4] != b"\x7fELF" {
+            return Err(ElfError::BadMagic);
+        }
+        if buf[4] != 1 {
+            return Err(ElfError::Unsupported("not ELF32"));
+        }
+        let big_endian = match buf[5] {
+            1 => false,
+            2 => true,
+            _ => return Err(ElfError::Unsupported("bad data encoding")),
+        };
+        let r = Reader { buf, big_endian };
+
+        let kind = r.u16(0x10)?;
+        let machine = r.u16(0x12)?;
+        if machine != EM_ARM {
+            return Err(ElfError::Unsupported("not ARM"));
+        }
+        let entry = r.u32(0x18)?;
+        let phoff = r.u32(0x1c)? as usize;
+        let shoff = r.u32(0x20)? as usize;
+        let flags = r.u32(0x24)?;
+        let phentsize = r.u16(0x2a)? as usize;
+        let phnum = r.u16(0x2c)? as usize;
+        let shentsize = r.u16(0x2e)? as usize;
+        let shnum = r.u16(0x30)? as usize;
+        let shstrndx = r.u16(0x32)? as usize;
+
+        let mut segments = Vec::new();
+        for i in 0..phnum {
+            let off = phoff + i * phentsize;
+            segments.push(Segment {
+                kind: r.u32(off)?,
+                offset: r.u32(off + 0x4)?,
+                vaddr: r.u32(off + 0x8)?,
+                paddr: r.u32(off + 0xc)?,
+                filesz: r.u32(off + 0x10)?,
+                memsz: r.u32(off + 0x14)?,
+                flags: r.u32(off + 0x18)?,
+            });
+        }
+
+        let mut sections = Vec::new();
+        let mut names = Vec::new();
+        for index in 0..shnum {
+            let off = shoff + index * shentsize;
+            names.push(r.u32(off)? as usize);
+            sections.push(Section {
+                index,
+                name: String::new(),
+                kind: r.u32(off + 0x4)?,
+                flags: r.u32(off + 0x8)?,
+                addr: r.u32(off + 0xc)?,
+                offset: r.u32(off + 0x10)?,
+                size: r.u32(off + 0x14)?,
+                link: r.u32(off + 0x18)?,
+                info: r.u32(off + 0x1c)?,
+                entsize: r.u32(off + 0x24)?,
+            });
+        }
+        if let Some(strtab) = sections.get(shstrndx).map(|s| s.offset as usize) {
+            for (sec, name) in sections.iter_mut().zip(names) {
+                sec.name = r.str(strtab + name)?;
+            }
+        }
+
+        let mut symbols = Vec::new();
+        let mut symtabs = Vec::new();
+        for symtab in sections.iter().filter(|s| s.kind == SHT_SYMTAB) {
+            symtabs.push((symtab.index, symbols.len()));
+            let strtab = sections.get(symtab.link as usize)
+                .ok_or(ElfError::Unsupported("bad symbol string table"))?
+                .offset as usize;
+            let entsize = if symtab.entsize == 0 { 16 } else { symtab.entsize };
+            for i in 0..(symtab.size / entsize) as usize {
+                let off = symtab.offset as usize + i * entsize as usize;
+                let info = r.u8(off + 0xc)?;
+                symbols.push(Symbol {
+                    name: r.str(strtab + r.u32(off)? as usize)?,
+                    value: r.u32(off + 0x4)?,
+                    size: r.u32(off + 0x8)?,
+                    kind: SymbolKind::from(info & 0xf),
+                    global: (info >> 4) != 0,
+                    shndx: r.u16(off + 0xe)?,
+                });
+            }
+        }
+
+        Ok(Elf {
+            buf, big_endian, kind, machine, entry, flags,
+            sections, segments, symbols, symtabs,
+        })
+    }
+
+    /// Returns the byte order of code in this file.
+    pub fn byte_order(&self) -> ByteOrder {
+        match (self.big_endian, (self.flags & EF_ARM_BE8) != 0) {
+            (false, _) => ByteOrder::Le,
+            (true, true) => ByteOrder::Be8,
+            (true, false) => ByteOrder::Be32,
+        }
+    }
+
+    /// Returns true if the BE-8 flag is set.
+    pub fn is_be8(&self) -> bool {
+        (self.flags & EF_ARM_BE8) != 0
+    }
+
+    /// Returns the EABI version (zero for old-ABI files).
+    pub fn eabi_version(&self) -> u8 {
+        ((self.flags & EF_ARM_EABIMASK) >> 24) as u8
+    }
+
+    /// Returns the section with some name.
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Returns all sections containing code.
+    pub fn code_sections(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter().filter(|s| s.is_exec() && s.kind == SHT_PROGBITS)
+    }
+
+    /// Returns the contents of some section.
+    pub fn data(&self, sec: &Section) -> &'a [u8] {
+        if sec.kind == SHT_NOBITS {
+            return &[];
+        }
+        let start = sec.offset as usize;
+        let end = start.saturating_add(sec.size as usize).min(self.buf.len());
+        self.buf.get(start..end).unwrap_or(&[])
+    }
+
+    /// Returns the symbols defined in some section, excluding mapping
+    /// symbols and section/file symbols.
+    pub fn labels(&self, sec: &Section) -> impl Iterator<Item = &Symbol> {
+        let index = sec.index;
+        self.symbols.iter().filter(move |s| s.shndx as usize == index
+            && !s.name.is_empty() && s.mapping().is_none()
+            && matches!(s.kind, SymbolKind::NoType | SymbolKind::Object
+                | SymbolKind::Func))
+    }
+
+    /// Returns the mapping symbols in some section as `(addr, region)`.
+    pub fn mapping_symbols(&self, sec: &Section) -> Vec<(u32, Region)> {
+        let mut res: Vec<(u32, Region)> = self.symbols.iter()
+            .filter(|s| s.shndx as usize == sec.index)
+            .filter_map(|s| Some((self.symbol_addr(s), s.mapping()?)))
+            .collect();
+        res.sort_by_key(|(addr, _)| *addr);
+        res
+    }
+
+    /// Returns the address of a symbol, which is relative to its section
+    /// in relocatable files.
+    pub fn symbol_addr(&self, sym: &Symbol) -> u32 {
+        match self.sections.get(sym.shndx as usize) {
+            Some(sec) if self.kind == ET_REL => sec.addr.wrapping_add(sym.addr()),
+            _ => sym.addr(),
+        }
+    }
+
+    /// Returns the functions in some section as `(addr, isa, name)`.
+    pub fn functions(&self, sec: &Section) -> Vec<(u32, Isa, &str)> {
+        self.labels(sec).filter_map(|s| {
+            Some((self.symbol_addr(s), s.isa()?, s.name.as_str()))
+        }).collect()
+    }
+
+    /// Returns the relocations which apply to some section, sorted by
+    /// offset.
+    pub fn relocs(&self, sec: &Section) -> Result<Vec<Reloc>, ElfError> {
+        let r = Reader { buf: self.buf, big_endian: self.big_endian };
+        let mut res = Vec::new();
+        let rel_secs = self.sections.iter().filter(|s| {
+            matches!(s.kind, SHT_REL | SHT_RELA) && s.info as usize == sec.index
+        });
+        for rel in rel_secs {
+            let first = self.symtabs.iter()
+                .find(|(index, _)| *index == rel.link as usize)
+                .map(|(_, first)| *first)
+                .ok_or(ElfError::Unsupported("bad relocation symbol table"))?;
+            let rela = rel.kind == SHT_RELA;
+            let entsize = match rel.entsize {
+                0 if rela => 12,
+                0 => 8,
+                n => n,
+            };
+            for i in 0..(rel.size / entsize) as usize {
+                let off = rel.offset as usize + i * entsize as usize;
+                let info = r.u32(off + 0x4)?;
+                let sym = first + (info >> 8) as usize;
+                if sym >= self.symbols.len() {
+                    return Err(ElfError::Unsupported("bad relocation symbol"));
+                }
+                // Offsets are relative to the section in relocatable files
+                let offset = r.u32(off)?;
+                res.push(Reloc {
+                    offset: if self.kind == ET_REL {
+                        sec.addr.wrapping_add(offset)
+                    } else {
+                        offset
+                    },
+                    kind: info & 0xff,
+                    sym,
+                    addend: if rela { Some(r.u32(off + 0x8)? as i32) } else { None },
+                });
+            }
+        }
+        res.sort_by_key(|r| r.offset);
+        Ok(res)
+    }
+
+    /// Set up a linear sweep over some section, using the mapping symbols
+    /// (or function symbols and the entry point, if there aren't any).
+    pub fn sweep(&self, sec: &Section) -> LinearSweep<'a> {
+        self.sweep_over(sec, self.data(sec))
+    }
+
+    /// Like [Elf::sweep], but over a copy of the section contents (i.e.
+    /// with relocations applied).
+    pub fn sweep_over<'b>(&self, sec: &Section, data: &'b [u8])
+        -> LinearSweep<'b>
+    {
+        let order = self.byte_order();
+        let mut sweep = LinearSweep::new(data, sec.addr, order, Isa::Arm);
+        for sym in self.symbols.iter().filter(|s| s.shndx as usize == sec.index) {
+            if sym.mapping().is_some() {
+                sweep.add_mapping_symbol(&sym.name, self.symbol_addr(sym));
+            } else if sym.kind == SymbolKind::Func {
+                let thumb = (sym.value & 1) != 0;
+                sweep.add_target(self.symbol_addr(sym) | thumb as u32);
+            }
+        }
+        if self.kind != ET_REL && sec.contains(self.entry & !1) {
+            sweep.add_target(self.entry);
+        }
+        sweep
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::disas::Disassembler;
+
+    fn read(name: &str) -> Vec<u8> {
+        std::fs::read(format!("{}/testsuite/{}", env!("CARGO_MANIFEST_DIR"),
+            name)).unwrap()
+    }
+
+    #[test]
+    fn sections_and_symbols() {
+        for (name, order) in [
+            ("elf_test.le.o", ByteOrder::Le),
+            ("elf_test.be.o", ByteOrder::Be32),
+        ].iter() {
+            let buf = read(name);
+            let elf = Elf::parse(&buf).unwrap();
+            assert_eq!(elf.kind, ET_REL);
+            assert_eq!(elf.byte_order(), *order);
+            assert_eq!(elf.eabi_version(), 5);
+
+            let text = elf.section(".text").unwrap();
+            assert_eq!(elf.code_sections().collect::<Vec<&Section>>(), vec![text]);
+            assert_eq!(elf.data(text).len(), 0x14);
+            assert_eq!(elf.data(elf.section(".data").unwrap()), b"Hello\0");
+
+            assert_eq!(elf.mapping_symbols(text), vec![
+                (0x00, Region::Code(Isa::Arm)),
+                (0x0c, Region::Code(Isa::Thumb)),
+                (0x10, Region::Data),
+            ]);
+            assert_eq!(elf.functions(text), vec![
+                (0x00, Isa::Arm, "_start"),
+                (0x0c, Isa::Thumb, "thumb_fn"),
+            ]);
+            let message = elf.symbols.iter().find(|s| s.name == "message").unwrap();
+            assert_eq!(message.kind, SymbolKind::Object);
+            assert!(!message.global);
+        }
+    }
+
+    #[test]
+    fn sweep_with_mapping_symbols() {
+        let buf = read("elf_test.be.o");
+        let elf = Elf::parse(&buf).unwrap();
+        let mut d = Disassembler::new();
+        let lines = elf.sweep(elf.section(".text").unwrap()).disassemble(&mut d);
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, vec![
+            "ldr\tr0, [pc, #8]", "blx\t0x4", "bx\tlr",
+            "movs\tr0, #1", "bx\tlr", ".word\t0x00000000",
+        ]);
+    }
+
+    #[test]
+    fn flags_and_errors() {
+        let mut buf = read("elf_test.be.o");
+        // Set EF_ARM_BE8
+        buf[0x25] |= 0x80;
+        assert_eq!(Elf::parse(&buf).unwrap().byte_order(), ByteOrder::Be8);
+
+        assert!(matches!(Elf::parse(b"\x7fELX").err(), Some(ElfError::BadMagic)));
+        buf[4] = 2;
+        assert!(matches!(Elf::parse(&buf).err(), Some(ElfError::Unsupported(_))));
+        let buf = read("elf_test.le.o");
+        assert!(matches!(Elf::parse(&buf[..0x40]).err(),
+            Some(ElfError::Truncated { .. })));
+    }
+}
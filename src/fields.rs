@@ -51,7 +51,6 @@ impl fmt::Display for Cond {
             Cond::Le => write!(f, "le"),
             Cond::Al => write!(f, ""),
             Cond::Un => write!(f, ""),
-            _ => unreachable!(),
         }
     }
 }
@@ -161,7 +160,6 @@ impl fmt::Display for Register {
             Register::sp  => write!(f, "sp"),
             Register::lr  => write!(f, "lr"),
             Register::pc  => write!(f, "pc"),
-            _ => unreachable!(),
         }
     }
 }
@@ -319,3 +317,136 @@ impl fmt::Display for CoprocRegister {
 }
 
 
+
+
+/// VFP single-precision registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingleRegister {
+    s0, s1, s2, s3, s4, s5, s6, s7, s8, s9, s10, s11, s12, s13, s14, s15,
+    s16, s17, s18, s19, s20, s21, s22, s23, s24, s25, s26, s27, s28, s29,
+    s30, s31
+}
+impl SingleRegister {
+    const ALL: [SingleRegister; 32] = {
+        use SingleRegister::*;
+        [s0, s1, s2, s3, s4, s5, s6, s7, s8, s9, s10, s11, s12, s13, s14, s15,
+        s16, s17, s18, s19, s20, s21, s22, s23, s24, s25, s26, s27, s28, s29,
+        s30, s31]
+    };
+    pub fn from_u32(x: u32) -> Self { SingleRegister::ALL[x as usize] }
+}
+impl fmt::Display for SingleRegister {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "s{}", *self as u32)
+    }
+}
+
+/// VFP double-precision registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoubleRegister {
+    d0, d1, d2, d3, d4, d5, d6, d7, d8, d9, d10, d11, d12, d13, d14, d15
+}
+impl DoubleRegister {
+    const ALL: [DoubleRegister; 16] = {
+        use DoubleRegister::*;
+        [d0, d1, d2, d3, d4, d5, d6, d7, d8, d9, d10, d11, d12, d13, d14, d15]
+    };
+    pub fn from_u32(x: u32) -> Self { DoubleRegister::ALL[x as usize] }
+}
+impl fmt::Display for DoubleRegister {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "d{}", *self as u32)
+    }
+}
+
+/// VFP system registers (the encodings of the Fn field in FMXR/FMRX).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VfpSysRegister { fpsid, fpscr, fpexc, fpinst, fpinst2 }
+impl VfpSysRegister {
+    pub fn from_u32(x: u32) -> Self {
+        match x {
+            0b0000 => VfpSysRegister::fpsid,
+            0b0001 => VfpSysRegister::fpscr,
+            0b1000 => VfpSysRegister::fpexc,
+            0b1001 => VfpSysRegister::fpinst,
+            0b1010 => VfpSysRegister::fpinst2,
+            _ => unreachable!(),
+        }
+    }
+    /// Returns true if some Fn field names a VFPv2 system register.
+    pub fn is_valid(x: u32) -> bool {
+        matches!(x, 0b0000 | 0b0001 | 0b1000 | 0b1001 | 0b1010)
+    }
+}
+impl fmt::Display for VfpSysRegister {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// FPA registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpaRegister { f0, f1, f2, f3, f4, f5, f6, f7 }
+impl FpaRegister {
+    const ALL: [FpaRegister; 8] = {
+        use FpaRegister::*;
+        [f0, f1, f2, f3, f4, f5, f6, f7]
+    };
+    pub fn from_u32(x: u32) -> Self { FpaRegister::ALL[x as usize] }
+}
+impl fmt::Display for FpaRegister {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "f{}", *self as u32)
+    }
+}
+
+/// FPA precisions (single, double, extended and packed decimal). Only
+/// loads and stores can use packed decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpaPrecision { S, D, E, P }
+impl FpaPrecision {
+    pub fn from_u32(x: u32) -> Self {
+        match x {
+            0b00 => FpaPrecision::S,
+            0b01 => FpaPrecision::D,
+            0b10 => FpaPrecision::E,
+            0b11 => FpaPrecision::P,
+            _ => unreachable!(),
+        }
+    }
+}
+impl fmt::Display for FpaPrecision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FpaPrecision::S => write!(f, "s"),
+            FpaPrecision::D => write!(f, "d"),
+            FpaPrecision::E => write!(f, "e"),
+            FpaPrecision::P => write!(f, "p"),
+        }
+    }
+}
+
+/// FPA rounding modes. Rounding to nearest has no suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpaRounding { Nearest, PlusInf, MinusInf, Zero }
+impl FpaRounding {
+    pub fn from_u32(x: u32) -> Self {
+        match x {
+            0b00 => FpaRounding::Nearest,
+            0b01 => FpaRounding::PlusInf,
+            0b10 => FpaRounding::MinusInf,
+            0b11 => FpaRounding::Zero,
+            _ => unreachable!(),
+        }
+    }
+}
+impl fmt::Display for FpaRounding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FpaRounding::Nearest => write!(f, ""),
+            FpaRounding::PlusInf => write!(f, "p"),
+            FpaRounding::MinusInf => write!(f, "m"),
+            FpaRounding::Zero => write!(f, "z"),
+        }
+    }
+}
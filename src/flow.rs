@@ -0,0 +1,194 @@
+//! Classifying instructions by their effect on control flow.
+
+use armbf_prim::*;
+
+use crate::disas::Isa;
+use crate::disas::branch::sign_extend;
+use crate::inst::*;
+
+/// The effect of an instruction on control flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Execution continues with the next instruction.
+    Next,
+    /// A direct branch.
+    Jump { target: u32, cond: bool },
+    /// A direct call (which might switch to another instruction set).
+    Call { target: u32, isa: Isa, cond: bool },
+    /// A return from a function.
+    Return { cond: bool },
+    /// A branch to some address computed at runtime.
+    IndirectJump { cond: bool },
+    /// A call to some address computed at runtime.
+    IndirectCall { cond: bool },
+    /// An undefined instruction, where execution can't continue.
+    Stop,
+}
+impl Flow {
+    /// Returns true if execution might continue with the next instruction.
+    pub fn falls_through(&self) -> bool {
+        match self {
+            Flow::Next | Flow::Call { .. } | Flow::IndirectCall { .. } => true,
+            Flow::Jump { cond, .. } |
+            Flow::Return { cond } |
+            Flow::IndirectJump { cond } => *cond,
+            Flow::Stop => false,
+        }
+    }
+}
+
+
+/// Classify an ARM instruction at some address.
+pub fn arm_flow(addr: u32, x: u32) -> Flow {
+    let cond = get_cond!(x) != 0b1110;
+    let rd = get_rd!(x);
+    let target = || {
+        let imm24 = sign_extend(get_imm24!(x) as i32, 24) << 2;
+        addr.wrapping_add(8).wrapping_add(imm24 as u32)
+    };
+
+    match ArmInst::decode(x) {
+        ArmInst::None => Flow::Stop,
+        ArmInst::B => Flow::Jump { target: target(), cond },
+        ArmInst::Bl => Flow::Call { target: target(), isa: Isa::Arm, cond },
+        ArmInst::BlxImm => Flow::Call {
+            target: target() | ((x >> 23) & 0b10), isa: Isa::Thumb, cond: false,
+        },
+        ArmInst::Bx if get_rm!(x) == 14 => Flow::Return { cond },
+        ArmInst::Bx => Flow::IndirectJump { cond },
+        ArmInst::BlxReg => Flow::IndirectCall { cond },
+
+        // mov pc, lr
+        ArmInst::MovShiftImm if rd == 15 && (x & 0xfff) == 14 => {
+            Flow::Return { cond }
+        },
+        inst if inst.is_dp() && !inst.is_compare() && rd == 15 => {
+            Flow::IndirectJump { cond }
+        },
+
+        // ldr pc, [sp], #4
+        ArmInst::LdrImm if rd == 15 && (x & 0x01ff_ffff) == 0x009d_f004 => {
+            Flow::Return { cond }
+        },
+        ArmInst::LdrImm | ArmInst::LdrReg if rd == 15 => {
+            Flow::IndirectJump { cond }
+        },
+
+        ArmInst::Ldmia | ArmInst::Ldmib | ArmInst::Ldmda | ArmInst::Ldmdb
+            if bit!(x, 15) =>
+        {
+            if get_rn!(x) == 13 {
+                Flow::Return { cond }
+            } else {
+                Flow::IndirectJump { cond }
+            }
+        },
+        _ => Flow::Next,
+    }
+}
+
+/// Classify a Thumb instruction at some address.
+///
+/// `next` is the following halfword (if any), used to decode BL/BLX pairs.
+/// Returns the flow and the size of the instruction in bytes.
+pub fn thumb_flow(addr: u32, x: u16, next: Option<u16>) -> (Flow, u32) {
+    let pc = addr.wrapping_add(4);
+    let flow = match ThumbInst::decode(x) {
+        ThumbInst::None => Flow::Stop,
+        ThumbInst::BranchCond => {
+            let off = sign_extend((x & 0xff) as i32, 8) << 1;
+            Flow::Jump { target: pc.wrapping_add(off as u32), cond: true }
+        },
+        ThumbInst::BranchUncond => {
+            let off = sign_extend((x & 0x7ff) as i32, 11) << 1;
+            Flow::Jump { target: pc.wrapping_add(off as u32), cond: false }
+        },
+        ThumbInst::BlPrefix => {
+            let lo = match next {
+                Some(lo) => lo,
+                None => return (Flow::Stop, 2),
+            };
+            let off = (sign_extend((x & 0x7ff) as i32, 11) << 12)
+                | ((lo & 0x7ff) as i32) << 1;
+            let target = pc.wrapping_add(off as u32);
+            let flow = match ThumbInst::decode(lo) {
+                ThumbInst::Bl => Flow::Call {
+                    target, isa: Isa::Thumb, cond: false
+                },
+                ThumbInst::Blx => Flow::Call {
+                    target: target & !3, isa: Isa::Arm, cond: false
+                },
+                _ => return (Flow::Stop, 2),
+            };
+            return (flow, 4);
+        },
+        // A suffix without a prefix
+        ThumbInst::Bl | ThumbInst::Blx => Flow::Stop,
+
+        ThumbInst::Bx if (x >> 3) & 0xf == 14 => Flow::Return { cond: false },
+        ThumbInst::Bx => Flow::IndirectJump { cond: false },
+        ThumbInst::BlxReg => Flow::IndirectCall { cond: false },
+        ThumbInst::Pop if bit!(x, 8) => Flow::Return { cond: false },
+
+        ThumbInst::MovReg | ThumbInst::AddReg2 => {
+            let rd = ((x >> 4) & 0b1000) | (x & 0b111);
+            match (rd, (x >> 3) & 0xf) {
+                (15, 14) if ThumbInst::decode(x) == ThumbInst::MovReg => {
+                    Flow::Return { cond: false }
+                },
+                (15, _) => Flow::IndirectJump { cond: false },
+                _ => Flow::Next,
+            }
+        },
+        _ => Flow::Next,
+    };
+    (flow, 2)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arm() {
+        assert_eq!(arm_flow(0x100, 0xeafffffe), Flow::Jump { target: 0x100, cond: false });
+        assert_eq!(arm_flow(0x100, 0x1a000000), Flow::Jump { target: 0x108, cond: true });
+        assert_eq!(arm_flow(0x100, 0xebffffff), Flow::Call {
+            target: 0x104, isa: Isa::Arm, cond: false
+        });
+        assert_eq!(arm_flow(0x100, 0xfb000000), Flow::Call {
+            target: 0x10a, isa: Isa::Thumb, cond: false
+        });
+        assert_eq!(arm_flow(0, 0xe12fff1e), Flow::Return { cond: false });
+        assert_eq!(arm_flow(0, 0x012fff13), Flow::IndirectJump { cond: true });
+        assert_eq!(arm_flow(0, 0xe12fff33), Flow::IndirectCall { cond: false });
+        assert_eq!(arm_flow(0, 0xe1a0f00e), Flow::Return { cond: false });
+        assert_eq!(arm_flow(0, 0xe08ff100), Flow::IndirectJump { cond: false });
+        assert_eq!(arm_flow(0, 0xe49df004), Flow::Return { cond: false });
+        assert_eq!(arm_flow(0, 0xe59ff000), Flow::IndirectJump { cond: false });
+        assert_eq!(arm_flow(0, 0xe8bd8010), Flow::Return { cond: false });
+        assert_eq!(arm_flow(0, 0xe35f0000), Flow::Next);
+        assert_eq!(arm_flow(0, 0xe1a00000), Flow::Next);
+    }
+
+    #[test]
+    fn thumb() {
+        assert_eq!(thumb_flow(0x10, 0xd0fe, None),
+            (Flow::Jump { target: 0x10, cond: true }, 2));
+        assert_eq!(thumb_flow(0x10, 0xe7fe, None),
+            (Flow::Jump { target: 0x10, cond: false }, 2));
+        assert_eq!(thumb_flow(0x10, 0xf000, Some(0xf802)),
+            (Flow::Call { target: 0x18, isa: Isa::Thumb, cond: false }, 4));
+        assert_eq!(thumb_flow(0x12, 0xf000, Some(0xe802)),
+            (Flow::Call { target: 0x18, isa: Isa::Arm, cond: false }, 4));
+        assert_eq!(thumb_flow(0, 0xf000, None), (Flow::Stop, 2));
+        assert_eq!(thumb_flow(0, 0x4770, None), (Flow::Return { cond: false }, 2));
+        assert_eq!(thumb_flow(0, 0x4718, None), (Flow::IndirectJump { cond: false }, 2));
+        assert_eq!(thumb_flow(0, 0x4798, None), (Flow::IndirectCall { cond: false }, 2));
+        assert_eq!(thumb_flow(0, 0xbd10, None), (Flow::Return { cond: false }, 2));
+        assert_eq!(thumb_flow(0, 0x46f7, None), (Flow::Return { cond: false }, 2));
+        assert_eq!(thumb_flow(0, 0x4487, None), (Flow::IndirectJump { cond: false }, 2));
+        assert_eq!(thumb_flow(0, 0x2001, None), (Flow::Next, 2));
+    }
+}
@@ -0,0 +1,291 @@
+//! Heuristics for finding functions in images without symbols.
+//!
+//! Every word (or halfword) in the primary instruction set is treated as a
+//! possible instruction. Function starts are found from:
+//!
+//! - Targets of BL/BLX instructions (which might be in either mode)
+//! - Prologues (`stmdb sp!, {..., lr}`, `push {..., lr}`, and the APCS
+//!   `mov ip, sp; stmdb sp!, {fp, ip, lr, pc}` sequence)
+//!
+//! Each candidate is then scored with some weaker evidence: whether it
+//! follows a return or unconditional branch, follows padding, or is
+//! aligned. Since data is decoded as code too, some candidates will be
+//! bogus; these usually have a low confidence.
+
+use std::collections::BTreeMap;
+
+use crate::disas::Isa;
+use crate::flow::*;
+use crate::memory::{MemoryImage, Unmapped};
+use crate::stream::*;
+
+/// A reason for believing that a function starts at some address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evidence {
+    /// The target of some number of direct calls.
+    Called { callers: usize },
+    /// Starts with a push of the link register.
+    Prologue,
+    /// Starts by setting up an APCS stack frame.
+    FramePrologue,
+    /// Follows a return or an unconditional branch.
+    AfterReturn,
+    /// Follows padding (zeroes or NOPs).
+    AfterPadding,
+    /// Aligned to a 16-byte boundary.
+    Aligned,
+}
+impl Evidence {
+    fn weight(&self) -> f32 {
+        match self {
+            Evidence::Called { callers } => 0.5 + 0.1 * (*callers - 1).min(2) as f32,
+            Evidence::Prologue => 0.4,
+            Evidence::FramePrologue => 0.6,
+            Evidence::AfterReturn => 0.15,
+            Evidence::AfterPadding => 0.1,
+            Evidence::Aligned => 0.05,
+        }
+    }
+}
+
+/// A function found in an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub addr: u32,
+    pub isa: Isa,
+    /// Between 0 and 1.
+    pub confidence: f32,
+    pub evidence: Vec<Evidence>,
+}
+
+const ARM_NOP: u32 = 0xe1a0_0000;
+const THUMB_NOP: u16 = 0x46c0;
+
+
+/// Finds functions in some image.
+pub struct FunctionFinder<'a> {
+    buf: &'a [u8],
+    base: u32,
+    order: ByteOrder,
+}
+
+impl<'a> FunctionFinder<'a> {
+    pub fn new(buf: &'a [u8], base: u32, order: ByteOrder) -> Self {
+        FunctionFinder { buf, base, order }
+    }
+
+    /// Create a finder over the segment of a memory image containing some
+    /// address.
+    pub fn from_memory(mem: &'a MemoryImage, addr: u32) -> Result<Self, Unmapped> {
+        let (seg, buf) = mem.segment_contents(addr)?;
+        Ok(FunctionFinder::new(buf, seg.base, seg.order))
+    }
+
+    fn arm_at(&self, addr: u32) -> Option<u32> {
+        let off = addr.checked_sub(self.base)? as usize;
+        let (_, x) = ArmStream::new(self.buf.get(off..)?, addr, self.order)
+            .next()?.ok()?;
+        Some(x)
+    }
+
+    fn thumb_at(&self, addr: u32) -> Option<u16> {
+        let off = addr.checked_sub(self.base)? as usize;
+        let (_, x) = ThumbStream::new(self.buf.get(off..)?, addr, self.order)
+            .next()?.ok()?;
+        Some(x)
+    }
+
+    /// Returns the flow of the instruction at some address.
+    fn flow_at(&self, addr: u32, isa: Isa) -> Option<Flow> {
+        match isa {
+            Isa::Arm => self.arm_at(addr).map(|x| arm_flow(addr, x)),
+            Isa::Thumb => self.thumb_at(addr).map(|x| {
+                thumb_flow(addr, x, self.thumb_at(addr.wrapping_add(2))).0
+            }),
+        }
+    }
+
+    /// Check for a prologue at some address.
+    fn prologue(&self, addr: u32, isa: Isa) -> Option<Evidence> {
+        match isa {
+            Isa::Arm => {
+                let x = self.arm_at(addr)?;
+                // mov ip, sp; stmdb sp!, {fp, ip, lr, pc}
+                if x == 0xe1a0_c00d
+                    && self.arm_at(addr.wrapping_add(4)) == Some(0xe92d_d800)
+                {
+                    return Some(Evidence::FramePrologue);
+                }
+                // The second half of the sequence above
+                if self.arm_at(addr.wrapping_sub(4)) == Some(0xe1a0_c00d) {
+                    return None;
+                }
+                // stmdb sp!, {..., lr} or str lr, [sp, #-4]!
+                if (x & 0xffff_4000) == 0xe92d_4000 || x == 0xe52d_e004 {
+                    return Some(Evidence::Prologue);
+                }
+                None
+            },
+            Isa::Thumb => {
+                // push {..., lr}
+                if (self.thumb_at(addr)? & 0xff00) == 0xb500 {
+                    return Some(Evidence::Prologue);
+                }
+                None
+            },
+        }
+    }
+
+    /// Check for padding or a return/unconditional branch before some
+    /// address, assuming it's in some instruction set.
+    fn preceded_by(&self, addr: u32, isa: Isa) -> Option<Evidence> {
+        let prev = match isa {
+            Isa::Arm => addr.wrapping_sub(4),
+            Isa::Thumb => addr.wrapping_sub(2),
+        };
+        let is_padding = match isa {
+            Isa::Arm => matches!(self.arm_at(prev)?, 0 | ARM_NOP),
+            Isa::Thumb => matches!(self.thumb_at(prev)?, 0 | THUMB_NOP),
+        };
+        if is_padding {
+            return Some(Evidence::AfterPadding);
+        }
+        match self.flow_at(prev, isa)? {
+            Flow::Stop => None,
+            flow if !flow.falls_through() => Some(Evidence::AfterReturn),
+            _ => None,
+        }
+    }
+
+    /// Collect the weaker evidence for a candidate.
+    fn context(&self, addr: u32, isa: Isa, evidence: &mut Vec<Evidence>) {
+        // Thumb functions are often placed right after ARM code
+        let prev = match isa {
+            Isa::Thumb if addr & 3 == 0 => self.preceded_by(addr, isa)
+                .or_else(|| self.preceded_by(addr, Isa::Arm)),
+            _ => self.preceded_by(addr, isa),
+        };
+        evidence.extend(prev);
+        if addr & 0xf == 0 {
+            evidence.push(Evidence::Aligned);
+        }
+    }
+
+    /// Find functions, assuming that most of the image is code in some
+    /// instruction set.
+    pub fn find(&self, isa: Isa) -> Vec<Function> {
+        let mut cands: BTreeMap<u32, (Isa, Vec<Evidence>)> = BTreeMap::new();
+        let mut callers: BTreeMap<(u32, Isa), usize> = BTreeMap::new();
+        let end = self.base as u64 + self.buf.len() as u64;
+        let in_range = |a: u32| a >= self.base && (a as u64) < end;
+
+        let step = match isa { Isa::Arm => 4, Isa::Thumb => 2 };
+        for off in (0..self.buf.len()).step_by(step) {
+            let addr = self.base.wrapping_add(off as u32);
+            if let Some(Flow::Call { target, isa, .. }) = self.flow_at(addr, isa) {
+                // Unrelocated calls in object files branch to themselves
+                if in_range(target) && target != addr {
+                    *callers.entry((target, isa)).or_insert(0) += 1;
+                }
+            }
+            if let Some(e) = self.prologue(addr, isa) {
+                cands.entry(addr).or_insert((isa, Vec::new())).1.push(e);
+            }
+        }
+
+        for ((target, isa), n) in callers {
+            // Calls into the middle of an instruction are bogus
+            if matches!(self.flow_at(target, isa), None | Some(Flow::Stop)) {
+                continue;
+            }
+            let (cand_isa, evidence) = cands.entry(target)
+                .or_insert((isa, Vec::new()));
+            if *cand_isa != isa {
+                continue;
+            }
+            // Prologues were only checked for in the primary mode
+            if evidence.is_empty() {
+                evidence.extend(self.prologue(target, isa));
+            }
+            evidence.insert(0, Evidence::Called { callers: n });
+        }
+
+        cands.into_iter().map(|(addr, (isa, mut evidence))| {
+            self.context(addr, isa, &mut evidence);
+            let confidence = evidence.iter().map(Evidence::weight).sum::<f32>()
+                .min(1.0);
+            Function { addr, isa, confidence, evidence }
+        }).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn be32(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|w| w.to_be_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn arm_functions() {
+        let buf = be32(&[
+            0xe92d4010, // 00: push {r4, lr}
+            0xeb000002, // 04: bl   0x14
+            0xe8bd8010, // 08: pop  {r4, pc}
+            0xe1a00000, // 0c: nop
+            0xe1a00000, // 10: nop
+            0xe1a0c00d, // 14: mov  ip, sp
+            0xe92dd800, // 18: push {r11, r12, lr, pc}
+            0xe89da800, // 1c: ldm  sp, {r11, sp, pc}
+            0xe3a00001, // 20: mov  r0, #1
+            0xe12fff1e, // 24: bx   lr
+        ]);
+        let funcs = FunctionFinder::new(&buf, 0x8000, ByteOrder::Be32)
+            .find(Isa::Arm);
+        assert_eq!(funcs.iter().map(|f| f.addr).collect::<Vec<u32>>(),
+            vec![0x8000, 0x8014]);
+
+        assert_eq!(funcs[0].evidence, vec![Evidence::Prologue, Evidence::Aligned]);
+        assert_eq!(funcs[1].evidence, vec![
+            Evidence::Called { callers: 1 }, Evidence::FramePrologue,
+            Evidence::AfterPadding,
+        ]);
+        assert!(funcs[1].confidence > funcs[0].confidence);
+        assert!(funcs[1].confidence <= 1.0);
+    }
+
+    #[test]
+    fn interworking_calls() {
+        let mut buf = be32(&[
+            0xfa000000, // 00: blx  0x8
+            0xe12fff1e, // 04: bx   lr
+        ]);
+        // 08: push {r4, lr}; pop {r4, pc}
+        buf.extend_from_slice(&[0xb5, 0x10, 0xbd, 0x10]);
+        let funcs = FunctionFinder::new(&buf, 0, ByteOrder::Be32)
+            .find(Isa::Arm);
+        assert_eq!(funcs.len(), 1);
+        assert_eq!(funcs[0].addr, 0x8);
+        assert_eq!(funcs[0].isa, Isa::Thumb);
+        assert_eq!(funcs[0].evidence, vec![
+            Evidence::Called { callers: 1 }, Evidence::Prologue,
+            Evidence::AfterReturn,
+        ]);
+    }
+
+    #[test]
+    fn top_of_memory() {
+        let buf = be32(&[
+            0xe92d4010, // push {r4, lr}
+            0xe8bd8010, // pop  {r4, pc}
+            0xe1a00000, // nop
+            0xe92d4010, // push {r4, lr}
+        ]);
+        let finder = FunctionFinder::new(&buf, 0xffff_fff0, ByteOrder::Be32);
+        assert_eq!(finder.find(Isa::Arm).iter().map(|f| f.addr).collect::<Vec<u32>>(),
+            vec![0xffff_fff0, 0xffff_fffc]);
+        assert!(finder.find(Isa::Thumb).iter().all(|f| f.addr >= 0xffff_fff0));
+    }
+}
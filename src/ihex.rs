@@ -0,0 +1,122 @@
+//! Intel HEX files.
+//!
+//! Each record is a line `:LLAAAATT<data>CC`, where `LL` is the number of
+//! data bytes, `AAAA` is a 16-bit address, `TT` is the record type and `CC`
+//! is the two's complement of the sum of all other bytes. Addresses above
+//! 64KiB are formed with extended segment (`02`) or extended linear (`04`)
+//! address records.
+
+use crate::image::*;
+
+const DATA: u8 = 0x00;
+const EOF: u8 = 0x01;
+const EXT_SEGMENT_ADDR: u8 = 0x02;
+const START_SEGMENT_ADDR: u8 = 0x03;
+const EXT_LINEAR_ADDR: u8 = 0x04;
+const START_LINEAR_ADDR: u8 = 0x05;
+
+/// Parse a single record, returning `(type, address, data)`.
+fn record(line: &str) -> Result<(u8, u16, Vec<u8>), RecordError> {
+    let hex = line.strip_prefix(':').ok_or(RecordError::BadStart)?;
+    let bytes = hex_bytes(hex)?;
+    if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+        return Err(RecordError::BadLength);
+    }
+    let (body, cs) = bytes.split_at(bytes.len() - 1);
+    let expected = body.iter().fold(0u8, |acc, b| acc.wrapping_add(*b))
+        .wrapping_neg();
+    if expected != cs[0] {
+        return Err(RecordError::Checksum { expected, found: cs[0] });
+    }
+    let addr = u16::from_be_bytes([body[1], body[2]]);
+    Ok((body[3], addr, body[4..].to_vec()))
+}
+
+/// Parse an Intel HEX file.
+pub fn parse(text: &str) -> Result<Image, ParseError> {
+    let mut img = Image::new();
+    let mut base = 0u32;
+    let lines = text.lines().enumerate()
+        .map(|(i, l)| (i + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty());
+
+    for (line, s) in lines {
+        let err = |kind| ParseError { line, kind };
+        let (kind, addr, data) = record(s).map_err(err)?;
+        let word = |data: &[u8]| -> Result<u32, ParseError> {
+            match data.len() {
+                2 => Ok(u16::from_be_bytes([data[0], data[1]]) as u32),
+                4 => Ok(u32::from_be_bytes([data[0], data[1], data[2], data[3]])),
+                _ => Err(err(RecordError::BadLength)),
+            }
+        };
+        match kind {
+            DATA => {
+                let addr = base.wrapping_add(addr as u32);
+                img.write(addr, &data)
+                    .map_err(|a| err(RecordError::Overlap(a)))?;
+            },
+            EOF => return Ok(img),
+            EXT_SEGMENT_ADDR => base = word(&data)? << 4,
+            EXT_LINEAR_ADDR => base = word(&data)? << 16,
+            START_SEGMENT_ADDR => {
+                let x = word(&data)?;
+                img.entry = Some(((x >> 16) << 4).wrapping_add(x & 0xffff));
+            },
+            START_LINEAR_ADDR => img.entry = Some(word(&data)?),
+            t => return Err(err(RecordError::UnknownType(t))),
+        }
+    }
+    Err(ParseError {
+        line: text.lines().count(),
+        kind: RecordError::MissingEof,
+    })
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extended_addresses() {
+        let img = parse("\
+            :020000040800F2\n\
+            :04000000E12FFF1ECF\n\
+            :02000400704743\n\
+            :020000021000EC\n\
+            :01000000AA55\n\
+            :0400000508000001EE\n\
+            :00000001FF\n").unwrap();
+        assert_eq!(img.chunks().collect::<Vec<(u32, &[u8])>>(), vec![
+            (0x0001_0000, &[0xaa][..]),
+            (0x0800_0000, &[0xe1, 0x2f, 0xff, 0x1e, 0x70, 0x47][..]),
+        ]);
+        assert_eq!(img.entry, Some(0x0800_0001));
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s| parse(s).unwrap_err();
+        assert_eq!(err(":00000001FE\n"), ParseError {
+            line: 1, kind: RecordError::Checksum { expected: 0xff, found: 0xfe },
+        });
+        assert_eq!(err("\n00000001FF").kind, RecordError::BadStart);
+        assert_eq!(err(":0200000001FD").kind, RecordError::BadLength);
+        assert_eq!(err(":0000000AF6").kind, RecordError::UnknownType(0xa));
+        assert_eq!(err(":0100000001FE\n:0100000002FD").kind,
+            RecordError::Overlap(0));
+        assert_eq!(err(":0100000001FE\n"), ParseError {
+            line: 1, kind: RecordError::MissingEof,
+        });
+    }
+
+    #[test]
+    fn corpus() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let bin = std::fs::read(format!("{}/testsuite/arm_test.bin", dir)).unwrap();
+        let hex = std::fs::read_to_string(format!("{}/testsuite/arm_test.hex", dir))
+            .unwrap();
+        assert_eq!(Image::parse(&hex).unwrap(), Image::from_bin(0, &bin));
+    }
+}
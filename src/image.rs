@@ -0,0 +1,230 @@
+//! Sparse images loaded from firmware files.
+//!
+//! Formats like Intel HEX and Motorola S-records describe memory as a set of
+//! records at arbitrary addresses, possibly with gaps between them. An
+//! [Image] collects these into contiguous chunks which can be handed to
+//! the disassembly and analysis passes (which all work on a buffer and its
+//! base address), or flattened into a single buffer.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::disas::{Disassembler, Isa};
+use crate::stream::*;
+use crate::sweep::*;
+
+/// The reason a firmware file couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    /// A line doesn't start with the record mark.
+    BadStart,
+    /// A line contains something other than hex digits.
+    BadHex,
+    /// The length of a record doesn't match its byte count.
+    BadLength,
+    /// The checksum of a record is wrong.
+    Checksum { expected: u8, found: u8 },
+    /// An unknown record type.
+    UnknownType(u8),
+    /// A record overlaps data which was already loaded.
+    Overlap(u32),
+    /// A record count doesn't match the number of data records.
+    BadCount,
+    /// The file doesn't end with an end-of-file record.
+    MissingEof,
+}
+
+/// Error returned when a firmware file can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line number (starting from 1).
+    pub line: usize,
+    pub kind: RecordError,
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            RecordError::BadStart => write!(f, "missing record mark"),
+            RecordError::BadHex => write!(f, "invalid hex digits"),
+            RecordError::BadLength => write!(f, "record length doesn't match byte count"),
+            RecordError::Checksum { expected, found } => {
+                write!(f, "bad checksum 0x{:02x} (expected 0x{:02x})", found, expected)
+            },
+            RecordError::UnknownType(t) => write!(f, "unknown record type {}", t),
+            RecordError::Overlap(addr) => {
+                write!(f, "data at 0x{:08x} overlaps an earlier record", addr)
+            },
+            RecordError::BadCount => write!(f, "record count doesn't match"),
+            RecordError::MissingEof => write!(f, "missing end-of-file record"),
+        }
+    }
+}
+impl std::error::Error for ParseError {}
+
+/// Decode a line of hex digits into bytes.
+pub(crate) fn hex_bytes(s: &str) -> Result<Vec<u8>, RecordError> {
+    if s.len() % 2 == 1 || !s.is_ascii() {
+        return Err(RecordError::BadHex);
+    }
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| RecordError::BadHex))
+        .collect()
+}
+
+
+/// A sparse image.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Image {
+    /// Chunks by start address. Adjacent chunks are always merged.
+    chunks: BTreeMap<u32, Vec<u8>>,
+    /// Entry point, if the file specifies one.
+    pub entry: Option<u32>,
+}
+
+impl Image {
+    pub fn new() -> Self {
+        Image::default()
+    }
+
+    /// Create an image from a flat binary loaded at some address.
+    pub fn from_bin(base: u32, buf: &[u8]) -> Self {
+        let mut res = Image::new();
+        res.write(base, buf).unwrap();
+        res
+    }
+
+    /// Load an Intel HEX or S-record file, depending on the first record.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        match text.trim_start().chars().next() {
+            Some('S') => crate::srec::parse(text),
+            _ => crate::ihex::parse(text),
+        }
+    }
+
+    /// Add data at some address. Returns the first overlapping address if
+    /// the data overlaps an existing chunk.
+    pub fn write(&mut self, addr: u32, data: &[u8]) -> Result<(), u32> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let end = addr as u64 + data.len() as u64;
+        if let Some((start, prev)) = self.chunks.range(..=addr).next_back() {
+            if (*start as u64 + prev.len() as u64) > addr as u64 {
+                return Err(addr);
+            }
+        }
+        if let Some((start, _)) = self.chunks.range(addr..).next() {
+            if (*start as u64) < end {
+                return Err(*start);
+            }
+        }
+
+        // Merge with the previous and next chunks if they're adjacent
+        let prev = self.chunks.range(..addr).next_back()
+            .filter(|(start, prev)| **start as u64 + prev.len() as u64 == addr as u64)
+            .map(|(start, _)| *start);
+        let start = prev.unwrap_or(addr);
+        let mut chunk = match prev {
+            Some(start) => self.chunks.remove(&start).unwrap(),
+            None => Vec::new(),
+        };
+        chunk.extend_from_slice(data);
+        if end <= u32::MAX as u64 {
+            if let Some(next) = self.chunks.remove(&(end as u32)) {
+                chunk.extend_from_slice(&next);
+            }
+        }
+        self.chunks.insert(start, chunk);
+        Ok(())
+    }
+
+    /// Returns the contiguous chunks in this image as `(addr, data)`, in
+    /// address order.
+    pub fn chunks(&self) -> impl Iterator<Item = (u32, &[u8])> {
+        self.chunks.iter().map(|(addr, data)| (*addr, data.as_slice()))
+    }
+
+    /// Returns the chunk containing some address, as `(start, data)`.
+    pub fn chunk_at(&self, addr: u32) -> Option<(u32, &[u8])> {
+        let (start, data) = self.chunks.range(..=addr).next_back()?;
+        if ((addr - start) as usize) < data.len() {
+            Some((*start, data.as_slice()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the bytes from some address until the end of its chunk.
+    pub fn bytes_at(&self, addr: u32) -> Option<&[u8]> {
+        let (start, data) = self.chunk_at(addr)?;
+        Some(&data[(addr - start) as usize..])
+    }
+
+    /// Number of bytes of data in the image.
+    pub fn len(&self) -> usize {
+        self.chunks.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Flatten the image into a single buffer, filling gaps with some value.
+    /// Returns the base address and the buffer.
+    pub fn flatten(&self, fill: u8) -> (u32, Vec<u8>) {
+        let base = match self.chunks.keys().next() {
+            Some(base) => *base,
+            None => return (0, Vec::new()),
+        };
+        let mut res = Vec::new();
+        for (addr, data) in self.chunks.iter() {
+            res.resize((addr - base) as usize, fill);
+            res.extend_from_slice(data);
+        }
+        (base, res)
+    }
+
+    /// Disassemble each chunk with a linear sweep starting in some mode.
+    pub fn disassemble(&self, order: ByteOrder, isa: Isa, d: &mut Disassembler)
+        -> Vec<Line>
+    {
+        let mut res = Vec::new();
+        for (addr, data) in self.chunks.iter() {
+            let mut sweep = LinearSweep::new(data, *addr, order, isa);
+            if let Some(entry) = self.entry {
+                sweep.add_target(entry);
+            }
+            res.extend(sweep.disassemble(d));
+        }
+        res
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_chunks() {
+        let mut img = Image::new();
+        img.write(0x10, &[1, 2]).unwrap();
+        img.write(0x14, &[5, 6]).unwrap();
+        img.write(0x20, &[0xaa]).unwrap();
+        img.write(0x12, &[3, 4]).unwrap();
+        assert_eq!(img.chunks().collect::<Vec<(u32, &[u8])>>(), vec![
+            (0x10, &[1, 2, 3, 4, 5, 6][..]),
+            (0x20, &[0xaa][..]),
+        ]);
+        assert_eq!(img.write(0x15, &[0]), Err(0x15));
+        assert_eq!(img.write(0x0f, &[0, 0]), Err(0x10));
+        assert_eq!(img.bytes_at(0x13), Some(&[4u8, 5, 6][..]));
+        assert_eq!(img.bytes_at(0x16), None);
+        assert_eq!(img.len(), 7);
+        assert_eq!(img.flatten(0xff), (0x10, vec![
+            1, 2, 3, 4, 5, 6, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xaa,
+        ]));
+    }
+}
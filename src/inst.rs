@@ -4,7 +4,7 @@ use crate::fields::*;
 use armbf_prim::*;
 
 /// The set of supported THUMB instructions.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ThumbInst {
     None,
 
@@ -206,6 +206,24 @@ impl ThumbInst {
 
 
 impl ThumbInst {
+    /// Returns the should-be-zero fields of this instruction as a
+    /// `(mask, value)` pair. Encodings where `x & mask != value` are
+    /// UNPREDICTABLE.
+    pub fn should_be(&self) -> (u16, u16) {
+        match self {
+            ThumbInst::Bx | ThumbInst::BlxReg => (0b111, 0),
+            _ => (0, 0),
+        }
+    }
+
+    /// Decode a THUMB instruction, treating encodings with the wrong
+    /// should-be-zero fields as undefined.
+    pub fn decode_strict(x: u16) -> ThumbInst {
+        let inst = ThumbInst::decode(x);
+        let (mask, value) = inst.should_be();
+        if x & mask == value { inst } else { ThumbInst::None }
+    }
+
     /// Decode a THUMB instruction.
     pub fn decode(x: u16) -> ThumbInst {
         match (x & 0b1110_0000_0000_0000) >> 13 {
@@ -272,7 +290,7 @@ impl ThumbInst {
 
 
 /// The set of supported ARMv5 instructions.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ArmInst {
     None,
 
@@ -312,10 +330,13 @@ pub enum ArmInst {
     Bkpt, Swi, Swp, Swpb,
 
     // Coprocessor (register transfer)
-    Mrc, Mcr,
+    Mrc, Mcr, Mrrc, Mcrr,
 
-    // Coprocessor
-    CoprocLs, CoprocDp,
+    // Coprocessor (load/store, data processing)
+    Ldc, Stc, Cdp,
+
+    // Coprocessor (unconditional)
+    Mrc2, Mcr2, Ldc2, Stc2, Cdp2,
 
     // Data processing (rotate immediate)
     AndRotImm, EorRotImm, SubRotImm, RsbRotImm,
@@ -542,33 +563,590 @@ impl ArmInst {
                     false => ArmInst::B,
                 }
             },
-            0b110 => ArmInst::CoprocLs,
+            0b110 => {
+                let uncond = get_cond!(x) == 0b1111;
+                match (x >> 21) & 0b1111 {
+                    // MCRR2/MRRC2 are ARMv6
+                    0b0010 if uncond => ArmInst::None,
+                    0b0010 if get_l!(x) => ArmInst::Mrrc,
+                    0b0010 => ArmInst::Mcrr,
+                    // Unindexed, but without U set
+                    0b0000 => ArmInst::None,
+                    _ => match (get_l!(x), uncond) {
+                        (false, false) => ArmInst::Stc,
+                        (false, true) => ArmInst::Stc2,
+                        (true, false) => ArmInst::Ldc,
+                        (true, true) => ArmInst::Ldc2,
+                    },
+                }
+            },
             0b111 => { 
+                let uncond = get_cond!(x) == 0b1111;
                 if bit!(x, 24) {
-                    if get_cond!(x) != 0b1111 { return ArmInst::Swi; }
+                    if !uncond { return ArmInst::Swi; }
                     return ArmInst::None;
                 }
                 if bit!(x, 4) { 
-                    if bit!(x, 20) { return ArmInst::Mrc; } 
-                    else { 
-                        return ArmInst::Mcr; 
-                    }
+                    return match (get_l!(x), uncond) {
+                        (false, false) => ArmInst::Mcr,
+                        (false, true) => ArmInst::Mcr2,
+                        (true, false) => ArmInst::Mrc,
+                        (true, true) => ArmInst::Mrc2,
+                    };
                 }
-                ArmInst::CoprocDp
+                if uncond { ArmInst::Cdp2 } else { ArmInst::Cdp }
             },
             _ => unreachable!(),
         }
     }
 }
 
+/// Should-be-one and should-be-zero fields of ARM instructions.
+impl ArmInst {
+    /// Returns the should-be-one/should-be-zero fields of this instruction
+    /// as a `(mask, value)` pair. Encodings where `x & mask != value` are
+    /// UNPREDICTABLE.
+    ///
+    /// [ArmInst::decode] ignores these fields (and so does the lookup table
+    /// index).
+    pub fn should_be(&self) -> (u32, u32) {
+        use ArmInst::*;
+        match self {
+            Mrs => (0x000f_0fff, 0x000f_0000),
+            MsrReg => (0x0000_fff0, 0x0000_f000),
+            MsrImm => (0x0000_f000, 0x0000_f000),
+            Bx | BlxReg => (0x000f_ff00, 0x000f_ff00),
+            Clz => (0x000f_0f00, 0x000f_0f00),
+            Qadd | Qsub | QdAdd | QdSub => (0x0000_0f00, 0),
+            SmulwY | SmulXy | Mul => (0x0000_f000, 0),
+            Swp | Swpb => (0x0000_0f00, 0),
+            StrhReg | LdrhReg | LdrsbReg | LdrshReg | StrdReg | LdrdReg => {
+                (0x0000_0f00, 0)
+            },
+            MovRotImm | MvnRotImm | MovShiftImm | MvnShiftImm |
+            MovShiftReg | MvnShiftReg => (0x000f_0000, 0),
+            _ if self.is_compare() => (0x0000_f000, 0),
+            _ => (0, 0),
+        }
+    }
+
+    /// Decode an ARM instruction, treating encodings with the wrong
+    /// should-be-one/should-be-zero fields as undefined.
+    pub fn decode_strict(x: u32) -> Self {
+        let inst = ArmInst::decode(x);
+        let (mask, value) = inst.should_be();
+        if x & mask == value { inst } else { ArmInst::None }
+    }
+}
+
+/// Classification of ARM instructions.
+impl ArmInst {
+    /// Returns true for data-processing instructions.
+    pub fn is_dp(&self) -> bool {
+        use ArmInst::*;
+        matches!(self,
+            AndRotImm | EorRotImm | SubRotImm | RsbRotImm |
+            AddRotImm | AdcRotImm | SbcRotImm | RscRotImm |
+            TstRotImm | TeqRotImm | CmpRotImm | CmnRotImm |
+            OrrRotImm | MovRotImm | BicRotImm | MvnRotImm |
+            AndShiftImm | EorShiftImm | SubShiftImm | RsbShiftImm |
+            AddShiftImm | AdcShiftImm | SbcShiftImm | RscShiftImm |
+            TstShiftImm | TeqShiftImm | CmpShiftImm | CmnShiftImm |
+            OrrShiftImm | MovShiftImm | BicShiftImm | MvnShiftImm |
+            AndShiftReg | EorShiftReg | SubShiftReg | RsbShiftReg |
+            AddShiftReg | AdcShiftReg | SbcShiftReg | RscShiftReg |
+            TstShiftReg | TeqShiftReg | CmpShiftReg | CmnShiftReg |
+            OrrShiftReg | MovShiftReg | BicShiftReg | MvnShiftReg
+        )
+    }
+
+    /// Returns true for data-processing instructions which only set flags
+    /// (and don't write a destination register).
+    pub fn is_compare(&self) -> bool {
+        use ArmInst::*;
+        matches!(self,
+            TstRotImm | TeqRotImm | CmpRotImm | CmnRotImm |
+            TstShiftImm | TeqShiftImm | CmpShiftImm | CmnShiftImm |
+            TstShiftReg | TeqShiftReg | CmpShiftReg | CmnShiftReg
+        )
+    }
+}
+
+
+
+/// The set of supported VFPv2 instructions.
+///
+/// These live in the coprocessor space (cp10 for single-precision and cp11
+/// for double-precision operations) and are told apart by fields outside of
+/// the ARM lookup table index, so they're decoded separately from
+/// [ArmInst].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VfpInst {
+    None,
+
+    // Data processing
+    Fmacs, Fmacd, Fnmacs, Fnmacd, Fmscs, Fmscd, Fnmscs, Fnmscd,
+    Fmuls, Fmuld, Fnmuls, Fnmuld, Fadds, Faddd, Fsubs, Fsubd,
+    Fdivs, Fdivd,
+
+    // Data processing (extension)
+    Fcpys, Fcpyd, Fabss, Fabsd, Fnegs, Fnegd, Fsqrts, Fsqrtd,
+    Fcmps, Fcmpd, Fcmpes, Fcmped, Fcmpzs, Fcmpzd, Fcmpezs, Fcmpezd,
+
+    // Conversions
+    Fcvtds, Fcvtsd, Fuitos, Fuitod, Fsitos, Fsitod,
+    Ftouis, Ftouid, Ftouizs, Ftouizd, Ftosis, Ftosid, Ftosizs, Ftosizd,
+
+    // Load/store
+    Flds, Fldd, Fsts, Fstd,
+
+    // Load/store multiple
+    Fldmias, Fldmiad, Fldmiax, Fldmdbs, Fldmdbd, Fldmdbx,
+    Fstmias, Fstmiad, Fstmiax, Fstmdbs, Fstmdbd, Fstmdbx,
+
+    // Register transfer
+    Fmsr, Fmrs, Fmdlr, Fmrdl, Fmdhr, Fmrdh, Fmxr, Fmrx, Fmstat,
+
+    // Register transfer (two registers)
+    Fmsrr, Fmrrs, Fmdrr, Fmrrd,
+}
+
+/// Decoding VFP instructions.
+impl VfpInst {
+    /// Returns true for instructions in the space used by the VFP (the
+    /// conditional coprocessor instructions on cp10 and cp11).
+    pub fn is_vfp(x: u32) -> bool {
+        let group = get_group!(x);
+        get_cond!(x) != 0b1111 && (get_cp_num!(x) & 0b1110) == 0b1010
+            && (group == 0b110 || (group == 0b111 && !bit!(x, 24)))
+    }
+
+    /// Decode a VFP instruction. Anything outside of the VFP space, or
+    /// undefined/unpredictable within it, is [VfpInst::None].
+    pub fn decode(x: u32) -> VfpInst {
+        if !VfpInst::is_vfp(x) {
+            return VfpInst::None;
+        }
+        let double = get_vfp_double!(x);
+        match (get_group!(x), bit!(x, 4)) {
+            (0b110, _) => VfpInst::decode_ls(x, double),
+            (_, false) => VfpInst::decode_dp(x, double),
+            (_, true) => VfpInst::decode_rt(x, double),
+        }
+    }
+
+    /// Data-processing instructions (in the CDP space).
+    fn decode_dp(x: u32, double: bool) -> VfpInst {
+        use VfpInst::*;
+        let pick = |s, d| if double { d } else { s };
+        let inst = match get_vfp_opcd!(x) {
+            0b0000 => pick(Fmacs, Fmacd),
+            0b0001 => pick(Fnmacs, Fnmacd),
+            0b0010 => pick(Fmscs, Fmscd),
+            0b0011 => pick(Fnmscs, Fnmscd),
+            0b0100 => pick(Fmuls, Fmuld),
+            0b0101 => pick(Fnmuls, Fnmuld),
+            0b0110 => pick(Fadds, Faddd),
+            0b0111 => pick(Fsubs, Fsubd),
+            0b1000 => pick(Fdivs, Fdivd),
+            0b1111 => return VfpInst::decode_ext(x, double),
+            _ => return None,
+        };
+        // The D, N and M bits extend single-precision registers, and must
+        // be clear for double-precision registers.
+        if double && (bit!(x, 22) || bit!(x, 7) || bit!(x, 5)) {
+            return None;
+        }
+        inst
+    }
+
+    /// Extension instructions (where Fn and N are the opcode).
+    fn decode_ext(x: u32, double: bool) -> VfpInst {
+        use VfpInst::*;
+        let pick = |s, d| if double { d } else { s };
+        let inst = match get_vfp_ext_opcd!(x) {
+            0b00000 => pick(Fcpys, Fcpyd),
+            0b00001 => pick(Fabss, Fabsd),
+            0b00010 => pick(Fnegs, Fnegd),
+            0b00011 => pick(Fsqrts, Fsqrtd),
+            0b01000 => pick(Fcmps, Fcmpd),
+            0b01001 => pick(Fcmpes, Fcmped),
+            0b01010 => pick(Fcmpzs, Fcmpzd),
+            0b01011 => pick(Fcmpezs, Fcmpezd),
+            0b01111 => pick(Fcvtds, Fcvtsd),
+            0b10000 => pick(Fuitos, Fuitod),
+            0b10001 => pick(Fsitos, Fsitod),
+            0b11000 => pick(Ftouis, Ftouid),
+            0b11001 => pick(Ftouizs, Ftouizd),
+            0b11010 => pick(Ftosis, Ftosid),
+            0b11011 => pick(Ftosizs, Ftosizd),
+            _ => return None,
+        };
+        // Compares with zero have no Fm
+        let zero = matches!(inst, Fcmpzs | Fcmpzd | Fcmpezs | Fcmpezd);
+        if zero && (x & 0b10_1111) != 0 {
+            return None;
+        }
+        if (inst.d_double() && bit!(x, 22)) || (inst.m_double() && bit!(x, 5)) {
+            return None;
+        }
+        inst
+    }
+
+    /// Single-register transfers (in the MCR/MRC space).
+    fn decode_rt(x: u32, double: bool) -> VfpInst {
+        use VfpInst::*;
+        // Bits 6-5 and 3-0 should be zero
+        if (x & 0b110_1111) != 0 {
+            return None;
+        }
+        let (l, n) = (get_l!(x), bit!(x, 7));
+        match (get_cp_opcd1_rt!(x), double) {
+            (0b000, false) => if l { Fmrs } else { Fmsr },
+            (0b000, true) if !n => if l { Fmrdl } else { Fmdlr },
+            (0b001, true) if !n => if l { Fmrdh } else { Fmdhr },
+            (0b111, false) if !n && VfpSysRegister::is_valid(get_crn!(x)) => {
+                match (l, get_crn!(x), get_rd!(x)) {
+                    (true, 0b0001, 15) => Fmstat,
+                    (true, _, _) => Fmrx,
+                    (false, _, _) => Fmxr,
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// Load/store instructions and two-register transfers (in the LDC/STC
+    /// and MCRR/MRRC space).
+    fn decode_ls(x: u32, double: bool) -> VfpInst {
+        use VfpInst::*;
+        let (l, d, imm8) = (get_l!(x), bit!(x, 22), get_imm8!(x));
+        match (get_p!(x), get_u!(x), get_w!(x)) {
+            // Two-register transfers; bits 7-6 should be zero and bit 4 set
+            (false, false, false) => match (d, double, x & 0b1101_0000) {
+                (true, false, 0b0001_0000) if get_vfp_sm!(x) != 31 => {
+                    if l { Fmrrs } else { Fmsrr }
+                },
+                (true, true, 0b0001_0000) if !bit!(x, 5) => {
+                    if l { Fmrrd } else { Fmdrr }
+                },
+                _ => None,
+            },
+            (true, _, false) => match (l, double) {
+                (_, true) if d => None,
+                (true, false) => Flds,
+                (true, true) => Fldd,
+                (false, false) => Fsts,
+                (false, true) => Fstd,
+            },
+            (false, true, _) | (true, false, true) => {
+                // Double-precision lists count words, and an odd count
+                // selects the FLDMX/FSTMX format.
+                let (first, count, regs) = match double {
+                    false => (get_vfp_sd!(x), imm8, 32),
+                    true => (get_vfp_dd!(x), imm8 / 2, 16),
+                };
+                if count == 0 || first + count > regs || (double && d) {
+                    return None;
+                }
+                let fmt = match (double, imm8 & 1) {
+                    (false, _) => 0,
+                    (true, 0) => 1,
+                    (true, _) => 2,
+                };
+                let insts = match (l, get_p!(x)) {
+                    (true, false) => [Fldmias, Fldmiad, Fldmiax],
+                    (true, true) => [Fldmdbs, Fldmdbd, Fldmdbx],
+                    (false, false) => [Fstmias, Fstmiad, Fstmiax],
+                    (false, true) => [Fstmdbs, Fstmdbd, Fstmdbx],
+                };
+                insts[fmt]
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Classification of VFP instructions.
+impl VfpInst {
+    /// Returns true for instructions on cp11 (which mostly operate on
+    /// double-precision registers).
+    pub fn is_double(&self) -> bool {
+        use VfpInst::*;
+        matches!(self,
+            Fmacd | Fnmacd | Fmscd | Fnmscd | Fmuld | Fnmuld | Faddd |
+            Fsubd | Fdivd | Fcpyd | Fabsd | Fnegd | Fsqrtd | Fcmpd |
+            Fcmped | Fcmpzd | Fcmpezd | Fcvtsd | Fuitod | Fsitod | Ftouid |
+            Ftouizd | Ftosid | Ftosizd | Fldd | Fstd | Fldmiad | Fldmiax |
+            Fldmdbd | Fldmdbx | Fstmiad | Fstmiax | Fstmdbd | Fstmdbx |
+            Fmdlr | Fmrdl | Fmdhr | Fmrdh | Fmdrr | Fmrrd
+        )
+    }
+
+    /// Returns true if the Fd register of a data-processing instruction is
+    /// double-precision.
+    pub fn d_double(&self) -> bool {
+        use VfpInst::*;
+        match self {
+            Fcvtds => true,
+            Fcvtsd | Ftouid | Ftouizd | Ftosid | Ftosizd => false,
+            _ => self.is_double(),
+        }
+    }
+
+    /// Returns true if the Fm register of a data-processing instruction is
+    /// double-precision.
+    pub fn m_double(&self) -> bool {
+        use VfpInst::*;
+        match self {
+            Fuitod | Fsitod => false,
+            _ => self.is_double(),
+        }
+    }
+
+    /// Returns true for compares, which only write the FPSCR flags.
+    pub fn is_compare(&self) -> bool {
+        use VfpInst::*;
+        matches!(self,
+            Fcmps | Fcmpd | Fcmpes | Fcmped | Fcmpzs | Fcmpzd | Fcmpezs |
+            Fcmpezd
+        )
+    }
+}
+
+/// The set of FPA instructions.
+///
+/// The FPA (and the floating-point emulators which replaced it) uses cp1,
+/// and cp2 for LFM/SFM. Like [VfpInst], these are told apart by fields
+/// outside of the ARM lookup table index. The variants are named after
+/// their mnemonics, without the precision and rounding suffixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpaInst {
+    None,
+
+    // Load/store
+    Ldf, Stf, Lfm, Sfm,
+
+    // Data processing (dyadic)
+    Adf, Muf, Suf, Rsf, Dvf, Rdf, Pow, Rpw, Rmf, Fml, Fdv, Frd, Pol,
+
+    // Data processing (monadic)
+    Mvf, Mnf, Abs, Rnd, Sqt, Log, Lgn, Exp, Sin, Cos, Tan, Asn, Acs, Atn,
+    Urd, Nrm,
+
+    // Register transfer
+    Flt, Fix, Wfs, Rfs, Wfc, Rfc,
+
+    // Compare
+    Cmf, Cnf, Cmfe, Cnfe,
+}
+
+/// Decoding FPA instructions.
+impl FpaInst {
+    /// Returns true for instructions in the space used by the FPA (the
+    /// conditional coprocessor instructions on cp1, and loads and stores on
+    /// cp2). The MCRR/MRRC space isn't used.
+    pub fn is_fpa(x: u32) -> bool {
+        let cp = get_cp_num!(x);
+        get_cond!(x) != 0b1111 && match get_group!(x) {
+            0b110 => (cp == 1 || cp == 2) && (get_p!(x) || get_u!(x) || get_w!(x)),
+            0b111 => cp == 1 && !bit!(x, 24),
+            _ => false,
+        }
+    }
+
+    /// Decode an FPA instruction. Anything outside of the FPA space, or
+    /// undefined within it, is [FpaInst::None].
+    pub fn decode(x: u32) -> FpaInst {
+        use FpaInst::*;
+        if !FpaInst::is_fpa(x) {
+            return None;
+        }
+        match (get_group!(x), bit!(x, 4)) {
+            (0b110, _) => match (get_cp_num!(x), get_l!(x)) {
+                (1, true) => Ldf,
+                (1, false) => Stf,
+                (_, true) => Lfm,
+                (_, false) => Sfm,
+            },
+            (_, false) => FpaInst::decode_dp(x),
+            (_, true) => FpaInst::decode_rt(x),
+        }
+    }
+
+    /// Data-processing instructions (in the CDP space).
+    fn decode_dp(x: u32) -> FpaInst {
+        use FpaInst::*;
+        const DYADIC: [FpaInst; 16] = [
+            Adf, Muf, Suf, Rsf, Dvf, Rdf, Pow, Rpw,
+            Rmf, Fml, Fdv, Frd, Pol, None, None, None,
+        ];
+        const MONADIC: [FpaInst; 16] = [
+            Mvf, Mnf, Abs, Rnd, Sqt, Log, Lgn, Exp,
+            Sin, Cos, Tan, Asn, Acs, Atn, Urd, Nrm,
+        ];
+        // Packed decimal can only be loaded and stored
+        if get_fpa_prec!(x) == 0b11 {
+            return None;
+        }
+        match get_fpa_monadic!(x) {
+            true => MONADIC[get_cp_opcd1!(x) as usize],
+            false => DYADIC[get_cp_opcd1!(x) as usize],
+        }
+    }
+
+    /// Register transfers and compares (in the MCR/MRC space).
+    fn decode_rt(x: u32) -> FpaInst {
+        use FpaInst::*;
+        match get_cp_opcd1!(x) {
+            // Bits 3-0 should be zero
+            0b0000 if get_fpa_prec!(x) != 0b11 && (x & 0xf) == 0 => Flt,
+            // Bits 19-16, 7 and 3 should be zero
+            0b0001 if (x & 0x000f_0088) == 0 => Fix,
+            // Bits 19-16 and 7-5 should be zero, and bits 3-0 too
+            op @ 0b0010..=0b0101 if (x & 0x000f_00ef) == 0 => {
+                [Wfs, Rfs, Wfc, Rfc][op as usize - 0b0010]
+            },
+            // Bit 19 and 7-5 should be zero, and Rd should be 15
+            op @ (0b1001 | 0b1011 | 0b1101 | 0b1111) if (x & 0x0008_f0e0) == 0xf000 => {
+                [Cmf, Cnf, Cmfe, Cnfe][(op as usize - 0b1001) / 2]
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Classification of FPA instructions.
+impl FpaInst {
+    /// Returns true for data-processing instructions with two operands.
+    pub fn is_dyadic(&self) -> bool {
+        use FpaInst::*;
+        matches!(self,
+            Adf | Muf | Suf | Rsf | Dvf | Rdf | Pow | Rpw | Rmf | Fml | Fdv |
+            Frd | Pol
+        )
+    }
+
+    /// Returns true for data-processing instructions with one operand.
+    pub fn is_monadic(&self) -> bool {
+        use FpaInst::*;
+        matches!(self,
+            Mvf | Mnf | Abs | Rnd | Sqt | Log | Lgn | Exp | Sin | Cos | Tan |
+            Asn | Acs | Atn | Urd | Nrm
+        )
+    }
+}
 
 #[cfg(test)]
 mod test {
     use crate::inst::*;
+    use crate::lut::*;
+
+    /// Every halfword decodes, and the result only depends on bits 15-5 
+    /// (which is what the Thumb lookup table indexes on).
     #[test]
     fn thumb_decode() {
-        for i in 0..0x800u16 {
-            println!("{:04x} {:?}", i << 5, ThumbInst::decode(i << 5));
+        let lut = make_thumb_lut::<ThumbInst>();
+        let mut undef = 0;
+        for x in 0..=0xffffu16 {
+            let inst = ThumbInst::decode(x);
+            assert_eq!(inst, *lut.lookup(x), "{:04x}", x);
+            if inst == ThumbInst::None {
+                undef += 1;
+            }
+        }
+        // Ten unallocated miscellaneous sub-opcodes, plus 0xdexx.
+        assert_eq!(undef, 11 * 0x100);
+    }
+
+    /// Every class of ARM words decodes without panicking.
+    ///
+    /// The decoder should only read the bits in 0xfff000f0. We enumerate all
+    /// 2^16 classes over those bits, but only try a few values for the other
+    /// bits in each class (none, all, and each bit by itself), checking that
+    /// they agree with the decoding of the class, which points at the
+    /// offending bit more directly than `arm_decode_exhaustive`.
+    #[test]
+    fn arm_decode() {
+        const CLASS: u32 = 0xfff0_00f0;
+        let free: Vec<u32> = (0..32).map(|i| 1 << i)
+            .filter(|b| CLASS & b == 0)
+            .collect();
+        for idx in 0..=0xffffu32 {
+            let base = (idx & 0xfff0) << 16 | (idx & 0xf) << 4;
+            let inst = ArmInst::decode(base);
+            assert_eq!(ArmInst::decode(base | !CLASS), inst, "{:08x}", base);
+            for b in free.iter() {
+                assert_eq!(ArmInst::decode(base | b), inst, 
+                    "{:08x}", base | b);
+            }
+        }
+    }
+
+    /// Every one of the 2^32 ARM words decodes without panicking, and agrees
+    /// with the decoding of its class over the bits in 0xfff000f0. The 2^16
+    /// classes are shared out between threads.
+    #[test]
+    fn arm_decode_exhaustive() {
+        const CLASS: u32 = 0xfff0_00f0;
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get() as u32).unwrap_or(1);
+        let workers: Vec<_> = (0..threads).map(|t| std::thread::spawn(move || {
+            for idx in (t..=0xffff).step_by(threads as usize) {
+                let base = (idx & 0xfff0) << 16 | (idx & 0xf) << 4;
+                let inst = ArmInst::decode(base);
+                for rest in 0..=0xffffu32 {
+                    let x = base | (rest & 0xfff0) << 4 | (rest & 0xf);
+                    debug_assert_eq!(x & CLASS, base);
+                    assert_eq!(ArmInst::decode(x), inst, "{:08x}", x);
+                }
+            }
+        })).collect();
+        for w in workers {
+            w.join().unwrap();
+        }
+    }
+
+    /// Only conditional coprocessor instructions on cp10/cp11 are VFP
+    /// instructions.
+    #[test]
+    fn vfp_space() {
+        // vadd.f32 s0, s1, s2
+        assert_eq!(VfpInst::decode(0xee300a81), VfpInst::Fadds);
+        assert_eq!(ArmInst::decode(0xee300a81), ArmInst::Cdp);
+        // cdp2 p10, ..., mcr p15, ..., svc #0xa00
+        for x in [0xfe300a81, 0xee070f15, 0xef000a00] {
+            assert!(!VfpInst::is_vfp(x));
+            assert_eq!(VfpInst::decode(x), VfpInst::None);
+        }
+        // vldr/vstr with a double-precision register take no D bit
+        assert_eq!(VfpInst::decode(0xedd00a01), VfpInst::Flds);
+        assert_eq!(VfpInst::decode(0xedd00b01), VfpInst::None);
+        // Empty or out-of-range register lists
+        assert_eq!(VfpInst::decode(0xec900a00), VfpInst::None);
+        assert_eq!(VfpInst::decode(0xec900b22), VfpInst::None);
+        assert_eq!(VfpInst::decode(0xecd0fa02), VfpInst::None);
+    }
+
+    /// Only conditional loads and stores on cp1/cp2, and other coprocessor
+    /// instructions on cp1, are FPA instructions.
+    #[test]
+    fn fpa_space() {
+        // ldfd f0, [r1, #8]
+        assert_eq!(FpaInst::decode(0xed918102), FpaInst::Ldf);
+        assert_eq!(ArmInst::decode(0xed918102), ArmInst::Ldc);
+        // mcrr p1, ..., ldc2 p1, ..., mcr p2, ..., cdp p3, ...
+        for x in [0xec410100, 0xfd918102, 0xee000210, 0xee000300] {
+            assert!(!FpaInst::is_fpa(x));
+            assert_eq!(FpaInst::decode(x), FpaInst::None);
         }
+        // Dyadic opcodes 13-15, and data processing in packed decimal
+        assert_eq!(FpaInst::decode(0xeed00100), FpaInst::None);
+        assert_eq!(FpaInst::decode(0xee080180), FpaInst::None);
+        // Register transfers with stray bits set, and compares which
+        // don't name r15
+        assert_eq!(FpaInst::decode(0xee201111), FpaInst::None);
+        assert_eq!(FpaInst::decode(0xee900111), FpaInst::None);
+        assert_eq!(FpaInst::decode(0xee90f111), FpaInst::Cmf);
     }
 }
@@ -7,15 +7,35 @@
 
 #![allow(unused_macros)]
 #![allow(unused_attributes)]
+#![allow(clippy::needless_return)]
+#![allow(clippy::manual_range_patterns)]
 
 //#![feature(trace_macros)]
 //trace_macros!(true);
 
 #[macro_use]
-
 pub mod traits;
 pub mod newtype;
 pub mod fields;
 pub mod inst;
 pub mod lut;
+pub mod disas;
+pub mod stream;
+pub mod sweep;
+pub mod flow;
+pub mod cfg;
+pub mod funcs;
+pub mod literal;
+pub mod sysreg;
+pub mod elf;
+pub mod reloc;
+pub mod image;
+pub mod ihex;
+pub mod srec;
+pub mod pe;
+pub mod memory;
+pub mod cpu;
+
+#[cfg(test)]
+mod golden;
 
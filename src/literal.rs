@@ -0,0 +1,290 @@
+//! Literal pools and PC-relative loads.
+//!
+//! Constants which can't be encoded as immediates are loaded from a "literal
+//! pool" placed somewhere near the code, i.e. with `ldr rd, [pc, #imm]`.
+//! The address is computed from the PC with the usual offsets:
+//!
+//! - ARM: `PC + 8 +/- imm12`
+//! - Thumb: `Align(PC + 4, 4) + imm8 * 4`
+//!
+//! [LiteralPools] finds these loads in a listing, reads the constants, and
+//! marks the pool entries as data so that a [LinearSweep] won't decode them
+//! as instructions.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::disas::{Disassembler, Isa};
+use crate::inst::*;
+use crate::memory::{MemoryImage, Unmapped};
+use crate::newtype::*;
+use crate::traits::*;
+use crate::stream::*;
+use crate::sweep::*;
+
+/// Returns the address loaded from by an ARM PC-relative load.
+pub fn arm_literal_addr(addr: u32, x: u32) -> Option<u32> {
+    if ArmInst::decode(x) != ArmInst::LdrImm {
+        return None;
+    }
+    let op = LsImmBf(x);
+    if op.rn() != 15 || !op.p() || op.w() {
+        return None;
+    }
+    let pc = addr.wrapping_add(8);
+    Some(if op.u() {
+        pc.wrapping_add(op.imm12())
+    } else {
+        pc.wrapping_sub(op.imm12())
+    })
+}
+
+/// Returns the address loaded from by a Thumb PC-relative load.
+pub fn thumb_literal_addr(addr: u32, x: u16) -> Option<u32> {
+    if ThumbInst::decode(x) != ThumbInst::LdrLit {
+        return None;
+    }
+    let op = ThumbLdrLitBf(x);
+    let pc = addr.wrapping_add(4) & !3;
+    Some(pc.wrapping_add(op.imm8() as u32 * 4))
+}
+
+
+/// The literal pools in some image.
+pub struct LiteralPools<'a> {
+    buf: &'a [u8],
+    base: u32,
+    order: ByteOrder,
+    /// Map from the address of a load to the address of its literal.
+    pub loads: BTreeMap<u32, u32>,
+    /// Map from the address of a literal to its value.
+    pub values: BTreeMap<u32, u32>,
+    /// Literals currently marked as data in the sweep.
+    marked: BTreeSet<u32>,
+    symbols: BTreeMap<u32, String>,
+}
+
+impl<'a> LiteralPools<'a> {
+    pub fn new(buf: &'a [u8], base: u32, order: ByteOrder) -> Self {
+        LiteralPools {
+            buf, base, order,
+            loads: BTreeMap::new(),
+            values: BTreeMap::new(),
+            marked: BTreeSet::new(),
+            symbols: BTreeMap::new(),
+        }
+    }
+
+    /// Create the literal pools over the segment of a memory image
+    /// containing some address.
+    pub fn from_memory(mem: &'a MemoryImage, addr: u32) -> Result<Self, Unmapped> {
+        let (seg, buf) = mem.segment_contents(addr)?;
+        Ok(LiteralPools::new(buf, seg.base, seg.order))
+    }
+
+    /// Add a symbol, used to annotate constants which point to it.
+    pub fn add_symbol(&mut self, addr: u32, name: &str) {
+        self.symbols.insert(addr, name.to_string());
+    }
+
+    /// Returns the bytes from some address until the end of the image.
+    fn bytes_at(&self, addr: u32) -> Option<&'a [u8]> {
+        let off = addr.checked_sub(self.base)? as usize;
+        self.buf.get(off..)
+    }
+
+    /// Read a data word (which is big-endian for both BE-8 and BE-32).
+    fn read_u32(&self, addr: u32) -> Option<u32> {
+        let b = self.bytes_at(addr)?.get(..4)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Some(match self.order {
+            ByteOrder::Le => u32::from_le_bytes(b),
+            ByteOrder::Be8 | ByteOrder::Be32 => u32::from_be_bytes(b),
+        })
+    }
+
+    /// Returns a NUL-terminated string of printable characters at some
+    /// address (if there's one at least 4 characters long).
+    pub fn string_at(&self, addr: u32) -> Option<String> {
+        let bytes = self.bytes_at(addr)?;
+        let len = bytes.iter().position(|b| *b == 0)?;
+        let s = &bytes[..len];
+        if len < 4 || !s.iter().all(|b| (0x20..0x7f).contains(b)
+            || b"\t\r\n".contains(b))
+        {
+            return None;
+        }
+        Some(String::from_utf8_lossy(s).to_string())
+    }
+
+    /// Find PC-relative loads in a listing. Returns true if the set of
+    /// literals changed.
+    ///
+    /// The loads and literals found in previous listings are replaced, so a
+    /// literal is dropped once the only load referring to it turns out to be
+    /// data.
+    pub fn scan(&mut self, lines: &[Line]) -> bool {
+        let old = std::mem::take(&mut self.values);
+        self.loads.clear();
+        for line in lines.iter() {
+            let lit = match (line.region, line.bytes.len()) {
+                (Region::Code(Isa::Arm), 4) => {
+                    let (_, x) = ArmStream::new(&line.bytes, line.addr,
+                        self.order).next().unwrap().unwrap();
+                    arm_literal_addr(line.addr, x)
+                },
+                (Region::Code(Isa::Thumb), 2) => {
+                    let (_, x) = ThumbStream::new(&line.bytes, line.addr,
+                        self.order).next().unwrap().unwrap();
+                    thumb_literal_addr(line.addr, x)
+                },
+                _ => None,
+            };
+            let (lit, value) = match lit.and_then(|a| Some((a, self.read_u32(a)?))) {
+                Some(x) => x,
+                None => continue,
+            };
+            self.loads.insert(line.addr, lit);
+            self.values.insert(lit, value);
+        }
+        self.values != old
+    }
+
+    /// Mark all known literals as data, and unmark the ones which have been
+    /// dropped since the last call.
+    pub fn mark(&mut self, sweep: &mut LinearSweep) {
+        for addr in self.marked.iter().filter(|a| !self.values.contains_key(a)) {
+            sweep.remove_range(*addr, addr.wrapping_add(4), Region::Data);
+        }
+        for addr in self.values.keys().filter(|a| !self.marked.contains(a)) {
+            sweep.add_range(*addr, addr.wrapping_add(4), Region::Data);
+        }
+        self.marked = self.values.keys().cloned().collect();
+    }
+
+    /// Describe the constant loaded by the instruction at some address.
+    pub fn annotation(&self, addr: u32) -> Option<String> {
+        let value = self.values[self.loads.get(&addr)?];
+        let mut res = format!("=0x{:08x}", value);
+        if let Some(name) = self.symbols.get(&value) {
+            res.push_str(&format!(" <{}>", name));
+        } else if let Some(s) = self.string_at(value) {
+            res.push_str(&format!(" {:?}", s));
+        }
+        Some(res)
+    }
+
+    /// Add a comment with the constant to each PC-relative load.
+    pub fn annotate(&self, lines: &mut [Line]) {
+        for line in lines.iter_mut() {
+            if let Some(note) = self.annotation(line.addr) {
+                line.text.push_str(&format!("\t@ {}", note));
+            }
+        }
+    }
+
+    /// Sweep some image, marking literals as data until the set of literals
+    /// stops changing, and return the annotated listing.
+    pub fn sweep(&mut self, sweep: &mut LinearSweep, d: &mut Disassembler)
+        -> Vec<Line>
+    {
+        let mut seen = Vec::new();
+        let mut lines = sweep.disassemble(d);
+        while self.scan(&lines) {
+            // Unmarking a literal can bring back a load which was dropped
+            // before, so stop if the passes start to cycle
+            if seen.contains(&self.values) {
+                break;
+            }
+            seen.push(self.values.clone());
+            self.mark(sweep);
+            lines = sweep.disassemble(d);
+        }
+        self.annotate(&mut lines);
+        lines
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn literal_addrs() {
+        assert_eq!(arm_literal_addr(0x14, 0xe59f1010), Some(0x2c));
+        assert_eq!(arm_literal_addr(0x14, 0xe51f1004), Some(0x18));
+        assert_eq!(arm_literal_addr(0x14, 0xe5911000), None);
+        assert_eq!(arm_literal_addr(0x14, 0xe5bf1004), None);
+        assert_eq!(thumb_literal_addr(0x2, 0x4801), Some(0x8));
+        assert_eq!(thumb_literal_addr(0x4, 0x4801), Some(0xc));
+        assert_eq!(thumb_literal_addr(0x4, 0x6808), None);
+    }
+
+    #[test]
+    fn corpus_pools() {
+        let buf = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"),
+            "/testsuite/arm_test.bin")).unwrap();
+        let mut d = Disassembler::new();
+        let mut sweep = LinearSweep::new(&buf, 0, ByteOrder::Be32, Isa::Arm);
+        let mut pools = LiteralPools::new(&buf, 0, ByteOrder::Be32);
+        let lines = pools.sweep(&mut sweep, &mut d);
+
+        assert_eq!(pools.values.keys().cloned().collect::<Vec<u32>>(),
+            vec![0x10, 0x28, 0x4c, 0x74, 0x78, 0xac]);
+        let line = |addr| lines.iter().find(|l| l.addr == addr).unwrap();
+        assert_eq!(line(0x10).text, ".word\t0xdeadbeef");
+        assert_eq!(line(0x10).region, Region::Data);
+        assert_eq!(line(0x00).text, "ldr\tr0, [pc, #8]\t@ =0xdeadbeef");
+        assert_eq!(line(0x34).text, "ldr\tr2, [pc, #16]\t@ =0xdeadcafe");
+    }
+
+    #[test]
+    fn stale_literals() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0xe59f0000u32.to_le_bytes()); // 00: ldr r0, [pc, #0]
+        buf.extend_from_slice(&0xe12fff1eu32.to_le_bytes()); // 04: bx lr
+        buf.extend_from_slice(&0xe59f1000u32.to_le_bytes()); // 08: literal
+        buf.extend_from_slice(&0xe12fff1eu32.to_le_bytes()); // 0c: bx lr
+        buf.extend_from_slice(&0xe1a00001u32.to_le_bytes()); // 10: mov r0, r1
+
+        // The literal at 0x08 looks like a load from 0x10 until it's marked
+        // as data, after which 0x10 must go back to being code.
+        let mut d = Disassembler::new();
+        let mut sweep = LinearSweep::new(&buf, 0, ByteOrder::Le, Isa::Arm);
+        let mut pools = LiteralPools::new(&buf, 0, ByteOrder::Le);
+        let lines = pools.sweep(&mut sweep, &mut d);
+
+        assert_eq!(pools.values.keys().cloned().collect::<Vec<u32>>(), vec![0x08]);
+        assert_eq!(lines[2].region, Region::Data);
+        assert_eq!(lines[4].region, Region::Code(Isa::Arm));
+        assert_eq!(lines[4].text, "mov\tr0, r1");
+    }
+
+    #[test]
+    fn strings_and_symbols() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0xe59f0004u32.to_le_bytes()); // 00: ldr r0, [pc, #4]
+        buf.extend_from_slice(&0xe59f1004u32.to_le_bytes()); // 04: ldr r1, [pc, #4]
+        buf.extend_from_slice(&0x4770_4801u32.to_le_bytes()); // 08: ldr r0, [pc, #4]; bx lr
+        buf.extend_from_slice(&0x0000_0014u32.to_le_bytes()); // 0c
+        buf.extend_from_slice(&0x0000_0008u32.to_le_bytes()); // 10
+        buf.extend_from_slice(b"Hello\0\0\0");                // 14
+
+        let mut d = Disassembler::new();
+        let mut sweep = LinearSweep::new(&buf, 0, ByteOrder::Le, Isa::Arm);
+        sweep.add_range(0x08, 0x0c, Region::Code(Isa::Thumb));
+        sweep.add_range(0x14, 0x1c, Region::Data);
+        let mut pools = LiteralPools::new(&buf, 0, ByteOrder::Le);
+        pools.add_symbol(0x8, "thumb_fn");
+        let lines = pools.sweep(&mut sweep, &mut d);
+
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts[..5], [
+            "ldr\tr0, [pc, #4]\t@ =0x00000014 \"Hello\"",
+            "ldr\tr1, [pc, #4]\t@ =0x00000008 <thumb_fn>",
+            "ldr\tr0, [pc, #4]\t@ =0x00000008 <thumb_fn>",
+            "bx\tlr",
+            ".word\t0x00000014",
+        ]);
+    }
+}
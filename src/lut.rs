@@ -1,15 +1,166 @@
 //! Generic implementation of an ARMv5 lookup table.
 
-use std::marker::Copy;
+use std::marker::PhantomData;
+use std::ops::Index;
 use crate::inst::*;
+use crate::newtype::*;
+use armbf_prim::*;
 
-/// An ARMv5 lookup table.
+/// Describes how instructions are mapped onto indexes into some table.
+///
+/// `X` is the type of instruction being indexed (u32 for ARM, u16 for Thumb).
+pub trait LutIndex<X> {
+    /// The number of entries in a table using this index.
+    const LEN: usize;
+    /// Get the table index for some instruction.
+    fn index(x: X) -> usize;
+    /// Get the instruction used to compute the entry at some table index.
+    fn inst(idx: usize) -> X;
+}
+
+/// The default ARM index (bits 27-20 and 7-4).
+pub struct ArmDecodeIndex;
+impl LutIndex<u32> for ArmDecodeIndex {
+    const LEN: usize = 0x1000;
+    #[inline(always)]
+    fn index(x: u32) -> usize { to_dec!(x) as usize }
+    #[inline(always)]
+    fn inst(idx: usize) -> u32 { from_dec!(idx as u32) }
+}
+
+/// The default ARM index, plus an extra bit (bit 12 of the index) which is 
+/// set when the condition field is 0b1111.
+pub struct ArmCondIndex;
+impl LutIndex<u32> for ArmCondIndex {
+    const LEN: usize = 0x2000;
+    #[inline(always)]
+    fn index(x: u32) -> usize { 
+        let uncond = (get_cond!(x) == 0b1111) as u32;
+        ((uncond << 12) | to_dec!(x)) as usize
+    }
+    #[inline(always)]
+    fn inst(idx: usize) -> u32 { 
+        let cond = if (idx & 0x1000) != 0 { 0xf000_0000 } else { 0 };
+        cond | from_dec!((idx & 0x0fff) as u32)
+    }
+}
+
+/// An ARM index made from an arbitrary selection of bits.
+///
+/// The set bits in MASK are packed (in order, from least-significant) into 
+/// the table index. All other bits in the instruction are assumed to be zero 
+/// when computing table entries.
+pub struct ArmBitIndex<const MASK: u32>;
+impl<const MASK: u32> LutIndex<u32> for ArmBitIndex<MASK> {
+    const LEN: usize = 1 << MASK.count_ones();
+    #[inline(always)]
+    fn index(x: u32) -> usize { gather_bits(x, MASK) }
+    fn inst(idx: usize) -> u32 { scatter_bits(idx, MASK) }
+}
+
+/// Pack the bits of x selected by some mask into the low bits of an index.
+#[inline(always)]
+fn gather_bits(x: u32, mask: u32) -> usize {
+    let (mut res, mut pos) = (0, 0);
+    for bit in 0..32 {
+        if bit!(mask, bit) {
+            if bit!(x, bit) { res |= 1 << pos; }
+            pos += 1;
+        }
+    }
+    res
+}
+
+/// Unpack the low bits of an index into the bits selected by some mask.
+fn scatter_bits(idx: usize, mask: u32) -> u32 {
+    let (mut res, mut pos) = (0, 0);
+    for bit in 0..32 {
+        if bit!(mask, bit) {
+            if bit!(idx, pos) { res |= 1 << bit; }
+            pos += 1;
+        }
+    }
+    res
+}
+
+/// The default Thumb index (bits 15-5).
+pub struct ThumbDecodeIndex;
+impl LutIndex<u16> for ThumbDecodeIndex {
+    const LEN: usize = 0x0800;
+    #[inline(always)]
+    fn index(x: u16) -> usize { (x >> 5) as usize }
+    #[inline(always)]
+    fn inst(idx: usize) -> u16 { (idx as u16) << 5 }
+}
+
+/// A Thumb index made from an arbitrary selection of bits.
+pub struct ThumbBitIndex<const MASK: u16>;
+impl<const MASK: u16> LutIndex<u16> for ThumbBitIndex<MASK> {
+    const LEN: usize = 1 << MASK.count_ones();
+    #[inline(always)]
+    fn index(x: u16) -> usize { gather_bits(x as u32, MASK as u32) }
+    fn inst(idx: usize) -> u16 { scatter_bits(idx, MASK as u32) as u16 }
+}
+
+
+/// Number of entries in each chunk of a [LutData]. Any multiple of 64 
+/// entries fills a whole number of cache lines, whatever the entry size.
+const CHUNK: usize = 64;
+
+/// A cache-line aligned run of table entries.
 #[repr(C, align(64))]
-pub struct ArmLut<T: ArmLutEntry> { pub data: [T; 0x1000] }
+struct Chunk<T>([T; CHUNK]);
+
+/// Storage for the entries of a table. 
+///
+/// Entries are stored contiguously on the heap, starting on a cache line. 
+/// Only one chunk of entries is built on the stack at a time, so large entry
+/// types don't blow the stack during construction.
+pub struct LutData<T> {
+    chunks: Box<[Chunk<T>]>,
+    len: usize,
+}
+
+impl<T> LutData<T> {
+    /// Create storage for `len` entries, where `f` gives the entry at some 
+    /// index.
+    fn from_fn(len: usize, mut f: impl FnMut(usize) -> T) -> Self {
+        let chunks = (0..len).step_by(CHUNK).map(|start| {
+            // Padding after the last entry is never looked up
+            Chunk(std::array::from_fn(|i| f((start + i).min(len - 1))))
+        }).collect();
+        LutData { chunks, len }
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize { self.len }
+
+    /// Returns true if there are no entries.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Iterate over the entries.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.chunks.iter().flat_map(|chunk| chunk.0.iter()).take(self.len)
+    }
+}
+
+impl<T> Index<usize> for LutData<T> {
+    type Output = T;
+    #[inline(always)]
+    fn index(&self, idx: usize) -> &T { &self.chunks[idx / CHUNK].0[idx % CHUNK] }
+}
+
+/// An ARMv5 lookup table. 
+pub struct ArmLut<T: ArmLutEntry, I: LutIndex<u32> = ArmDecodeIndex> { 
+    pub data: LutData<T>,
+    index: PhantomData<I>,
+}
 
 /// A Thumb lookup table.
-#[repr(C, align(64))]
-pub struct ThumbLut<T: ThumbLutEntry> { pub data: [T; 0x0800] }
+pub struct ThumbLut<T: ThumbLutEntry, I: LutIndex<u16> = ThumbDecodeIndex> { 
+    pub data: LutData<T>,
+    index: PhantomData<I>,
+}
 
 /// Implemented on all types store-able by some ArmLut.
 pub trait ArmLutEntry { fn from_inst(inst: ArmInst) -> Self; }
@@ -17,26 +168,575 @@ pub trait ArmLutEntry { fn from_inst(inst: ArmInst) -> Self; }
 /// Implemented on all types store-able by some ThumbLut.
 pub trait ThumbLutEntry { fn from_inst(inst: ThumbInst) -> Self; }
 
+/// A table of decoded ARM instructions.
+impl ArmLutEntry for ArmInst { fn from_inst(inst: ArmInst) -> Self { inst } }
+
+/// A table of decoded Thumb instructions.
+impl ThumbLutEntry for ThumbInst { fn from_inst(inst: ThumbInst) -> Self { inst } }
+
 
 /// Creates a new ArmLut for some T.
 ///
 /// The details of how to obtain an entry T are left to the user.
-pub fn make_arm_lut<T: ArmLutEntry + Copy>(default_entry: T) -> ArmLut<T> {
-    let mut lut = ArmLut { data: [default_entry; 0x1000] };
-    for i in 0..0x1000 {
-        let inst: u32 = ((i & 0x0ff0) << 16) | ((i & 0x000f) << 4);
-        lut.data[i as usize] = T::from_inst(ArmInst::decode(inst));
+pub fn make_arm_lut<T: ArmLutEntry>() -> ArmLut<T> { ArmLut::new() }
+
+/// Create a new ThumbLut for some T.
+pub fn make_thumb_lut<T: ThumbLutEntry>() -> ThumbLut<T> { ThumbLut::new() }
+
+impl<T: ArmLutEntry, I: LutIndex<u32>> ArmLut<T, I> {
+    /// Create a new ArmLut using some index I.
+    pub fn new() -> Self {
+        let data = LutData::from_fn(I::LEN, 
+            |idx| T::from_inst(ArmInst::decode(I::inst(idx))));
+        ArmLut { data, index: PhantomData }
     }
-    lut
+
+    /// Get the entry corresponding to some ARM instruction.
+    #[inline(always)]
+    pub fn lookup(&self, x: u32) -> &T { &self.data[I::index(x)] }
+}
+impl<T: ArmLutEntry, I: LutIndex<u32>> Default for ArmLut<T, I> {
+    fn default() -> Self { Self::new() }
 }
 
-/// Create a new ThumbLut for some T.
-pub fn make_thumb_lut<T: ThumbLutEntry + Copy>(default_entry: T) -> ThumbLut<T> {
-    let mut lut = ThumbLut { data: [default_entry; 0x0800] };
-    for i in 0..0x800 {
-        let inst: u16 = i << 5;
-        lut.data[i as usize] = T::from_inst(ThumbInst::decode(inst));
+impl<T: ThumbLutEntry, I: LutIndex<u16>> ThumbLut<T, I> {
+    /// Create a new ThumbLut using some index I.
+    pub fn new() -> Self {
+        let data = LutData::from_fn(I::LEN, 
+            |idx| T::from_inst(ThumbInst::decode(I::inst(idx))));
+        ThumbLut { data, index: PhantomData }
     }
-    lut
+
+    /// Get the entry corresponding to some Thumb instruction.
+    #[inline(always)]
+    pub fn lookup(&self, x: u16) -> &T { &self.data[I::index(x)] }
+}
+impl<T: ThumbLutEntry, I: LutIndex<u16>> Default for ThumbLut<T, I> {
+    fn default() -> Self { Self::new() }
+}
+
+
+/// A deduplicated, two-level ARM lookup table.
+///
+/// Each table index maps to a small class ID, and each class ID maps to a 
+/// single entry T. Only one entry is created for each distinct [ArmInst].
+pub struct ArmClassLut<T: ArmLutEntry, I: LutIndex<u32> = ArmDecodeIndex> {
+    /// Class ID for each table index.
+    pub class: Box<[u8]>,
+    /// The instruction corresponding to each class ID.
+    pub insts: Vec<ArmInst>,
+    /// The entry corresponding to each class ID.
+    pub entries: Vec<T>,
+    index: PhantomData<I>,
 }
 
+/// A deduplicated, two-level Thumb lookup table.
+pub struct ThumbClassLut<T: ThumbLutEntry, I: LutIndex<u16> = ThumbDecodeIndex> {
+    /// Class ID for each table index.
+    pub class: Box<[u8]>,
+    /// The instruction corresponding to each class ID.
+    pub insts: Vec<ThumbInst>,
+    /// The entry corresponding to each class ID.
+    pub entries: Vec<T>,
+    index: PhantomData<I>,
+}
+
+/// Get the class ID for some instruction, adding a new class if necessary.
+fn get_class<X: PartialEq>(insts: &mut Vec<X>, inst: X) -> u8 {
+    let id = match insts.iter().position(|x| *x == inst) {
+        Some(id) => id,
+        None => { insts.push(inst); insts.len() - 1 },
+    };
+    assert!(id <= u8::MAX as usize, "Too many instruction classes");
+    id as u8
+}
+
+impl<T: ArmLutEntry, I: LutIndex<u32>> ArmClassLut<T, I> {
+    /// Create a new ArmClassLut using some index I.
+    pub fn new() -> Self {
+        let mut insts = Vec::new();
+        let class = (0..I::LEN)
+            .map(|idx| get_class(&mut insts, ArmInst::decode(I::inst(idx))))
+            .collect();
+        let entries = insts.iter().map(|inst| T::from_inst(*inst)).collect();
+        ArmClassLut { class, insts, entries, index: PhantomData }
+    }
+
+    /// Get the class ID corresponding to some ARM instruction.
+    #[inline(always)]
+    pub fn class_of(&self, x: u32) -> u8 { self.class[I::index(x)] }
+
+    /// Get the entry corresponding to some ARM instruction.
+    #[inline(always)]
+    pub fn lookup(&self, x: u32) -> &T { 
+        &self.entries[self.class_of(x) as usize] 
+    }
+}
+impl<T: ArmLutEntry, I: LutIndex<u32>> Default for ArmClassLut<T, I> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: ThumbLutEntry, I: LutIndex<u16>> ThumbClassLut<T, I> {
+    /// Create a new ThumbClassLut using some index I.
+    pub fn new() -> Self {
+        let mut insts = Vec::new();
+        let class = (0..I::LEN)
+            .map(|idx| get_class(&mut insts, ThumbInst::decode(I::inst(idx))))
+            .collect();
+        let entries = insts.iter().map(|inst| T::from_inst(*inst)).collect();
+        ThumbClassLut { class, insts, entries, index: PhantomData }
+    }
+
+    /// Get the class ID corresponding to some Thumb instruction.
+    #[inline(always)]
+    pub fn class_of(&self, x: u16) -> u8 { self.class[I::index(x)] }
+
+    /// Get the entry corresponding to some Thumb instruction.
+    #[inline(always)]
+    pub fn lookup(&self, x: u16) -> &T { 
+        &self.entries[self.class_of(x) as usize] 
+    }
+}
+impl<T: ThumbLutEntry, I: LutIndex<u16>> Default for ThumbClassLut<T, I> {
+    fn default() -> Self { Self::new() }
+}
+
+
+/// A handler for ARM instructions, typed on the newtype it operates on.
+///
+/// Handlers take a mutable reference to some user-defined context `C` and
+/// return some user-defined `R`. The `Raw` variant receives the instruction
+/// as a plain u32 (i.e. for undefined instructions).
+pub enum ArmFn<C, R> {
+    DpRotImm(fn(&mut C, &DpRotImmBf) -> R),
+    DpShift(fn(&mut C, &DpShiftBf) -> R),
+    Mul(fn(&mut C, &MulBf) -> R),
+    Sat(fn(&mut C, &SatBf) -> R),
+    Clz(fn(&mut C, &ClzBf) -> R),
+    LsImm(fn(&mut C, &LsImmBf) -> R),
+    LsShift(fn(&mut C, &LsShiftBf) -> R),
+    LsMulti(fn(&mut C, &LsMultiBf) -> R),
+    LsMisc(fn(&mut C, &LsMiscBf) -> R),
+    Swp(fn(&mut C, &SwpBf) -> R),
+    Branch(fn(&mut C, &BranchBf) -> R),
+    Bx(fn(&mut C, &BxBf) -> R),
+    Coproc(fn(&mut C, &CoprocBf) -> R),
+    Status(fn(&mut C, &StatusBf) -> R),
+    Swi(fn(&mut C, &SwiBf) -> R),
+    Bkpt(fn(&mut C, &BkptBf) -> R),
+    Raw(fn(&mut C, u32) -> R),
+}
+
+// Function pointers are always Copy, regardless of C and R.
+impl<C, R> Clone for ArmFn<C, R> { fn clone(&self) -> Self { *self } }
+impl<C, R> Copy for ArmFn<C, R> {}
+
+impl<C, R> ArmFn<C, R> {
+    /// Call this handler on some ARM instruction.
+    #[inline(always)]
+    pub fn call(&self, ctx: &mut C, x: u32) -> R {
+        match *self {
+            ArmFn::DpRotImm(f)  => f(ctx, &DpRotImmBf(x)),
+            ArmFn::DpShift(f)   => f(ctx, &DpShiftBf(x)),
+            ArmFn::Mul(f)       => f(ctx, &MulBf(x)),
+            ArmFn::Sat(f)       => f(ctx, &SatBf(x)),
+            ArmFn::Clz(f)       => f(ctx, &ClzBf(x)),
+            ArmFn::LsImm(f)     => f(ctx, &LsImmBf(x)),
+            ArmFn::LsShift(f)   => f(ctx, &LsShiftBf(x)),
+            ArmFn::LsMulti(f)   => f(ctx, &LsMultiBf(x)),
+            ArmFn::LsMisc(f)    => f(ctx, &LsMiscBf(x)),
+            ArmFn::Swp(f)       => f(ctx, &SwpBf(x)),
+            ArmFn::Branch(f)    => f(ctx, &BranchBf(x)),
+            ArmFn::Bx(f)        => f(ctx, &BxBf(x)),
+            ArmFn::Coproc(f)    => f(ctx, &CoprocBf(x)),
+            ArmFn::Status(f)    => f(ctx, &StatusBf(x)),
+            ArmFn::Swi(f)       => f(ctx, &SwiBf(x)),
+            ArmFn::Bkpt(f)      => f(ctx, &BkptBf(x)),
+            ArmFn::Raw(f)       => f(ctx, x),
+        }
+    }
+}
+
+/// A handler for Thumb instructions, typed on the newtype it operates on.
+pub enum ThumbFn<C, R> {
+    LsMulti(fn(&mut C, &ThumbLsMultiBf) -> R),
+    PushPop(fn(&mut C, &PushPopBf) -> R),
+    Excep(fn(&mut C, &ThumbExcepBf) -> R),
+    DpSpecial(fn(&mut C, &DpSpecialBf) -> R),
+    LdrLit(fn(&mut C, &ThumbLdrLitBf) -> R),
+    DpFmt3(fn(&mut C, &DpFmt3Bf) -> R),
+    DpFmt4(fn(&mut C, &DpFmt4Bf) -> R),
+    DpFmt5(fn(&mut C, &DpFmt5Bf) -> R),
+    BranchLink(fn(&mut C, &ThumbBranchLinkBf) -> R),
+    BranchExchange(fn(&mut C, &ThumbBranchExchangeBf) -> R),
+    LsFmt1(fn(&mut C, &ThumbLsFmt1Bf) -> R),
+    LsFmt2(fn(&mut C, &ThumbLsFmt2Bf) -> R),
+    LsFmt4(fn(&mut C, &ThumbLsFmt4Bf) -> R),
+    DpFmt1(fn(&mut C, &DpFmt1Bf) -> R),
+    DpFmt2(fn(&mut C, &DpFmt2Bf) -> R),
+    DpFmt6(fn(&mut C, &DpFmt6Bf) -> R),
+    DpFmt7(fn(&mut C, &DpFmt7Bf) -> R),
+    CondBranch(fn(&mut C, &ThumbCondBranchBf) -> R),
+    Raw(fn(&mut C, u16) -> R),
+}
+
+impl<C, R> Clone for ThumbFn<C, R> { fn clone(&self) -> Self { *self } }
+impl<C, R> Copy for ThumbFn<C, R> {}
+
+impl<C, R> ThumbFn<C, R> {
+    /// Call this handler on some Thumb instruction.
+    #[inline(always)]
+    pub fn call(&self, ctx: &mut C, x: u16) -> R {
+        match *self {
+            ThumbFn::LsMulti(f)         => f(ctx, &ThumbLsMultiBf(x)),
+            ThumbFn::PushPop(f)         => f(ctx, &PushPopBf(x)),
+            ThumbFn::Excep(f)           => f(ctx, &ThumbExcepBf(x)),
+            ThumbFn::DpSpecial(f)       => f(ctx, &DpSpecialBf(x)),
+            ThumbFn::LdrLit(f)          => f(ctx, &ThumbLdrLitBf(x)),
+            ThumbFn::DpFmt3(f)          => f(ctx, &DpFmt3Bf(x)),
+            ThumbFn::DpFmt4(f)          => f(ctx, &DpFmt4Bf(x)),
+            ThumbFn::DpFmt5(f)          => f(ctx, &DpFmt5Bf(x)),
+            ThumbFn::BranchLink(f)      => f(ctx, &ThumbBranchLinkBf(x)),
+            ThumbFn::BranchExchange(f)  => f(ctx, &ThumbBranchExchangeBf(x)),
+            ThumbFn::LsFmt1(f)          => f(ctx, &ThumbLsFmt1Bf(x)),
+            ThumbFn::LsFmt2(f)          => f(ctx, &ThumbLsFmt2Bf(x)),
+            ThumbFn::LsFmt4(f)          => f(ctx, &ThumbLsFmt4Bf(x)),
+            ThumbFn::DpFmt1(f)          => f(ctx, &DpFmt1Bf(x)),
+            ThumbFn::DpFmt2(f)          => f(ctx, &DpFmt2Bf(x)),
+            ThumbFn::DpFmt6(f)          => f(ctx, &DpFmt6Bf(x)),
+            ThumbFn::DpFmt7(f)          => f(ctx, &DpFmt7Bf(x)),
+            ThumbFn::CondBranch(f)      => f(ctx, &ThumbCondBranchBf(x)),
+            ThumbFn::Raw(f)             => f(ctx, x),
+        }
+    }
+}
+
+/// Implemented on some context type in order to select an [ArmFn] for each
+/// kind of ARM instruction.
+pub trait ArmHandlers<R>: Sized {
+    fn arm_handler(inst: ArmInst) -> ArmFn<Self, R>;
+}
+
+/// Implemented on some context type in order to select a [ThumbFn] for each
+/// kind of Thumb instruction.
+pub trait ThumbHandlers<R>: Sized {
+    fn thumb_handler(inst: ThumbInst) -> ThumbFn<Self, R>;
+}
+
+impl<C: ArmHandlers<R>, R> ArmLutEntry for ArmFn<C, R> {
+    fn from_inst(inst: ArmInst) -> Self { C::arm_handler(inst) }
+}
+
+impl<C: ThumbHandlers<R>, R> ThumbLutEntry for ThumbFn<C, R> {
+    fn from_inst(inst: ThumbInst) -> Self { C::thumb_handler(inst) }
+}
+
+impl<C: ArmHandlers<R>, R, I: LutIndex<u32>> ArmLut<ArmFn<C, R>, I> {
+    /// Look up and call the handler for some ARM instruction.
+    #[inline(always)]
+    pub fn dispatch(&self, ctx: &mut C, x: u32) -> R {
+        self.lookup(x).call(ctx, x)
+    }
+}
+
+impl<C: ThumbHandlers<R>, R, I: LutIndex<u16>> ThumbLut<ThumbFn<C, R>, I> {
+    /// Look up and call the handler for some Thumb instruction.
+    #[inline(always)]
+    pub fn dispatch(&self, ctx: &mut C, x: u16) -> R {
+        self.lookup(x).call(ctx, x)
+    }
+}
+
+impl<C: ArmHandlers<R>, R, I: LutIndex<u32>> ArmClassLut<ArmFn<C, R>, I> {
+    /// Look up and call the handler for some ARM instruction.
+    #[inline(always)]
+    pub fn dispatch(&self, ctx: &mut C, x: u32) -> R {
+        self.lookup(x).call(ctx, x)
+    }
+}
+
+impl<C: ThumbHandlers<R>, R, I: LutIndex<u16>> ThumbClassLut<ThumbFn<C, R>, I> {
+    /// Look up and call the handler for some Thumb instruction.
+    #[inline(always)]
+    pub fn dispatch(&self, ctx: &mut C, x: u16) -> R {
+        self.lookup(x).call(ctx, x)
+    }
+}
+
+
+/// Some table index whose instruction class is not uniquely determined by the
+/// bits used to compute the index.
+#[derive(Debug)]
+pub struct LutMismatch<X, D> {
+    /// The offending table index.
+    pub index: usize,
+    /// The class stored in the table at this index.
+    pub entry: D,
+    /// An example instruction for each other class that maps onto this index.
+    pub conflicts: Vec<(X, D)>,
+}
+
+/// Instruction types which can be used to index into a table.
+trait LutWord: Copy { 
+    const WIDTH: u32;
+    fn from_u32(x: u32) -> Self; 
+    fn to_u32(self) -> u32; 
+}
+impl LutWord for u32 {
+    const WIDTH: u32 = 32;
+    fn from_u32(x: u32) -> Self { x }
+    fn to_u32(self) -> u32 { self }
+}
+impl LutWord for u16 {
+    const WIDTH: u32 = 16;
+    fn from_u32(x: u32) -> Self { x as u16 }
+    fn to_u32(self) -> u32 { self as u32 }
+}
+
+/// Get the set of bits in some instruction that don't affect its index.
+fn free_bits<X: LutWord, I: LutIndex<X>>(base: u32) -> u32 {
+    let idx = I::index(X::from_u32(base));
+    (0..X::WIDTH)
+        .filter(|bit| I::index(X::from_u32(base ^ (1 << bit))) == idx)
+        .fold(0, |mask, bit| mask | (1 << bit))
+}
+
+/// Collect a set of words which may map onto the same index as `base`. 
+///
+/// When there are more than `samples` combinations of free bits, this returns
+/// some walking-ones/walking-zeros patterns, plus `samples` pseudo-random 
+/// patterns. Otherwise, all combinations are enumerated.
+fn candidate_words(base: u32, free: u32, samples: usize) -> Vec<u32> {
+    let n = 1usize << free.count_ones();
+    if n <= samples {
+        return (0..n).map(|i| base | scatter_bits(i, free)).collect();
+    }
+    let mut res = vec![base & !free, base | free];
+    for bit in (0..32).filter(|bit| bit!(free, bit)) {
+        res.push((base & !free) | (1 << bit));
+        res.push((base | free) & !(1 << bit));
+    }
+    // xorshift32, seeded with the base instruction
+    let mut state = base ^ 0x9e37_79b9;
+    for _ in 0..samples {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        res.push((base & !free) | (state & free));
+    }
+    res
+}
+
+/// Compare the entry at each index against the classes of instructions that 
+/// map onto the index, collecting any mismatches.
+fn verify_lut<X, I, D>(samples: usize, decode: impl Fn(X) -> D, 
+    entry: impl Fn(usize) -> D) -> Vec<LutMismatch<X, D>>
+    where X: LutWord, I: LutIndex<X>, D: PartialEq + Copy
+{
+    let mut res = Vec::new();
+    for idx in 0..I::LEN {
+        let base = I::inst(idx).to_u32();
+        let expected = entry(idx);
+        let mut conflicts: Vec<(X, D)> = Vec::new();
+        let free = free_bits::<X, I>(base);
+        for word in candidate_words(base, free, samples) {
+            let word = X::from_u32(word);
+            if I::index(word) != idx { continue; }
+            let found = decode(word);
+            if found != expected && !conflicts.iter().any(|(_, d)| *d == found) {
+                conflicts.push((word, found));
+            }
+        }
+        if !conflicts.is_empty() {
+            res.push(LutMismatch { index: idx, entry: expected, conflicts });
+        }
+    }
+    res
+}
+
+impl<I: LutIndex<u32>> ArmLut<ArmInst, I> {
+    /// Check that the class of every instruction is uniquely determined by 
+    /// its index, returning a list of all indexes where this is untrue.
+    /// Encodings with the wrong should-be-one/should-be-zero fields are
+    /// treated as undefined (see [ArmInst::decode_strict]).
+    ///
+    /// The remaining bits for each index are enumerated when there are at
+    /// most `samples` combinations. Otherwise, they are sampled (with some 
+    /// fixed patterns, plus `samples` pseudo-random patterns). The fixed 
+    /// patterns clear and set every remaining bit, so any conflict caused by
+    /// a single field (the condition, or some should-be field) is always 
+    /// found.
+    pub fn verify(&self, samples: usize) -> Vec<LutMismatch<u32, ArmInst>> {
+        verify_lut::<u32, I, _>(samples, ArmInst::decode_strict, 
+            |idx| self.data[idx])
+    }
+}
+
+impl<I: LutIndex<u16>> ThumbLut<ThumbInst, I> {
+    /// Check that the class of every instruction is uniquely determined by 
+    /// its index, returning a list of all indexes where this is untrue.
+    /// Encodings with the wrong should-be-zero fields are treated as 
+    /// undefined (see [ThumbInst::decode_strict]).
+    ///
+    /// Thumb instructions are always small enough to enumerate exhaustively.
+    pub fn verify(&self) -> Vec<LutMismatch<u16, ThumbInst>> {
+        verify_lut::<u16, I, _>(1 << 16, ThumbInst::decode_strict, 
+            |idx| self.data[idx])
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::lut::*;
+    use crate::traits::*;
+
+    struct Ctx { calls: usize }
+    fn rd(ctx: &mut Ctx, op: &DpRotImmBf) -> u32 { ctx.calls += 1; op.rd() }
+    fn undef(ctx: &mut Ctx, x: u32) -> u32 { ctx.calls += 1; x }
+
+    impl ArmHandlers<u32> for Ctx {
+        fn arm_handler(inst: ArmInst) -> ArmFn<Self, u32> {
+            match inst {
+                ArmInst::MovRotImm => ArmFn::DpRotImm(rd),
+                _ => ArmFn::Raw(undef),
+            }
+        }
+    }
+
+    #[test]
+    fn arm_dispatch() {
+        let lut = make_arm_lut::<ArmFn<Ctx, u32>>();
+        let mut ctx = Ctx { calls: 0 };
+        // mov r4, #0x100
+        assert_eq!(lut.dispatch(&mut ctx, 0xe3a04c01), 4);
+        // svc #0
+        assert_eq!(lut.dispatch(&mut ctx, 0xef000000), 0xef000000);
+        assert_eq!(ctx.calls, 2);
+    }
+
+    #[test]
+    fn bit_index() {
+        type DecodeBits = ArmBitIndex<0x0ff0_00f0>;
+        assert_eq!(DecodeBits::LEN, ArmDecodeIndex::LEN);
+        for idx in 0..ArmDecodeIndex::LEN {
+            let x = ArmDecodeIndex::inst(idx);
+            assert_eq!(DecodeBits::inst(idx), x);
+            assert_eq!(DecodeBits::index(x | 0xf000_0f0f), idx);
+        }
+        type ThumbBits = ThumbBitIndex<0xffe0>;
+        assert_eq!(ThumbBits::LEN, ThumbDecodeIndex::LEN);
+        assert_eq!(ThumbBits::index(0xe801), ThumbDecodeIndex::index(0xe801));
+    }
+
+    #[test]
+    fn storage() {
+        let lut = ArmLut::<ArmFn<Ctx, u32>>::new();
+        assert_eq!(&lut.data[0] as *const _ as usize % 64, 0);
+        assert_eq!(lut.data.iter().count(), ArmDecodeIndex::LEN);
+        // Smaller than a single chunk
+        let lut = ArmLut::<ArmInst, ArmBitIndex<0x0e00_0000>>::new();
+        assert_eq!(lut.data.len(), 8);
+        assert_eq!(lut.data.iter().copied().collect::<Vec<_>>(), 
+            (0..8).map(|idx| lut.data[idx]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cond_index() {
+        let lut = ArmLut::<ArmInst, ArmCondIndex>::new();
+        assert_eq!(*lut.lookup(0xfa000000), ArmInst::BlxImm);
+        assert_eq!(*lut.lookup(0xea000000), ArmInst::B);
+    }
+
+    #[test]
+    fn class_lut() {
+        let flat = Box::new(ArmLut::<ArmInst>::new());
+        let lut = ArmClassLut::<ArmInst>::new();
+        for idx in 0..ArmDecodeIndex::LEN {
+            let x = ArmDecodeIndex::inst(idx);
+            assert_eq!(lut.lookup(x), flat.lookup(x));
+        }
+        let flat = Box::new(ThumbLut::<ThumbInst>::new());
+        let lut = ThumbClassLut::<ThumbInst>::new();
+        for idx in 0..ThumbDecodeIndex::LEN {
+            let x = ThumbDecodeIndex::inst(idx);
+            assert_eq!(lut.lookup(x), flat.lookup(x));
+        }
+        assert_eq!(lut.entries.len(), lut.insts.len());
+    }
+
+    /// The distinct pairs of (entry, conflicting class) in the result of
+    /// some verification.
+    fn conflict_classes<X, D: PartialEq + Copy>(res: &[LutMismatch<X, D>])
+        -> Vec<(D, D)>
+    {
+        let mut pairs = Vec::new();
+        for m in res.iter() {
+            for (_, class) in m.conflicts.iter() {
+                if !pairs.contains(&(m.entry, *class)) {
+                    pairs.push((m.entry, *class));
+                }
+            }
+        }
+        pairs
+    }
+
+    fn assert_classes<D>(found: &[(D, D)], expected: &[(D, D)])
+        where D: PartialEq + Copy + std::fmt::Debug
+    {
+        for pair in expected.iter() {
+            assert!(found.contains(pair), "missing {:?}", pair);
+        }
+        for pair in found.iter() {
+            assert!(expected.contains(pair), "unexpected {:?}", pair);
+        }
+    }
+
+    /// Instructions with should-be fields, which are UNPREDICTABLE (and
+    /// reported as undefined) when the fields are wrong.
+    const ARM_SHOULD_BE: &[ArmInst] = {
+        use ArmInst::*;
+        &[
+            Mrs, MsrReg, MsrImm, Bx, BlxReg, Clz, Qadd, Qsub, QdAdd, QdSub,
+            SmulwY, SmulXy, Mul, Swp, Swpb,
+            StrhReg, LdrhReg, LdrsbReg, LdrshReg, StrdReg, LdrdReg,
+            MovRotImm, MvnRotImm, MovShiftImm, MvnShiftImm, MovShiftReg, MvnShiftReg,
+            TstRotImm, TeqRotImm, CmpRotImm, CmnRotImm,
+            TstShiftImm, TeqShiftImm, CmpShiftImm, CmnShiftImm,
+            TstShiftReg, TeqShiftReg, CmpShiftReg, CmnShiftReg,
+        ]
+    };
+
+    #[test]
+    fn verify_arm_lut() {
+        use ArmInst::*;
+        let mut expected: Vec<(ArmInst, ArmInst)> = ARM_SHOULD_BE.iter()
+            .map(|inst| (*inst, None))
+            .collect();
+        let res = ArmLut::<ArmInst, ArmCondIndex>::new().verify(64);
+        assert_classes(&conflict_classes(&res), &expected);
+
+        // Without the condition in the index, BLX(1), the undefined space 
+        // beside SWI and the coprocessor instructions (which have "2" forms)
+        // are also ambiguous
+        expected.extend_from_slice(&[
+            (B, BlxImm), (Bl, BlxImm), (Swi, None),
+            (Ldc, Ldc2), (Stc, Stc2), (Mcrr, None), (Mrrc, None),
+            (Cdp, Cdp2), (Mcr, Mcr2), (Mrc, Mrc2),
+        ]);
+        let res = ArmLut::<ArmInst>::new().verify(64);
+        assert_classes(&conflict_classes(&res), &expected);
+        for m in res.iter().filter(|m| !ARM_SHOULD_BE.contains(&m.entry)) {
+            for (word, _) in m.conflicts.iter() {
+                assert_eq!(get_cond!(word), 0b1111, "{:?}", m);
+            }
+        }
+    }
+
+    #[test]
+    fn verify_thumb_lut() {
+        use ThumbInst::*;
+        let res = ThumbLut::<ThumbInst>::new().verify();
+        assert_classes(&conflict_classes(&res), &[(Bx, None), (BlxReg, None)]);
+    }
+}
@@ -0,0 +1,566 @@
+//! Memory images made of multiple segments.
+//!
+//! A [MemoryImage] is a set of non-overlapping [Segment]s, each with its
+//! own base address, permissions, byte order and default instruction set
+//! (i.e. the mode a linear sweep starts in). Reads which aren't entirely
+//! inside a single segment fail with [Unmapped].
+//!
+//! Patches can be layered on top of the segments with [Overlay]s, without
+//! modifying the original bytes. Overlays added later take precedence, and
+//! all reads (and the analyses below) see the patched contents.
+//!
+//! The loaders for each file format produce a [MemoryImage], which is then
+//! the input for the analyses: [LinearSweep], [FunctionFinder],
+//! [RecursiveDescent] and [LiteralPools](crate::literal::LiteralPools) can
+//! each be created over the segment containing some address with
+//! `from_memory`. [MemoryImage::disassemble], [MemoryImage::find_functions]
+//! and [MemoryImage::analyze] run them over every executable segment.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::BitOr;
+
+use crate::cfg::*;
+use crate::disas::{Disassembler, Isa};
+use crate::elf::{Elf, ET_REL, SHF_EXECINSTR, SHF_WRITE, SHT_NOBITS};
+use crate::flow::*;
+use crate::funcs::*;
+use crate::image::Image;
+use crate::pe::*;
+use crate::stream::*;
+use crate::sweep::*;
+
+/// Access permissions for a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Perms(u8);
+impl Perms {
+    pub const NONE: Perms = Perms(0);
+    pub const R: Perms = Perms(0b001);
+    pub const W: Perms = Perms(0b010);
+    pub const X: Perms = Perms(0b100);
+    pub const RW: Perms = Perms(0b011);
+    pub const RX: Perms = Perms(0b101);
+    pub const RWX: Perms = Perms(0b111);
+
+    /// Returns true if all permissions in `other` are set.
+    pub fn contains(&self, other: Perms) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+impl BitOr for Perms {
+    type Output = Perms;
+    fn bitor(self, rhs: Perms) -> Perms {
+        Perms(self.0 | rhs.0)
+    }
+}
+impl fmt::Display for Perms {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flag = |p, c| if self.contains(p) { c } else { '-' };
+        write!(f, "{}{}{}", flag(Perms::R, 'r'), flag(Perms::W, 'w'),
+            flag(Perms::X, 'x'))
+    }
+}
+
+/// Error returned when reading memory outside of any segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unmapped {
+    pub addr: u32,
+    /// Size of the access in bytes.
+    pub size: u32,
+}
+impl fmt::Display for Unmapped {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unmapped {}-byte access at 0x{:08x}", self.size, self.addr)
+    }
+}
+impl std::error::Error for Unmapped {}
+
+
+/// A contiguous region of memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub name: String,
+    pub base: u32,
+    pub bytes: Vec<u8>,
+    pub perms: Perms,
+    pub order: ByteOrder,
+    /// The instruction set code in this segment is assumed to start in.
+    pub mode: Isa,
+}
+impl Segment {
+    /// Create a little-endian, readable and executable ARM segment.
+    pub fn new(name: &str, base: u32, bytes: Vec<u8>) -> Self {
+        Segment {
+            name: name.to_string(), base, bytes,
+            perms: Perms::RX,
+            order: ByteOrder::Le,
+            mode: Isa::Arm,
+        }
+    }
+    pub fn with_perms(mut self, perms: Perms) -> Self {
+        self.perms = perms;
+        self
+    }
+    pub fn with_order(mut self, order: ByteOrder) -> Self {
+        self.order = order;
+        self
+    }
+    pub fn with_mode(mut self, mode: Isa) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Address after the last byte.
+    pub fn end(&self) -> u64 {
+        self.base as u64 + self.bytes.len() as u64
+    }
+    /// Returns true if `[addr, addr + size)` is inside this segment.
+    pub fn contains(&self, addr: u32, size: u32) -> bool {
+        addr >= self.base && addr as u64 + size as u64 <= self.end()
+    }
+}
+
+/// A set of patches applied on top of the segments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overlay {
+    pub name: String,
+    /// Patched bytes by address.
+    patches: BTreeMap<u32, u8>,
+}
+impl Overlay {
+    pub fn new(name: &str) -> Self {
+        Overlay { name: name.to_string(), patches: BTreeMap::new() }
+    }
+    /// Replace the bytes at some address.
+    pub fn patch(&mut self, addr: u32, bytes: &[u8]) {
+        for (i, b) in bytes.iter().enumerate() {
+            self.patches.insert(addr.wrapping_add(i as u32), *b);
+        }
+    }
+    /// Returns the patched ranges as `(addr, bytes)`.
+    pub fn ranges(&self) -> Vec<(u32, Vec<u8>)> {
+        let mut res: Vec<(u32, Vec<u8>)> = Vec::new();
+        for (addr, b) in self.patches.iter() {
+            match res.last_mut() {
+                Some((start, bytes)) if start.wrapping_add(bytes.len() as u32) == *addr => {
+                    bytes.push(*b);
+                },
+                _ => res.push((*addr, vec![*b])),
+            }
+        }
+        res
+    }
+}
+
+
+/// A memory image with multiple segments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryImage {
+    /// Segments, sorted by base address.
+    segments: Vec<Segment>,
+    /// Contents of each segment with the overlays applied.
+    patched: Vec<Vec<u8>>,
+    overlays: Vec<Overlay>,
+    pub entry: Option<u32>,
+}
+
+impl MemoryImage {
+    pub fn new() -> Self {
+        MemoryImage::default()
+    }
+
+    /// Add a segment. Returns the first overlapping address if it overlaps
+    /// an existing segment.
+    pub fn add_segment(&mut self, seg: Segment) -> Result<(), u32> {
+        if let Some(other) = self.segments.iter()
+            .find(|s| (seg.base as u64) < s.end() && (s.base as u64) < seg.end())
+        {
+            return Err(other.base.max(seg.base));
+        }
+        let index = self.segments.iter().position(|s| s.base > seg.base)
+            .unwrap_or(self.segments.len());
+        let mut bytes = seg.bytes.clone();
+        self.apply_overlays(seg.base, &mut bytes);
+        self.segments.insert(index, seg);
+        self.patched.insert(index, bytes);
+        Ok(())
+    }
+
+    /// Returns the segments, sorted by base address.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns the segment containing `[addr, addr + size)`.
+    pub fn segment_at(&self, addr: u32, size: u32) -> Option<&Segment> {
+        self.segments.iter().find(|s| s.contains(addr, size))
+    }
+
+    /// Returns the segment containing some address, and its contents with
+    /// the overlays applied.
+    pub fn segment_contents(&self, addr: u32) -> Result<(&Segment, &[u8]), Unmapped> {
+        let index = self.segments.iter().position(|s| s.contains(addr, 1))
+            .ok_or(Unmapped { addr, size: 1 })?;
+        Ok((&self.segments[index], &self.patched[index]))
+    }
+
+    /// Add an overlay on top of all previous ones.
+    pub fn push_overlay(&mut self, overlay: Overlay) {
+        self.overlays.push(overlay);
+        self.refresh();
+    }
+
+    /// Remove the most recently added overlay.
+    pub fn pop_overlay(&mut self) -> Option<Overlay> {
+        let res = self.overlays.pop();
+        self.refresh();
+        res
+    }
+
+    pub fn overlays(&self) -> &[Overlay] {
+        &self.overlays
+    }
+
+    /// Apply the overlays to a copy of some range of memory.
+    fn apply_overlays(&self, addr: u32, bytes: &mut [u8]) {
+        let end = addr as u64 + bytes.len() as u64;
+        for overlay in self.overlays.iter() {
+            for (a, b) in overlay.patches.range(addr..) {
+                if *a as u64 >= end {
+                    break;
+                }
+                bytes[(a - addr) as usize] = *b;
+            }
+        }
+    }
+
+    /// Reapply the overlays to all segments.
+    fn refresh(&mut self) {
+        let mut patched = Vec::new();
+        for seg in self.segments.iter() {
+            let mut bytes = seg.bytes.clone();
+            self.apply_overlays(seg.base, &mut bytes);
+            patched.push(bytes);
+        }
+        self.patched = patched;
+    }
+
+    /// Read bytes (with overlays applied).
+    pub fn read_bytes(&self, addr: u32, size: u32) -> Result<Vec<u8>, Unmapped> {
+        let index = self.segments.iter().position(|s| s.contains(addr, size))
+            .ok_or(Unmapped { addr, size })?;
+        let off = (addr - self.segments[index].base) as usize;
+        Ok(self.patched[index][off..off + size as usize].to_vec())
+    }
+
+    /// Returns the byte order of the segment containing some access.
+    fn order_at(&self, addr: u32, size: u32) -> Result<ByteOrder, Unmapped> {
+        Ok(self.segment_at(addr, size).ok_or(Unmapped { addr, size })?.order)
+    }
+
+    pub fn read_u8(&self, addr: u32) -> Result<u8, Unmapped> {
+        Ok(self.read_bytes(addr, 1)?[0])
+    }
+
+    /// Read a data halfword (which is big-endian for both BE-8 and BE-32).
+    pub fn read_u16(&self, addr: u32) -> Result<u16, Unmapped> {
+        let b = self.read_bytes(addr, 2)?;
+        let b = [b[0], b[1]];
+        Ok(match self.order_at(addr, 2)? {
+            ByteOrder::Le => u16::from_le_bytes(b),
+            ByteOrder::Be8 | ByteOrder::Be32 => u16::from_be_bytes(b),
+        })
+    }
+
+    /// Read a data word (which is big-endian for both BE-8 and BE-32).
+    pub fn read_u32(&self, addr: u32) -> Result<u32, Unmapped> {
+        let b = self.read_bytes(addr, 4)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Ok(match self.order_at(addr, 4)? {
+            ByteOrder::Le => u32::from_le_bytes(b),
+            ByteOrder::Be8 | ByteOrder::Be32 => u32::from_be_bytes(b),
+        })
+    }
+
+    /// Fetch an ARM instruction.
+    pub fn fetch_arm(&self, addr: u32) -> Result<u32, Unmapped> {
+        let b = self.read_bytes(addr, 4)?;
+        let order = self.order_at(addr, 4)?;
+        let (_, x) = ArmStream::new(&b, addr, order).next().unwrap().unwrap();
+        Ok(x)
+    }
+
+    /// Fetch a Thumb instruction.
+    pub fn fetch_thumb(&self, addr: u32) -> Result<u16, Unmapped> {
+        let b = self.read_bytes(addr, 2)?;
+        let order = self.order_at(addr, 2)?;
+        let (_, x) = ThumbStream::new(&b, addr, order).next().unwrap().unwrap();
+        Ok(x)
+    }
+
+    /// Returns the executable segments.
+    fn code_segments(&self) -> impl Iterator<Item = &Segment> {
+        self.segments.iter().filter(|s| s.perms.contains(Perms::X))
+    }
+
+    /// Disassemble each executable segment with a linear sweep, starting in
+    /// the segment's default mode.
+    pub fn disassemble(&self, d: &mut Disassembler) -> Vec<Line> {
+        let mut res = Vec::new();
+        for seg in self.code_segments().filter(|s| !s.bytes.is_empty()) {
+            let mut sweep = LinearSweep::from_memory(self, seg.base).unwrap();
+            if let Some(entry) = self.entry.filter(|e| seg.contains(e & !1, 1)) {
+                sweep.add_target(entry);
+            }
+            res.extend(sweep.disassemble(d));
+        }
+        res
+    }
+
+    /// Find functions in each executable segment, assuming most of it is
+    /// code in the segment's default mode.
+    pub fn find_functions(&self) -> Vec<Function> {
+        let mut res = Vec::new();
+        for seg in self.code_segments().filter(|s| !s.bytes.is_empty()) {
+            res.extend(FunctionFinder::from_memory(self, seg.base).unwrap()
+                .find(seg.mode));
+        }
+        res
+    }
+
+    /// Follow code from some entry points (and the image entry point).
+    /// Calls between segments are followed too.
+    pub fn analyze(&self, entries: &[(u32, Isa)]) -> BTreeMap<u32, Cfg> {
+        let mut work: Vec<(u32, Isa)> = entries.to_vec();
+        if let Some(entry) = self.entry {
+            let isa = if (entry & 1) != 0 { Isa::Thumb } else { Isa::Arm };
+            work.push((entry & !1, isa));
+        }
+        let mut res = BTreeMap::new();
+        while !work.is_empty() {
+            for seg in self.code_segments().filter(|s| !s.bytes.is_empty()) {
+                let mut rd = RecursiveDescent::from_memory(self, seg.base).unwrap();
+                for (addr, isa) in work.iter().filter(|(a, _)| seg.contains(*a, 1)) {
+                    rd.add_entry(*addr, *isa);
+                }
+                res.extend(rd.analyze());
+            }
+            // Calls into other segments become new entry points
+            work = res.values().flat_map(|cfg| self.calls(cfg)).filter(|(a, _)| !res.contains_key(a)
+                && self.code_segments().any(|s| s.contains(*a, 1)))
+                .collect();
+        }
+        res
+    }
+
+    /// Returns the targets of direct calls made from some function.
+    fn calls(&self, cfg: &Cfg) -> Vec<(u32, Isa)> {
+        cfg.blocks.values().filter_map(|b| {
+            let addr = *b.insts.last()?;
+            let flow = match cfg.isa {
+                Isa::Arm => arm_flow(addr, self.fetch_arm(addr).ok()?),
+                Isa::Thumb => thumb_flow(addr, self.fetch_thumb(addr).ok()?,
+                    self.fetch_thumb(addr.wrapping_add(2)).ok()).0,
+            };
+            match flow {
+                Flow::Call { target, isa, .. } => Some((target, isa)),
+                _ => None,
+            }
+        }).collect()
+    }
+
+    /// Create an image from a sparse image, with one segment per chunk.
+    pub fn from_image(img: &Image, order: ByteOrder, mode: Isa) -> Self {
+        let mut res = MemoryImage::new();
+        for (addr, data) in img.chunks() {
+            let seg = Segment::new(&format!("chunk_{:08x}", addr), addr, data.to_vec())
+                .with_perms(Perms::RWX).with_order(order).with_mode(mode);
+            res.add_segment(seg).unwrap();
+        }
+        res.entry = img.entry;
+        res
+    }
+
+    /// Create an image from the allocated sections of an ELF file. In
+    /// relocatable files, sections are laid out one after another, and any
+    /// which don't fit below 4 GiB are skipped.
+    pub fn from_elf(elf: &Elf) -> Self {
+        let mut res = MemoryImage::new();
+        let mut next = 0u32;
+        for sec in elf.sections.iter().filter(|s| s.is_alloc() && s.size != 0) {
+            let base = if elf.kind == ET_REL {
+                let base = next.checked_add(0xf).map(|x| x & !0xf);
+                match base.and_then(|base| base.checked_add(sec.size)) {
+                    Some(end) => next = end,
+                    None => continue,
+                }
+                next - sec.size
+            } else {
+                sec.addr
+            };
+            let mut bytes = if sec.kind == SHT_NOBITS {
+                Vec::new()
+            } else {
+                elf.data(sec).to_vec()
+            };
+            bytes.resize(sec.size as usize, 0);
+            let mut perms = Perms::R;
+            if (sec.flags & SHF_WRITE) != 0 { perms = perms | Perms::W; }
+            if (sec.flags & SHF_EXECINSTR) != 0 { perms = perms | Perms::X; }
+            let seg = Segment::new(&sec.name, base, bytes).with_perms(perms)
+                .with_order(elf.byte_order());
+            // Overlapping sections (e.g. in overlays) are skipped
+            let _ = res.add_segment(seg);
+        }
+        if elf.kind != ET_REL {
+            res.entry = Some(elf.entry);
+        }
+        res
+    }
+
+    /// Create an image from a PE image loaded at some base.
+    pub fn from_pe(pe: &Pe, base: u32) -> Result<Self, PeError> {
+        let img = pe.map(base)?;
+        let mut res = MemoryImage::new();
+        for sec in pe.sections.iter() {
+            let addr = base.wrapping_add(sec.rva);
+            let bytes = img.bytes_at(addr).map(|b| b[..sec.size() as usize].to_vec())
+                .unwrap_or_default();
+            let mut perms = Perms::NONE;
+            if (sec.flags & IMAGE_SCN_MEM_READ) != 0 { perms = perms | Perms::R; }
+            if (sec.flags & IMAGE_SCN_MEM_WRITE) != 0 { perms = perms | Perms::W; }
+            if sec.is_exec() { perms = perms | Perms::R | Perms::X; }
+            let seg = Segment::new(&sec.name, addr, bytes).with_perms(perms)
+                .with_mode(pe.entry_isa());
+            res.add_segment(seg)
+                .map_err(|_| PeError::Unsupported("overlapping sections"))?;
+        }
+        res.entry = img.entry;
+        Ok(res)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn image() -> MemoryImage {
+        let mut mem = MemoryImage::new();
+        mem.add_segment(Segment::new("rom", 0x1000, vec![
+            0x1e, 0xff, 0x2f, 0xe1, // bx lr
+            0x01, 0x20, 0x70, 0x47, // movs r0, #1; bx lr
+        ])).unwrap();
+        mem.add_segment(Segment::new("ram", 0x2000_0000, vec![0x12, 0x34, 0x56, 0x78])
+            .with_perms(Perms::RW).with_order(ByteOrder::Be32)).unwrap();
+        mem
+    }
+
+    #[test]
+    fn reads() {
+        let mut mem = image();
+        assert_eq!(mem.fetch_arm(0x1000), Ok(0xe12fff1e));
+        assert_eq!(mem.fetch_thumb(0x1004), Ok(0x2001));
+        assert_eq!(mem.read_u32(0x2000_0000), Ok(0x12345678));
+        assert_eq!(mem.read_u16(0x2000_0002), Ok(0x5678));
+        assert_eq!(mem.read_u8(0x1007), Ok(0x47));
+        assert_eq!(mem.read_u32(0x1006), Err(Unmapped { addr: 0x1006, size: 4 }));
+        assert_eq!(mem.read_u8(0x0), Err(Unmapped { addr: 0, size: 1 }));
+        assert_eq!(mem.add_segment(Segment::new("bad", 0xffc, vec![0; 8])),
+            Err(0x1000));
+        assert_eq!(mem.segments()[1].perms.to_string(), "rw-");
+    }
+
+    #[test]
+    fn overlays() {
+        let mut mem = image();
+        let mut patch = Overlay::new("nop");
+        patch.patch(0x1000, &[0x00, 0x00, 0xa0, 0xe1]);
+        mem.push_overlay(patch);
+        let mut patch = Overlay::new("ret");
+        patch.patch(0x1002, &[0x2f]);
+        patch.patch(0x1003, &[0xe1]);
+        assert_eq!(patch.ranges(), vec![(0x1002, vec![0x2f, 0xe1])]);
+        mem.push_overlay(patch);
+        assert_eq!(mem.fetch_arm(0x1000), Ok(0xe12f0000));
+        mem.pop_overlay();
+        assert_eq!(mem.fetch_arm(0x1000), Ok(0xe1a00000));
+        assert_eq!(mem.segments()[0].bytes[..4], [0x1e, 0xff, 0x2f, 0xe1]);
+
+        let mut d = Disassembler::new();
+        let texts: Vec<String> = mem.disassemble(&mut d).into_iter()
+            .map(|l| l.text).collect();
+        assert_eq!(texts, vec!["mov\tr0, r0", "ldrbmi\tr2, [r0, -r1]!"]);
+    }
+
+    #[test]
+    fn literal_pools() {
+        use crate::literal::LiteralPools;
+        let mut mem = MemoryImage::new();
+        mem.add_segment(Segment::new("rom", 0x1000, vec![
+            0x00, 0x00, 0x9f, 0xe5, // ldr r0, [pc]
+            0x1e, 0xff, 0x2f, 0xe1, // bx lr
+            0xef, 0xbe, 0xad, 0xde, // .word 0xdeadbeef
+        ])).unwrap();
+        let mut patch = Overlay::new("value");
+        patch.patch(0x1008, &0x12345678u32.to_le_bytes());
+        mem.push_overlay(patch);
+
+        let mut d = Disassembler::new();
+        let mut pools = LiteralPools::from_memory(&mem, 0x1004).unwrap();
+        let mut sweep = LinearSweep::from_memory(&mem, 0x1004).unwrap();
+        let texts: Vec<String> = pools.sweep(&mut sweep, &mut d).into_iter()
+            .map(|l| l.text).collect();
+        assert_eq!(texts, vec![
+            "ldr\tr0, [pc]\t@ =0x12345678", "bx\tlr", ".word\t0x12345678",
+        ]);
+        assert_eq!(LiteralPools::from_memory(&mem, 0x100c).err(),
+            Some(Unmapped { addr: 0x100c, size: 1 }));
+    }
+
+    #[test]
+    fn calls_between_segments() {
+        let mut mem = MemoryImage::new();
+        // blx 0x10000; bx lr
+        mem.add_segment(Segment::new("a", 0x0, vec![
+            0xfe, 0x3f, 0x00, 0xfa, 0x1e, 0xff, 0x2f, 0xe1,
+        ])).unwrap();
+        // movs r0, #1; bx lr
+        mem.add_segment(Segment::new("b", 0x10000, vec![0x01, 0x20, 0x70, 0x47])
+            .with_mode(Isa::Thumb)).unwrap();
+        mem.entry = Some(0);
+        let cfgs = mem.analyze(&[]);
+        assert_eq!(cfgs.keys().cloned().collect::<Vec<u32>>(), vec![0, 0x10000]);
+        assert_eq!(cfgs[&0x10000].isa, Isa::Thumb);
+    }
+
+    #[test]
+    fn from_elf() {
+        let buf = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"),
+            "/testsuite/elf_test.be.o")).unwrap();
+        let mem = MemoryImage::from_elf(&Elf::parse(&buf).unwrap());
+        let segs: Vec<(&str, u32, String)> = mem.segments().iter()
+            .map(|s| (s.name.as_str(), s.base, s.perms.to_string())).collect();
+        assert_eq!(segs, vec![
+            (".text", 0x00, "r-x".to_string()),
+            (".data", 0x20, "rw-".to_string()),
+        ]);
+        assert_eq!(mem.segments()[0].order, ByteOrder::Be32);
+        assert_eq!(mem.read_bytes(0x20, 6).unwrap(), b"Hello\0");
+        assert_eq!(mem.fetch_arm(0x8), Ok(0xe12fff1e));
+
+        // A section too big for the rest of the address space is skipped
+        let mut elf = Elf::parse(&buf).unwrap();
+        let mut bss = elf.sections.iter().find(|s| s.name == ".data").unwrap().clone();
+        bss.kind = SHT_NOBITS;
+        for (name, size) in [(".huge", 0xffff_ffe0), (".bss", 4)] {
+            bss.name = name.to_string();
+            bss.size = size;
+            elf.sections.push(bss.clone());
+        }
+        let mem = MemoryImage::from_elf(&elf);
+        let segs: Vec<(&str, u32)> = mem.segments().iter()
+            .map(|s| (s.name.as_str(), s.base)).collect();
+        assert_eq!(segs, vec![(".text", 0x00), (".data", 0x20), (".bss", 0x30)]);
+    }
+}
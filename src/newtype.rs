@@ -33,6 +33,8 @@ declare_instr_fields!(SwpBf,            LsBits, RegBits);
 declare_instr_fields!(BranchBf,         BranchBits, ImmBits, RegBits);
 declare_instr_fields!(BxBf,             RegBits);
 declare_instr_fields!(CoprocBf,         CoprocBits, LsBits, ImmBits, RegBits);
+declare_instr_fields!(VfpBf,            VfpBits, CoprocBits, LsBits, ImmBits, RegBits);
+declare_instr_fields!(FpaBf,            FpaBits, CoprocBits, LsBits, ImmBits, RegBits);
 declare_instr_fields!(StatusBf,         SrBits, RegBits, ImmBits, RotBits);
 declare_instr_fields!(SwiBf,            ImmBits);
 declare_instr_fields!(BkptBf,           ImmBits);
@@ -55,14 +57,21 @@ declare_thumb_fields!(ThumbExcepBf,     ThumbExcepBits);
 declare_thumb_fields!(DpSpecialBf,      DpFmt8Bits);
 declare_thumb_fields!(ThumbLdrLitBf,    LsRegFmt3Bits);
 
+declare_thumb_fields!(DpFmt1Bf,         DpFmt1Bits);
+declare_thumb_fields!(DpFmt2Bf,         DpFmt2Bits);
 declare_thumb_fields!(DpFmt3Bf,         DpFmt3Bits);
 declare_thumb_fields!(DpFmt4Bf,         DpFmt4Bits);
 declare_thumb_fields!(DpFmt5Bf,         DpFmt5Bits);
+declare_thumb_fields!(DpFmt6Bf,         DpFmt6Bits);
+declare_thumb_fields!(DpFmt7Bf,         DpFmt7Bits);
 
+declare_thumb_fields!(ThumbCondBranchBf,        ThumbCondBranchBits);
 declare_thumb_fields!(ThumbBranchLinkBf,        ThumbUncondBranchBits);
 declare_thumb_fields!(ThumbBranchExchangeBf,    ThumbBranchExchangeBits);
 
 declare_thumb_fields!(ThumbLsFmt1Bf,      LsRegFmt1Bits);
+declare_thumb_fields!(ThumbLsFmt2Bf,      LsRegFmt2Bits);
+declare_thumb_fields!(ThumbLsFmt4Bf,      LsRegFmt4Bits);
 
 
 
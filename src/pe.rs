@@ -0,0 +1,613 @@
+//! A reader for Windows CE PE/COFF images.
+//!
+//! Windows CE on ARM uses PE32 images with the machine type set to
+//! `IMAGE_FILE_MACHINE_ARM` (ARM code) or `IMAGE_FILE_MACHINE_THUMB`
+//! (mixed ARM/Thumb code). Images are always little-endian. Exported Thumb
+//! functions have bit 0 of their address set, like interworking branch
+//! targets.
+//!
+//! [Pe::map] places the sections at some base address in an [Image],
+//! applying base relocations if the image is moved from its preferred base.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::disas::Isa;
+use crate::image::Image;
+
+pub const IMAGE_FILE_MACHINE_ARM: u16 = 0x01c0;
+pub const IMAGE_FILE_MACHINE_THUMB: u16 = 0x01c2;
+
+pub const IMAGE_SCN_CNT_CODE: u32 = 0x0000_0020;
+pub const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+pub const IMAGE_SCN_MEM_READ: u32 = 0x4000_0000;
+pub const IMAGE_SCN_MEM_WRITE: u32 = 0x8000_0000;
+
+pub const IMAGE_DIRECTORY_ENTRY_EXPORT: usize = 0;
+pub const IMAGE_DIRECTORY_ENTRY_IMPORT: usize = 1;
+pub const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+
+pub const IMAGE_REL_BASED_ABSOLUTE: u16 = 0;
+pub const IMAGE_REL_BASED_HIGH: u16 = 1;
+pub const IMAGE_REL_BASED_LOW: u16 = 2;
+pub const IMAGE_REL_BASED_HIGHLOW: u16 = 3;
+
+/// Error returned when an image can't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeError {
+    /// The file doesn't start with the DOS or PE magic numbers.
+    BadMagic,
+    /// The file isn't a 32-bit ARM PE image.
+    Unsupported(&'static str),
+    /// Some structure extends past the end of the file.
+    Truncated { offset: usize },
+    /// An RVA doesn't point into any section.
+    BadRva(u32),
+}
+impl fmt::Display for PeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PeError::BadMagic => write!(f, "not a PE image"),
+            PeError::Unsupported(what) => write!(f, "unsupported PE image: {}", what),
+            PeError::Truncated { offset } => {
+                write!(f, "PE image truncated at offset 0x{:x}", offset)
+            },
+            PeError::BadRva(rva) => write!(f, "bad RVA 0x{:08x}", rva),
+        }
+    }
+}
+impl std::error::Error for PeError {}
+
+/// Add an offset to an RVA read from the file.
+fn add(rva: u32, off: u32) -> Result<u32, PeError> {
+    rva.checked_add(off).ok_or(PeError::BadRva(rva))
+}
+/// Returns the RVA of entry `i` in a table of `size`-byte entries.
+fn entry(table: u32, i: u32, size: u32) -> Result<u32, PeError> {
+    i.checked_mul(size).and_then(|off| table.checked_add(off))
+        .ok_or(PeError::BadRva(table))
+}
+
+
+/// A section header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub virtual_size: u32,
+    /// Address relative to the image base.
+    pub rva: u32,
+    pub raw_size: u32,
+    pub raw_offset: u32,
+    pub flags: u32,
+}
+impl Section {
+    /// Returns true if this section contains code.
+    pub fn is_exec(&self) -> bool {
+        (self.flags & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE)) != 0
+    }
+    /// Size of this section in memory.
+    pub fn size(&self) -> u32 {
+        if self.virtual_size == 0 { self.raw_size } else { self.virtual_size }
+    }
+    /// Returns true if some RVA is inside this section.
+    pub fn contains(&self, rva: u32) -> bool {
+        rva >= self.rva && rva - self.rva < self.size()
+    }
+}
+
+/// An exported function (or data).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    pub name: Option<String>,
+    pub ordinal: u32,
+    /// Address relative to the image base; bit 0 is set for Thumb code.
+    pub rva: u32,
+    /// Name of the function this is forwarded to, if any.
+    pub forwarder: Option<String>,
+}
+impl Export {
+    /// Returns a name for this export, using the ordinal if it's unnamed.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("ordinal_{}", self.ordinal),
+        }
+    }
+}
+
+/// An imported function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub dll: String,
+    pub name: Option<String>,
+    /// Ordinal, for imports by ordinal, or the hint for imports by name.
+    pub ordinal: u16,
+    /// RVA of the import address table entry.
+    pub iat: u32,
+}
+
+
+/// A parsed PE image.
+pub struct Pe<'a> {
+    buf: &'a [u8],
+    pub machine: u16,
+    pub image_base: u32,
+    /// Size of the image in memory (SizeOfImage).
+    pub image_size: u32,
+    /// RVA of the entry point.
+    pub entry: u32,
+    pub sections: Vec<Section>,
+    /// Data directories as `(rva, size)`.
+    pub dirs: Vec<(u32, u32)>,
+}
+
+impl<'a> Pe<'a> {
+    fn bytes(&self, off: usize, len: usize) -> Result<&'a [u8], PeError> {
+        off.checked_add(len).and_then(|end| self.buf.get(off..end))
+            .ok_or(PeError::Truncated { offset: off })
+    }
+    fn u16(&self, off: usize) -> Result<u16, PeError> {
+        let b = self.bytes(off, 2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+    fn u32(&self, off: usize) -> Result<u32, PeError> {
+        let b = self.bytes(off, 4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Parse a PE image.
+    pub fn parse(buf: &'a [u8]) -> Result<Self, PeError> {
+        let mut pe = Pe {
+            buf, machine: 0, image_base: 0, image_size: 0, entry: 0,
+            sections: Vec::new(), dirs: Vec::new(),
+        };
+        if !buf.starts_with(b"MZ") {
+            return Err(PeError::BadMagic);
+        }
+        let nt = pe.u32(0x3c)? as usize;
+        if pe.bytes(nt, 4)? != b"PE\0\0" {
+            return Err(PeError::BadMagic);
+        }
+        let coff = nt + 4;
+        pe.machine = pe.u16(coff)?;
+        if !matches!(pe.machine, IMAGE_FILE_MACHINE_ARM | IMAGE_FILE_MACHINE_THUMB) {
+            return Err(PeError::Unsupported("not ARM"));
+        }
+        let nsections = pe.u16(coff + 2)? as usize;
+        let opt_size = pe.u16(coff + 16)? as usize;
+
+        let opt = coff + 20;
+        if pe.u16(opt)? != 0x10b {
+            return Err(PeError::Unsupported("not PE32"));
+        }
+        pe.entry = pe.u32(opt + 16)?;
+        pe.image_base = pe.u32(opt + 28)?;
+        pe.image_size = pe.u32(opt + 56)?;
+        let ndirs = pe.u32(opt + 92)? as usize;
+        for i in 0..ndirs.min(16) {
+            let off = opt + 96 + i * 8;
+            pe.dirs.push((pe.u32(off)?, pe.u32(off + 4)?));
+        }
+
+        let table = opt + opt_size;
+        for i in 0..nsections {
+            let off = table + i * 40;
+            let name = pe.bytes(off, 8)?;
+            let len = name.iter().position(|b| *b == 0).unwrap_or(8);
+            pe.sections.push(Section {
+                name: String::from_utf8_lossy(&name[..len]).to_string(),
+                virtual_size: pe.u32(off + 8)?,
+                rva: pe.u32(off + 12)?,
+                raw_size: pe.u32(off + 16)?,
+                raw_offset: pe.u32(off + 20)?,
+                flags: pe.u32(off + 36)?,
+            });
+        }
+        Ok(pe)
+    }
+
+    /// Returns the instruction set of the entry point.
+    pub fn entry_isa(&self) -> Isa {
+        if (self.entry & 1) != 0 { Isa::Thumb } else { Isa::Arm }
+    }
+
+    /// Returns the section with some name.
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Convert an RVA into a file offset.
+    fn offset(&self, rva: u32) -> Result<usize, PeError> {
+        let sec = self.sections.iter().find(|s| s.contains(rva))
+            .ok_or(PeError::BadRva(rva))?;
+        if rva - sec.rva >= sec.raw_size {
+            return Err(PeError::BadRva(rva));
+        }
+        Ok(add(sec.raw_offset, rva - sec.rva)? as usize)
+    }
+
+    fn u16_at(&self, rva: u32) -> Result<u16, PeError> {
+        self.u16(self.offset(rva)?)
+    }
+    fn u32_at(&self, rva: u32) -> Result<u32, PeError> {
+        self.u32(self.offset(rva)?)
+    }
+    /// Read a NUL-terminated string at some RVA.
+    fn str_at(&self, rva: u32) -> Result<String, PeError> {
+        let off = self.offset(rva)?;
+        let rest = self.buf.get(off..).ok_or(PeError::Truncated { offset: off })?;
+        let len = rest.iter().position(|b| *b == 0)
+            .ok_or(PeError::Truncated { offset: self.buf.len() })?;
+        Ok(String::from_utf8_lossy(&rest[..len]).to_string())
+    }
+
+    /// Returns a data directory, if it's present.
+    fn dir(&self, index: usize) -> Option<(u32, u32)> {
+        self.dirs.get(index).cloned().filter(|(rva, size)| *rva != 0 && *size != 0)
+    }
+
+    /// Returns the exported functions, sorted by ordinal.
+    pub fn exports(&self) -> Result<Vec<Export>, PeError> {
+        let (dir, size) = match self.dir(IMAGE_DIRECTORY_ENTRY_EXPORT) {
+            Some(d) => d,
+            None => return Ok(Vec::new()),
+        };
+        let base = self.u32_at(add(dir, 16)?)?;
+        let nfuncs = self.u32_at(add(dir, 20)?)?;
+        let nnames = self.u32_at(add(dir, 24)?)?;
+        let funcs = self.u32_at(add(dir, 28)?)?;
+        let names = self.u32_at(add(dir, 32)?)?;
+        let ords = self.u32_at(add(dir, 36)?)?;
+
+        let mut by_index = BTreeMap::new();
+        for i in 0..nnames {
+            let index = self.u16_at(entry(ords, i, 2)?)? as u32;
+            let name = self.str_at(self.u32_at(entry(names, i, 4)?)?)?;
+            by_index.insert(index, name);
+        }
+        let mut res = Vec::new();
+        for i in 0..nfuncs {
+            let rva = self.u32_at(entry(funcs, i, 4)?)?;
+            if rva == 0 {
+                continue;
+            }
+            // Forwarders point to a string inside the export directory
+            let forwarder = if rva >= dir && rva - dir < size {
+                Some(self.str_at(rva)?)
+            } else {
+                None
+            };
+            res.push(Export {
+                name: by_index.remove(&i),
+                ordinal: base.wrapping_add(i),
+                rva, forwarder,
+            });
+        }
+        Ok(res)
+    }
+
+    /// Returns the imported functions.
+    pub fn imports(&self) -> Result<Vec<Import>, PeError> {
+        let (mut desc, _) = match self.dir(IMAGE_DIRECTORY_ENTRY_IMPORT) {
+            Some(d) => d,
+            None => return Ok(Vec::new()),
+        };
+        let mut res = Vec::new();
+        loop {
+            let lookup = self.u32_at(desc)?;
+            let name = self.u32_at(add(desc, 12)?)?;
+            let iat = self.u32_at(add(desc, 16)?)?;
+            if name == 0 && iat == 0 {
+                break;
+            }
+            let dll = self.str_at(name)?;
+            // Some linkers leave out the lookup table; the IAT is identical
+            // in unbound images.
+            let mut thunk = if lookup != 0 { lookup } else { iat };
+            let mut slot = iat;
+            loop {
+                let x = self.u32_at(thunk)?;
+                if x == 0 {
+                    break;
+                }
+                let (name, ordinal) = if (x & 0x8000_0000) != 0 {
+                    (None, x as u16)
+                } else {
+                    (Some(self.str_at(add(x, 2)?)?), self.u16_at(x)?)
+                };
+                res.push(Import { dll: dll.clone(), name, ordinal, iat: slot });
+                thunk = add(thunk, 4)?;
+                slot = add(slot, 4)?;
+            }
+            desc = add(desc, 20)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the base relocations as `(rva, type)`.
+    pub fn base_relocs(&self) -> Result<Vec<(u32, u16)>, PeError> {
+        let (dir, size) = match self.dir(IMAGE_DIRECTORY_ENTRY_BASERELOC) {
+            Some(d) => d,
+            None => return Ok(Vec::new()),
+        };
+        let mut res = Vec::new();
+        let end = add(dir, size)?;
+        let mut block = dir;
+        while block < end {
+            let page = self.u32_at(block)?;
+            let len = self.u32_at(add(block, 4)?)?;
+            if len < 8 {
+                break;
+            }
+            let next = add(block, len)?;
+            for i in 0..(len - 8) / 2 {
+                let x = self.u16_at(block + 8 + i * 2)?;
+                if (x >> 12) != IMAGE_REL_BASED_ABSOLUTE {
+                    res.push((add(page, (x & 0xfff) as u32)?, x >> 12));
+                }
+            }
+            block = next;
+        }
+        Ok(res)
+    }
+
+    /// Map the sections at some base address, applying base relocations if
+    /// it isn't the preferred base. Relocation types other than HIGHLOW, HIGH
+    /// and LOW are skipped. A section which extends past SizeOfImage is a
+    /// [PeError::BadRva].
+    pub fn map(&self, base: u32) -> Result<Image, PeError> {
+        let mut mem: Vec<(u32, Vec<u8>)> = Vec::new();
+        for sec in self.sections.iter() {
+            if add(sec.rva, sec.size())? > self.image_size {
+                return Err(PeError::BadRva(sec.rva));
+            }
+            let start = sec.raw_offset as usize;
+            let len = sec.raw_size.min(sec.size()) as usize;
+            let mut data = self.bytes(start, len)?.to_vec();
+            data.resize(sec.size() as usize, 0);
+            mem.push((sec.rva, data));
+        }
+
+        let delta = base.wrapping_sub(self.image_base);
+        if delta != 0 {
+            for (rva, kind) in self.base_relocs()? {
+                let width = match kind {
+                    IMAGE_REL_BASED_HIGHLOW => 4,
+                    IMAGE_REL_BASED_HIGH | IMAGE_REL_BASED_LOW => 2,
+                    _ => continue,
+                };
+                let (start, data) = mem.iter_mut()
+                    .find(|(start, data)| rva >= *start
+                        && ((rva - *start) as usize) < data.len())
+                    .ok_or(PeError::BadRva(rva))?;
+                let off = (rva - *start) as usize;
+                let field = data.get_mut(off..off + width)
+                    .ok_or(PeError::BadRva(rva))?;
+                match kind {
+                    IMAGE_REL_BASED_HIGHLOW => {
+                        let x = u32::from_le_bytes([field[0], field[1], field[2], field[3]]);
+                        field.copy_from_slice(&x.wrapping_add(delta).to_le_bytes());
+                    },
+                    _ => {
+                        let x = u16::from_le_bytes([field[0], field[1]]);
+                        let d = if kind == IMAGE_REL_BASED_HIGH {
+                            (delta >> 16) as u16
+                        } else {
+                            delta as u16
+                        };
+                        field.copy_from_slice(&x.wrapping_add(d).to_le_bytes());
+                    },
+                }
+            }
+        }
+
+        let mut img = Image::new();
+        for (rva, data) in mem {
+            img.write(base.wrapping_add(rva), &data)
+                .map_err(|_| PeError::Unsupported("overlapping sections"))?;
+        }
+        img.entry = Some(base.wrapping_add(self.entry));
+        Ok(img)
+    }
+
+    /// Returns labelled starting points for disassembly when the image is
+    /// loaded at some base: the entry point and exported functions in code
+    /// sections. Addresses have bit 0 set for Thumb code.
+    pub fn targets(&self, base: u32) -> Result<BTreeMap<u32, String>, PeError> {
+        let mut res = BTreeMap::new();
+        for e in self.exports()? {
+            let in_code = self.sections.iter()
+                .any(|s| s.is_exec() && s.contains(e.rva & !1));
+            if e.forwarder.is_none() && in_code {
+                res.insert(base.wrapping_add(e.rva), e.label());
+            }
+        }
+        res.entry(base.wrapping_add(self.entry)).or_insert_with(|| "entry".to_string());
+        Ok(res)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::disas::Disassembler;
+    use crate::stream::*;
+    use crate::sweep::*;
+
+    fn put(buf: &mut [u8], off: usize, bytes: &[u8]) {
+        buf[off..off + bytes.len()].copy_from_slice(bytes);
+    }
+    fn put16(buf: &mut [u8], off: usize, x: u16) { put(buf, off, &x.to_le_bytes()); }
+    fn put32(buf: &mut [u8], off: usize, x: u32) { put(buf, off, &x.to_le_bytes()); }
+
+    /// Build a small DLL with an ARM and a Thumb function, one export by
+    /// name and one by ordinal, two imports and a base relocation.
+    fn build() -> Vec<u8> {
+        let mut buf = vec![0u8; 0x600];
+        put(&mut buf, 0, b"MZ");
+        put32(&mut buf, 0x3c, 0x40);
+        put(&mut buf, 0x40, b"PE\0\0");
+        put16(&mut buf, 0x44, IMAGE_FILE_MACHINE_THUMB);
+        put16(&mut buf, 0x46, 2);
+        put16(&mut buf, 0x54, 0xe0);
+        let opt = 0x58;
+        put16(&mut buf, opt, 0x10b);
+        put32(&mut buf, opt + 16, 0x1000);
+        put32(&mut buf, opt + 28, 0x10000);
+        put32(&mut buf, opt + 56, 0x3000);
+        put32(&mut buf, opt + 92, 16);
+        for (i, (rva, size)) in [(0x2000, 0x70), (0x2080, 40), (0, 0), (0, 0),
+            (0, 0), (0x2100, 12)].iter().enumerate()
+        {
+            put32(&mut buf, opt + 96 + i * 8, *rva);
+            put32(&mut buf, opt + 100 + i * 8, *size);
+        }
+        let table = opt + 0xe0;
+        for (i, (name, rva, off, flags)) in [
+            (".text", 0x1000, 0x200, IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE),
+            (".rdata", 0x2000, 0x400, IMAGE_SCN_MEM_READ),
+        ].iter().enumerate() {
+            let sec = table + i * 40;
+            put(&mut buf, sec, name.as_bytes());
+            put32(&mut buf, sec + 8, 0x200);
+            put32(&mut buf, sec + 12, *rva);
+            put32(&mut buf, sec + 16, 0x200);
+            put32(&mut buf, sec + 20, *off);
+            put32(&mut buf, sec + 36, *flags);
+        }
+
+        // .text
+        put32(&mut buf, 0x200, 0xe59f0000); // 1000: ldr r0, [pc]
+        put32(&mut buf, 0x204, 0xe12fff1e); // 1004: bx lr
+        put32(&mut buf, 0x208, 0x00011011); // 1008: .word thumb_fn
+        put16(&mut buf, 0x210, 0x2001);     // 1010: movs r0, #1
+        put16(&mut buf, 0x212, 0x4770);     // 1012: bx lr
+
+        // .rdata: exports
+        let rdata = |rva: usize| rva - 0x2000 + 0x400;
+        put32(&mut buf, rdata(0x200c), 0x2060);
+        put32(&mut buf, rdata(0x2010), 1);
+        put32(&mut buf, rdata(0x2014), 2);
+        put32(&mut buf, rdata(0x2018), 1);
+        put32(&mut buf, rdata(0x201c), 0x2028);
+        put32(&mut buf, rdata(0x2020), 0x2030);
+        put32(&mut buf, rdata(0x2024), 0x2034);
+        put32(&mut buf, rdata(0x2028), 0x1000);
+        put32(&mut buf, rdata(0x202c), 0x1011);
+        put32(&mut buf, rdata(0x2030), 0x2040);
+        put(&mut buf, rdata(0x2040), b"ArmFn\0");
+        put(&mut buf, rdata(0x2060), b"test.dll\0");
+
+        // imports
+        put32(&mut buf, rdata(0x2080), 0x20c0);
+        put32(&mut buf, rdata(0x208c), 0x20b0);
+        put32(&mut buf, rdata(0x2090), 0x20d0);
+        put(&mut buf, rdata(0x20b0), b"coredll.dll\0");
+        for thunks in [0x20c0, 0x20d0].iter() {
+            put32(&mut buf, rdata(*thunks), 0x20e0);
+            put32(&mut buf, rdata(*thunks + 4), 0x8000_0003);
+        }
+        put16(&mut buf, rdata(0x20e0), 0x10);
+        put(&mut buf, rdata(0x20e2), b"Sleep\0");
+
+        // base relocations
+        put32(&mut buf, rdata(0x2100), 0x1000);
+        put32(&mut buf, rdata(0x2104), 12);
+        put16(&mut buf, rdata(0x2108), (IMAGE_REL_BASED_HIGHLOW << 12) | 0x008);
+        buf
+    }
+
+    #[test]
+    fn exports_and_imports() {
+        let buf = build();
+        let pe = Pe::parse(&buf).unwrap();
+        assert_eq!(pe.machine, IMAGE_FILE_MACHINE_THUMB);
+        assert_eq!(pe.entry_isa(), Isa::Arm);
+        assert_eq!(pe.exports().unwrap(), vec![
+            Export { name: Some("ArmFn".to_string()), ordinal: 1, rva: 0x1000,
+                forwarder: None },
+            Export { name: None, ordinal: 2, rva: 0x1011, forwarder: None },
+        ]);
+        assert_eq!(pe.imports().unwrap(), vec![
+            Import { dll: "coredll.dll".to_string(), name: Some("Sleep".to_string()),
+                ordinal: 0x10, iat: 0x20d0 },
+            Import { dll: "coredll.dll".to_string(), name: None,
+                ordinal: 3, iat: 0x20d4 },
+        ]);
+        let targets: Vec<(u32, String)> = pe.targets(0x10000).unwrap()
+            .into_iter().collect();
+        assert_eq!(targets, vec![
+            (0x11000, "ArmFn".to_string()), (0x11011, "ordinal_2".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn rebase_and_disassemble() {
+        let buf = build();
+        let pe = Pe::parse(&buf).unwrap();
+        let word = |img: &Image, addr| {
+            let b = img.bytes_at(addr).unwrap();
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+        };
+        assert_eq!(word(&pe.map(0x10000).unwrap(), 0x11008), 0x11011);
+        let img = pe.map(0x8000_0000).unwrap();
+        assert_eq!(word(&img, 0x8000_1008), 0x8000_1011);
+        assert_eq!(img.entry, Some(0x8000_1000));
+
+        let (base, flat) = img.flatten(0);
+        let mut sweep = LinearSweep::new(&flat[..0x14], base, ByteOrder::Le, Isa::Arm);
+        for addr in pe.targets(0x8000_0000).unwrap().keys() {
+            sweep.add_target(*addr);
+        }
+        sweep.add_range(0x8000_1008, 0x8000_1010, Region::Data);
+        let lines = sweep.disassemble(&mut Disassembler::new());
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, vec![
+            "ldr\tr0, [pc]", "bx\tlr", ".word\t0x80001011", ".word\t0x00000000",
+            "movs\tr0, #1", "bx\tlr",
+        ]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut buf = build();
+        assert!(matches!(Pe::parse(b"ELF").err(), Some(PeError::BadMagic)));
+        assert!(matches!(Pe::parse(&buf[..0x50]).err(),
+            Some(PeError::Truncated { .. })));
+        put16(&mut buf, 0x44, 0x14c);
+        assert!(matches!(Pe::parse(&buf).err(), Some(PeError::Unsupported(_))));
+    }
+
+    /// Offsets read from the file don't overflow, and unknown relocation
+    /// types are skipped.
+    #[test]
+    fn crafted() {
+        let rdata = |rva: usize| rva - 0x2000 + 0x400;
+        let mut buf = build();
+        put16(&mut buf, rdata(0x210a), (5 << 12) | 0x004);
+        let pe = Pe::parse(&buf).unwrap();
+        assert_eq!(pe.base_relocs().unwrap(), vec![(0x1008, 3), (0x1004, 5)]);
+        let img = pe.map(0x8000_0000).unwrap();
+        assert_eq!(img.bytes_at(0x8000_1004).unwrap()[..4], 0xe12fff1eu32.to_le_bytes());
+
+        put32(&mut buf, rdata(0x2104), 0xffff_fff0);
+        let pe = Pe::parse(&buf).unwrap();
+        assert_eq!(pe.base_relocs().err(), Some(PeError::BadRva(0x2100)));
+        put32(&mut buf, 0x58 + 96 + 5 * 8 + 4, 0xffff_ffff);
+        let pe = Pe::parse(&buf).unwrap();
+        assert_eq!(pe.base_relocs().err(), Some(PeError::BadRva(0x2100)));
+
+        put32(&mut buf, 0x58 + 96, 0xffff_fff8);
+        let pe = Pe::parse(&buf).unwrap();
+        assert_eq!(pe.exports().err(), Some(PeError::BadRva(0xffff_fff8)));
+
+        let mut buf = build();
+        put32(&mut buf, 0x58 + 0xe0 + 40 + 8, 0xffff_ffff);
+        let pe = Pe::parse(&buf).unwrap();
+        assert_eq!(pe.map(0x10000).err(), Some(PeError::BadRva(0x2000)));
+        put32(&mut buf, 0x58 + 0xe0 + 40 + 8, 0x1001);
+        let pe = Pe::parse(&buf).unwrap();
+        assert_eq!(pe.map(0x10000).err(), Some(PeError::BadRva(0x2000)));
+    }
+}
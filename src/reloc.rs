@@ -0,0 +1,298 @@
+//! Relocation-aware disassembly of ELF files.
+//!
+//! In relocatable objects, branches to other functions are left as
+//! placeholders (usually branching to themselves) and the real target is
+//! described by a relocation. [Relocations] resolves branch relocations
+//! against symbols in the same section by patching a copy of the section,
+//! i.e. like a linker would, and annotates each relocated instruction or
+//! data word with the relocation type and symbol (like `objdump -dr`).
+//!
+//! Relocations against other sections or undefined symbols can't be
+//! resolved, since their final addresses aren't known; these are only
+//! annotated.
+
+use armbf_prim::*;
+
+use crate::disas::{Disassembler, Isa};
+use crate::disas::branch::sign_extend;
+use crate::elf::*;
+use crate::stream::*;
+use crate::sweep::*;
+
+pub const R_ARM_NONE: u32 = 0;
+pub const R_ARM_PC24: u32 = 1;
+pub const R_ARM_ABS32: u32 = 2;
+pub const R_ARM_REL32: u32 = 3;
+pub const R_ARM_ABS16: u32 = 5;
+pub const R_ARM_ABS12: u32 = 6;
+pub const R_ARM_THM_ABS5: u32 = 7;
+pub const R_ARM_ABS8: u32 = 8;
+pub const R_ARM_THM_CALL: u32 = 10;
+pub const R_ARM_THM_PC8: u32 = 11;
+pub const R_ARM_CALL: u32 = 28;
+pub const R_ARM_JUMP24: u32 = 29;
+pub const R_ARM_THM_JUMP24: u32 = 30;
+pub const R_ARM_TARGET1: u32 = 38;
+pub const R_ARM_V4BX: u32 = 40;
+pub const R_ARM_TARGET2: u32 = 41;
+pub const R_ARM_PREL31: u32 = 42;
+pub const R_ARM_THM_JUMP11: u32 = 102;
+pub const R_ARM_THM_JUMP8: u32 = 103;
+
+/// Returns the name of a relocation type.
+pub fn reloc_name(kind: u32) -> String {
+    match kind {
+        R_ARM_NONE => "R_ARM_NONE",
+        R_ARM_PC24 => "R_ARM_PC24",
+        R_ARM_ABS32 => "R_ARM_ABS32",
+        R_ARM_REL32 => "R_ARM_REL32",
+        R_ARM_ABS16 => "R_ARM_ABS16",
+        R_ARM_ABS12 => "R_ARM_ABS12",
+        R_ARM_THM_ABS5 => "R_ARM_THM_ABS5",
+        R_ARM_ABS8 => "R_ARM_ABS8",
+        R_ARM_THM_CALL => "R_ARM_THM_CALL",
+        R_ARM_THM_PC8 => "R_ARM_THM_PC8",
+        R_ARM_CALL => "R_ARM_CALL",
+        R_ARM_JUMP24 => "R_ARM_JUMP24",
+        R_ARM_THM_JUMP24 => "R_ARM_THM_JUMP24",
+        R_ARM_TARGET1 => "R_ARM_TARGET1",
+        R_ARM_V4BX => "R_ARM_V4BX",
+        R_ARM_TARGET2 => "R_ARM_TARGET2",
+        R_ARM_PREL31 => "R_ARM_PREL31",
+        R_ARM_THM_JUMP11 => "R_ARM_THM_JUMP11",
+        R_ARM_THM_JUMP8 => "R_ARM_THM_JUMP8",
+        _ => return format!("R_ARM_{}", kind),
+    }.to_string()
+}
+
+
+/// The relocations for a single section.
+pub struct Relocations<'e, 'a> {
+    elf: &'e Elf<'a>,
+    sec: &'e Section,
+    pub relocs: Vec<Reloc>,
+}
+
+impl<'e, 'a> Relocations<'e, 'a> {
+    pub fn new(elf: &'e Elf<'a>, sec: &'e Section) -> Result<Self, ElfError> {
+        Ok(Relocations { elf, sec, relocs: elf.relocs(sec)? })
+    }
+
+    /// Returns the name of the symbol for some relocation (or the section
+    /// name, for section symbols), with the explicit addend if there is one.
+    pub fn target_name(&self, r: &Reloc) -> String {
+        let sym = &self.elf.symbols[r.sym];
+        let mut name = match sym.kind {
+            SymbolKind::Section => self.elf.sections.get(sym.shndx as usize)
+                .map(|s| s.name.clone()).unwrap_or_default(),
+            _ => sym.name.clone(),
+        };
+        match r.addend {
+            Some(a) if a > 0 => name.push_str(&format!("+0x{:x}", a)),
+            Some(a) if a < 0 => name.push_str(&format!("-0x{:x}", -(a as i64))),
+            _ => {},
+        }
+        name
+    }
+
+    /// Returns the value of the symbol for some relocation (with bit 0 set
+    /// for Thumb functions), if it's defined in this section.
+    fn resolve(&self, r: &Reloc) -> Option<u32> {
+        let sym = &self.elf.symbols[r.sym];
+        if sym.shndx as usize != self.sec.index {
+            return None;
+        }
+        let thumb = sym.isa() == Some(Isa::Thumb);
+        Some(self.elf.symbol_addr(sym) | thumb as u32)
+    }
+
+    /// Apply the branch relocations which can be resolved to a copy of the
+    /// section contents.
+    pub fn apply(&self, buf: &mut [u8]) {
+        let order = self.elf.byte_order();
+        for r in self.relocs.iter() {
+            let s = match self.resolve(r) {
+                Some(s) => s,
+                None => continue,
+            };
+            let off = r.offset.wrapping_sub(self.sec.addr) as usize;
+            let p = r.offset;
+            match r.kind {
+                R_ARM_PC24 | R_ARM_CALL | R_ARM_JUMP24 => {
+                    let x = match read_u32(buf, off, order) {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    if let Some(x) = arm_branch(x, s, p, r.kind, r.addend) {
+                        write_u32(buf, off, order, x);
+                    }
+                },
+                R_ARM_THM_CALL => {
+                    let (hi, lo) = match (read_u16(buf, off, order),
+                        read_u16(buf, off + 2, order))
+                    {
+                        (Some(hi), Some(lo)) => (hi, lo),
+                        _ => continue,
+                    };
+                    if let Some((hi, lo)) = thumb_call(hi, lo, s, p, r.addend) {
+                        write_u16(buf, off, order, hi);
+                        write_u16(buf, off + 2, order, lo);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Add a comment with the relocation type and symbol to each relocated
+    /// line.
+    pub fn annotate(&self, lines: &mut [Line]) {
+        let mut relocs = self.relocs.iter().peekable();
+        for line in lines.iter_mut() {
+            let end = line.addr as u64 + line.bytes.len() as u64;
+            while let Some(r) = relocs.peek() {
+                if r.offset as u64 >= end {
+                    break;
+                }
+                if r.offset >= line.addr {
+                    line.text.push_str(&format!("\t@ {} {}",
+                        reloc_name(r.kind), self.target_name(r)));
+                }
+                relocs.next();
+            }
+        }
+    }
+
+    /// Disassemble the section with relocations applied, and return the
+    /// annotated listing.
+    pub fn disassemble(&self, d: &mut Disassembler) -> Vec<Line> {
+        let mut buf = self.elf.data(self.sec).to_vec();
+        self.apply(&mut buf);
+        let mut lines = self.elf.sweep_over(self.sec, &buf).disassemble(d);
+        self.annotate(&mut lines);
+        lines
+    }
+}
+
+
+/// Resolve an ARM branch to `s` at `p`, returning the new instruction.
+///
+/// R_ARM_CALL may change BL into BLX (or back) depending on the target's
+/// instruction set. Other branches can't switch to Thumb without a veneer.
+fn arm_branch(x: u32, s: u32, p: u32, kind: u32, addend: Option<i32>)
+    -> Option<u32>
+{
+    let blx = get_cond!(x) == 0b1111;
+    let a = addend.unwrap_or_else(|| {
+        let h = if blx { (x >> 23) & 0b10 } else { 0 };
+        (sign_extend(get_imm24!(x) as i32, 24) << 2) | h as i32
+    });
+    let thumb = (s & 1) != 0;
+    if thumb && kind != R_ARM_CALL {
+        return None;
+    }
+    let off = (s & !1).wrapping_add(a as u32).wrapping_sub(p) as i32;
+    if !(-(1 << 25)..(1 << 25)).contains(&off) {
+        return None;
+    }
+    let imm24 = ((off >> 2) as u32) & 0x00ff_ffff;
+    Some(match (thumb, blx) {
+        (true, _) => 0xfa00_0000 | (((off as u32) & 0b10) << 23) | imm24,
+        (false, true) => 0xeb00_0000 | imm24,
+        (false, false) => (x & 0xff00_0000) | imm24,
+    })
+}
+
+/// Resolve a Thumb BL/BLX pair to `s` at `p`, returning the new halfwords.
+fn thumb_call(hi: u16, lo: u16, s: u32, p: u32, addend: Option<i32>)
+    -> Option<(u16, u16)>
+{
+    let a = addend.unwrap_or_else(|| sign_extend(
+        (((hi & 0x7ff) as i32) << 12) | (((lo & 0x7ff) as i32) << 1), 23));
+    let thumb = (s & 1) != 0;
+    // BLX targets are relative to the word-aligned PC
+    let p = if thumb { p } else { p & !3 };
+    let off = (s & !1).wrapping_add(a as u32).wrapping_sub(p) as i32;
+    if !(-(1 << 22)..(1 << 22)).contains(&off) {
+        return None;
+    }
+    let hi = 0xf000 | ((off >> 12) as u16 & 0x7ff);
+    let lo = if thumb {
+        0xf800 | ((off >> 1) as u16 & 0x7ff)
+    } else {
+        0xe800 | ((off >> 1) as u16 & 0x7fe)
+    };
+    Some((hi, lo))
+}
+
+fn read_u32(buf: &[u8], off: usize, order: ByteOrder) -> Option<u32> {
+    let (_, x) = ArmStream::new(buf.get(off..)?, 0, order).next()?.ok()?;
+    Some(x)
+}
+fn read_u16(buf: &[u8], off: usize, order: ByteOrder) -> Option<u16> {
+    let (_, x) = ThumbStream::new(buf.get(off..)?, 0, order).next()?.ok()?;
+    Some(x)
+}
+fn write_u32(buf: &mut [u8], off: usize, order: ByteOrder, x: u32) {
+    let b = if order.code_is_be() { x.to_be_bytes() } else { x.to_le_bytes() };
+    buf[off..off + 4].copy_from_slice(&b);
+}
+fn write_u16(buf: &mut [u8], off: usize, order: ByteOrder, x: u16) {
+    let b = if order.code_is_be() { x.to_be_bytes() } else { x.to_le_bytes() };
+    buf[off..off + 2].copy_from_slice(&b);
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn resolve_branches() {
+        // bl -> blx to a Thumb function, and blx -> bl to an ARM function
+        assert_eq!(arm_branch(0xebfffffe, 0x29, 0x4, R_ARM_CALL, None),
+            Some(0xfa000007));
+        assert_eq!(arm_branch(0xfafffffe, 0x0, 0x8, R_ARM_CALL, None),
+            Some(0xebfffffc));
+        assert_eq!(arm_branch(0x1afffffe, 0x0, 0x1c, R_ARM_JUMP24, None),
+            Some(0x1afffff7));
+        assert_eq!(arm_branch(0xeafffffe, 0x29, 0x1c, R_ARM_JUMP24, None), None);
+        assert_eq!(thumb_call(0xf7ff, 0xfffe, 0x29, 0x2e, None),
+            Some((0xf7ff, 0xfffb)));
+        assert_eq!(thumb_call(0xf7ff, 0xfffe, 0x0, 0x2a, None),
+            Some((0xf7ff, 0xefea)));
+    }
+
+    #[test]
+    fn corpus_objects() {
+        for name in ["reloc_test.le.o", "reloc_test.be.o"].iter() {
+            let buf = std::fs::read(format!("{}/testsuite/{}",
+                env!("CARGO_MANIFEST_DIR"), name)).unwrap();
+            let elf = Elf::parse(&buf).unwrap();
+            let text = elf.section(".text").unwrap();
+            let relocs = Relocations::new(&elf, text).unwrap();
+            assert_eq!(relocs.relocs.len(), 9);
+
+            let mut d = Disassembler::new();
+            let lines = relocs.disassemble(&mut d);
+            let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+            assert_eq!(texts, vec![
+                "push\t{r4, lr}",
+                "blx\t0x28\t@ R_ARM_CALL thumb_fn",
+                "bl\t0x0\t@ R_ARM_CALL arm_fn",
+                "bl\t0xc\t@ R_ARM_CALL external",
+                "ldr\tr0, [pc, #8]",
+                "ldr\tr1, [pc, #8]",
+                "pop\t{r4, pc}",
+                "b\t0x0\t@ R_ARM_JUMP24 arm_fn",
+                ".word\t0x00000000\t@ R_ARM_ABS32 .data",
+                ".word\t0x00000000\t@ R_ARM_ABS32 external",
+                "push\t{r4, lr}",
+                "blx\t0x0\t@ R_ARM_THM_CALL arm_fn",
+                "bl\t0x28\t@ R_ARM_THM_CALL thumb_fn",
+                "bl\t0x32\t@ R_ARM_THM_CALL external",
+                "pop\t{r4, pc}",
+            ]);
+        }
+    }
+}
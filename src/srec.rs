@@ -0,0 +1,106 @@
+//! Motorola S-record files.
+//!
+//! Each record is a line `S<type><count><address><data><checksum>`, where
+//! the count covers the address, data and checksum bytes, and the checksum
+//! is the one's complement of the sum of all other bytes. Data records use
+//! 16 (`S1`), 24 (`S2`) or 32-bit (`S3`) addresses, and the file ends with
+//! a record containing the entry point (`S9`, `S8` or `S7` respectively).
+
+use crate::image::*;
+
+/// Parse a single record, returning `(type, address, data)`.
+fn record(line: &str) -> Result<(u8, u32, Vec<u8>), RecordError> {
+    let rest = line.strip_prefix('S').ok_or(RecordError::BadStart)?;
+    let kind = rest.chars().next().and_then(|c| c.to_digit(10))
+        .ok_or(RecordError::BadHex)? as u8;
+    let bytes = hex_bytes(&rest[1..])?;
+    let addr_len = match kind {
+        0 | 1 | 5 | 9 => 2,
+        2 | 6 | 8 => 3,
+        3 | 7 => 4,
+        t => return Err(RecordError::UnknownType(t)),
+    };
+    if bytes.len() < addr_len + 2 || bytes.len() != bytes[0] as usize + 1 {
+        return Err(RecordError::BadLength);
+    }
+    let (body, cs) = bytes.split_at(bytes.len() - 1);
+    let expected = !body.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+    if expected != cs[0] {
+        return Err(RecordError::Checksum { expected, found: cs[0] });
+    }
+    let addr = body[1..=addr_len].iter().fold(0u32, |acc, b| (acc << 8) | *b as u32);
+    Ok((kind, addr, body[addr_len + 1..].to_vec()))
+}
+
+/// Parse an S-record file.
+///
+/// The `S0` header is ignored. A terminating record is optional, since
+/// some tools leave it out when there's no entry point.
+pub fn parse(text: &str) -> Result<Image, ParseError> {
+    let mut img = Image::new();
+    let mut count = 0u32;
+    let lines = text.lines().enumerate()
+        .map(|(i, l)| (i + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty());
+
+    for (line, s) in lines {
+        let err = |kind| ParseError { line, kind };
+        let (kind, addr, data) = record(s).map_err(err)?;
+        match kind {
+            0 => {},
+            1 | 2 | 3 => {
+                img.write(addr, &data)
+                    .map_err(|a| err(RecordError::Overlap(a)))?;
+                count += 1;
+            },
+            5 | 6 => {
+                if addr != count {
+                    return Err(err(RecordError::BadCount));
+                }
+            },
+            _ => {
+                img.entry = Some(addr);
+                return Ok(img);
+            },
+        }
+    }
+    Ok(img)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn address_sizes() {
+        let img = parse("\
+            S00600004844521B\n\
+            S1070100E12FFF1ECA\n\
+            S206020000704740\n\
+            S30608000000AA47\n\
+            S5030003F9\n\
+            S70508000000F2\n").unwrap();
+        assert_eq!(img.chunks().collect::<Vec<(u32, &[u8])>>(), vec![
+            (0x0100, &[0xe1, 0x2f, 0xff, 0x1e][..]),
+            (0x0002_0000, &[0x70, 0x47][..]),
+            (0x0800_0000, &[0xaa][..]),
+        ]);
+        assert_eq!(img.entry, Some(0x0800_0000));
+        assert_eq!(Image::parse("S1040000AA51\n").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s| parse(s).unwrap_err();
+        assert_eq!(err("S1040000AA50"), ParseError {
+            line: 1, kind: RecordError::Checksum { expected: 0x51, found: 0x50 },
+        });
+        assert_eq!(err("S4030000FC").kind, RecordError::UnknownType(4));
+        assert_eq!(err("S1050000AA51").kind, RecordError::BadLength);
+        assert_eq!(err("S1040000AA51\nS5030002FA").kind, RecordError::BadCount);
+        assert_eq!(err("S1040000AA51\nS1040000AA51").kind,
+            RecordError::Overlap(0));
+        assert_eq!(err("S1040000AG51").kind, RecordError::BadHex);
+    }
+}
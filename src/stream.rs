@@ -0,0 +1,156 @@
+//! Iterators over instructions in a byte buffer.
+//!
+//! Code might be stored in one of a few different layouts:
+//!
+//! - Little-endian: instructions and data are both little-endian
+//! - BE-8 (ARMv6+): data is big-endian, but instructions are little-endian
+//! - BE-32 (up to ARMv5): instructions and data are both big-endian
+//!
+//! The readers here yield instructions along with their address. Trailing
+//! bytes that don't make up a whole instruction are reported with a
+//! [Truncated] error instead of being silently dropped.
+
+use std::fmt;
+
+/// The byte order of instructions in a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Little-endian code and data.
+    Le,
+    /// Big-endian data, little-endian code.
+    Be8,
+    /// Big-endian code and data.
+    Be32,
+}
+impl ByteOrder {
+    /// Returns true if instructions are stored big-endian.
+    pub fn code_is_be(&self) -> bool {
+        matches!(self, ByteOrder::Be32)
+    }
+}
+
+/// Error returned when a buffer ends in the middle of an instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncated {
+    /// Address of the incomplete instruction.
+    pub addr: u32,
+    /// The remaining bytes.
+    pub bytes: Vec<u8>,
+}
+impl fmt::Display for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "truncated instruction at 0x{:08x} ({} trailing bytes)",
+            self.addr, self.bytes.len())
+    }
+}
+impl std::error::Error for Truncated {}
+
+
+/// Iterator over ARM instructions, yielding `(address, word)`.
+pub struct ArmStream<'a> {
+    buf: &'a [u8],
+    addr: u32,
+    order: ByteOrder,
+}
+impl<'a> ArmStream<'a> {
+    pub fn new(buf: &'a [u8], base: u32, order: ByteOrder) -> Self {
+        ArmStream { buf, addr: base, order }
+    }
+}
+impl<'a> Iterator for ArmStream<'a> {
+    type Item = Result<(u32, u32), Truncated>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            return None;
+        }
+        let addr = self.addr;
+        if self.buf.len() < 4 {
+            let bytes = self.buf.to_vec();
+            self.buf = &[];
+            return Some(Err(Truncated { addr, bytes }));
+        }
+        let b = [self.buf[0], self.buf[1], self.buf[2], self.buf[3]];
+        let x = if self.order.code_is_be() {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        };
+        self.buf = &self.buf[4..];
+        self.addr = self.addr.wrapping_add(4);
+        Some(Ok((addr, x)))
+    }
+}
+
+
+/// Iterator over Thumb instructions, yielding `(address, halfword)`.
+///
+/// BL/BLX are yielded as two separate halfwords.
+pub struct ThumbStream<'a> {
+    buf: &'a [u8],
+    addr: u32,
+    order: ByteOrder,
+}
+impl<'a> ThumbStream<'a> {
+    pub fn new(buf: &'a [u8], base: u32, order: ByteOrder) -> Self {
+        ThumbStream { buf, addr: base, order }
+    }
+}
+impl<'a> Iterator for ThumbStream<'a> {
+    type Item = Result<(u32, u16), Truncated>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            return None;
+        }
+        let addr = self.addr;
+        if self.buf.len() < 2 {
+            let bytes = self.buf.to_vec();
+            self.buf = &[];
+            return Some(Err(Truncated { addr, bytes }));
+        }
+        let b = [self.buf[0], self.buf[1]];
+        let x = if self.order.code_is_be() {
+            u16::from_be_bytes(b)
+        } else {
+            u16::from_le_bytes(b)
+        };
+        self.buf = &self.buf[2..];
+        self.addr = self.addr.wrapping_add(2);
+        Some(Ok((addr, x)))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BUF: [u8; 6] = [0xe3, 0xa0, 0x00, 0x01, 0xaa, 0xbb];
+
+    #[test]
+    fn arm_byte_orders() {
+        let be: Vec<_> = ArmStream::new(&BUF, 0x1000, ByteOrder::Be32)
+            .collect();
+        assert_eq!(be[0], Ok((0x1000, 0xe3a0_0001)));
+        assert_eq!(be[1], Err(Truncated { addr: 0x1004, bytes: vec![0xaa, 0xbb] }));
+        assert_eq!(be.len(), 2);
+
+        for order in [ByteOrder::Le, ByteOrder::Be8].iter() {
+            let mut s = ArmStream::new(&BUF[..4], 0, *order);
+            assert_eq!(s.next(), Some(Ok((0, 0x0100_a0e3))));
+            assert_eq!(s.next(), None);
+        }
+    }
+
+    #[test]
+    fn thumb_byte_orders() {
+        let be: Vec<_> = ThumbStream::new(&BUF[..5], 0x8000, ByteOrder::Be32)
+            .collect();
+        assert_eq!(be, vec![
+            Ok((0x8000, 0xe3a0)), Ok((0x8002, 0x0001)),
+            Err(Truncated { addr: 0x8004, bytes: vec![0xaa] }),
+        ]);
+        let le: Vec<_> = ThumbStream::new(&BUF, 0, ByteOrder::Be8)
+            .map(Result::unwrap).collect();
+        assert_eq!(le, vec![(0, 0xa0e3), (2, 0x0100), (4, 0xbbaa)]);
+    }
+}
@@ -0,0 +1,323 @@
+//! Linear-sweep disassembly over a buffer with mixed ARM and Thumb code.
+//!
+//! The sweep starts in some mode at the base address and decodes every
+//! instruction in order. The mode changes when the sweep reaches an address
+//! with a hint attached to it. In order of precedence, hints come from:
+//!
+//! - Ranges provided by the user
+//! - ELF mapping symbols (`$a`, `$t`, `$d`)
+//! - Interworking branch targets (where bit 0 selects Thumb), including
+//!   the targets of BLX instructions found during the sweep
+//!
+//! Branch targets are only used when no mapping symbols are available.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::disas::{Disassembler, Isa};
+use crate::flow::*;
+use crate::memory::{MemoryImage, Unmapped};
+use crate::stream::*;
+
+/// The contents of some region of memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Code(Isa),
+    Data,
+}
+
+/// A single line in a listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub addr: u32,
+    /// Raw bytes, in the order they appear in memory.
+    pub bytes: Vec<u8>,
+    pub region: Region,
+    pub text: String,
+}
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes: Vec<String> = self.bytes.iter()
+            .map(|b| format!("{:02x}", b)).collect();
+        write!(f, "{:8x}: {:12}\t{}", self.addr, bytes.join(" "), self.text)
+    }
+}
+
+/// A linear-sweep disassembler.
+pub struct LinearSweep<'a> {
+    buf: &'a [u8],
+    base: u32,
+    order: ByteOrder,
+    start: Isa,
+    /// User-provided ranges `(start, end, region)`, end-exclusive.
+    ranges: Vec<(u32, u32, Region)>,
+    /// Mapping symbols.
+    symbols: BTreeMap<u32, Region>,
+    /// Interworking branch targets.
+    targets: BTreeMap<u32, Isa>,
+}
+
+impl<'a> LinearSweep<'a> {
+    pub fn new(buf: &'a [u8], base: u32, order: ByteOrder, start: Isa) -> Self {
+        LinearSweep {
+            buf, base, order, start,
+            ranges: Vec::new(),
+            symbols: BTreeMap::new(),
+            targets: BTreeMap::new(),
+        }
+    }
+
+    /// Create a sweep over the segment of a memory image containing some
+    /// address, starting in the segment's default mode.
+    pub fn from_memory(mem: &'a MemoryImage, addr: u32) -> Result<Self, Unmapped> {
+        let (seg, buf) = mem.segment_contents(addr)?;
+        Ok(LinearSweep::new(buf, seg.base, seg.order, seg.mode))
+    }
+
+    /// Force the contents of the range `[start, end)`. Ranges added later
+    /// take precedence over earlier ones.
+    pub fn add_range(&mut self, start: u32, end: u32, region: Region) {
+        self.ranges.push((start, end, region));
+    }
+
+    /// Remove the latest range added with [add_range](Self::add_range) for
+    /// `[start, end)`. Returns false if there's no such range.
+    pub fn remove_range(&mut self, start: u32, end: u32, region: Region) -> bool {
+        match self.ranges.iter().rposition(|r| *r == (start, end, region)) {
+            Some(idx) => {
+                self.ranges.remove(idx);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Add an ELF mapping symbol. Returns false if the name isn't a mapping
+    /// symbol.
+    pub fn add_mapping_symbol(&mut self, name: &str, addr: u32) -> bool {
+        let region = match name.split('.').next().unwrap() {
+            "$a" => Region::Code(Isa::Arm),
+            "$t" => Region::Code(Isa::Thumb),
+            "$d" => Region::Data,
+            _ => return false,
+        };
+        self.symbols.insert(addr, region);
+        true
+    }
+
+    /// Add an interworking branch target (i.e. the operand of a BX), where
+    /// bit 0 is set for Thumb code.
+    pub fn add_target(&mut self, target: u32) {
+        let isa = if (target & 1) != 0 { Isa::Thumb } else { Isa::Arm };
+        self.targets.insert(target & !1, isa);
+    }
+
+    /// Returns the region forced by a user range at some address.
+    fn range_at(&self, addr: u32) -> Option<Region> {
+        self.ranges.iter().rev()
+            .find(|(start, end, _)| (*start..*end).contains(&addr))
+            .map(|(_, _, region)| *region)
+    }
+
+    /// Returns the next address after `addr` where the mode might change,
+    /// or [None] if `addr` is the last address.
+    fn next_boundary(&self, addr: u32, targets: &BTreeMap<u32, Isa>) -> Option<u64> {
+        let end = self.base as u64 + self.buf.len() as u64;
+        let after = |x: u32| if x > addr { Some(x as u64) } else { None };
+        let ranges = self.ranges.iter()
+            .flat_map(|(start, end, _)| vec![after(*start), after(*end)]);
+        let next = addr.checked_add(1)?;
+        let symbols = self.symbols.range(next..).next()
+            .map(|(a, _)| *a as u64);
+        let targets = targets.range(next..).next()
+            .map(|(a, _)| *a as u64);
+        Some(ranges.chain(vec![symbols, targets]).flatten()
+            .fold(end, u64::min))
+    }
+
+    /// Format `len` bytes at some offset as data.
+    fn data_line(&self, off: usize, len: usize) -> Line {
+        let addr = self.base.wrapping_add(off as u32);
+        let bytes = self.buf[off..off + len].to_vec();
+        let val = if self.order == ByteOrder::Le {
+            bytes.iter().rev().fold(0u32, |acc, b| (acc << 8) | *b as u32)
+        } else {
+            bytes.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32)
+        };
+        let text = match len {
+            4 => format!(".word\t0x{:08x}", val),
+            2 => format!(".short\t0x{:04x}", val),
+            _ => format!(".byte\t0x{:02x}", val),
+        };
+        Line { addr, bytes, region: Region::Data, text }
+    }
+
+    /// Disassemble the whole buffer.
+    pub fn disassemble(&self, d: &mut Disassembler) -> Vec<Line> {
+        let mut res = Vec::new();
+        let mut targets = self.targets.clone();
+        let mut cur = Region::Code(self.start);
+        let mut off = 0;
+
+        while off < self.buf.len() {
+            let addr = self.base.wrapping_add(off as u32);
+            let region = match self.range_at(addr) {
+                Some(region) => region,
+                None => {
+                    if let Some(region) = self.symbols.get(&addr) {
+                        cur = *region;
+                    } else if self.symbols.is_empty() {
+                        if let Some(isa) = targets.get(&addr) {
+                            cur = Region::Code(*isa);
+                        }
+                    }
+                    cur
+                },
+            };
+
+            // Number of bytes until the next possible mode change
+            let avail = match self.next_boundary(addr, &targets) {
+                Some(next) => ((next - addr as u64) as usize).min(self.buf.len() - off),
+                None => 1,
+            };
+
+            let line = match region {
+                Region::Code(Isa::Arm) if addr & 3 == 0 && avail >= 4 => {
+                    let (_, x) = ArmStream::new(&self.buf[off..], addr,
+                        self.order).next().unwrap().unwrap();
+                    if let Flow::Call { target, isa: Isa::Thumb, .. } = arm_flow(addr, x) {
+                        targets.entry(target).or_insert(Isa::Thumb);
+                    }
+                    Line {
+                        addr, bytes: self.buf[off..off + 4].to_vec(),
+                        region, text: d.arm(addr, x),
+                    }
+                },
+                Region::Code(Isa::Thumb) if addr & 1 == 0 && avail >= 2 => {
+                    let mut s = ThumbStream::new(&self.buf[off..avail.min(4) + off],
+                        addr, self.order);
+                    let (_, hi) = s.next().unwrap().unwrap();
+                    let lo = match s.next() {
+                        Some(Ok((_, lo))) => lo,
+                        _ => 0,
+                    };
+                    match d.thumb_bl(addr, hi, lo) {
+                        Some(text) => {
+                            if let (Flow::Call { target, isa: Isa::Arm, .. }, _) =
+                                thumb_flow(addr, hi, Some(lo))
+                            {
+                                targets.entry(target).or_insert(Isa::Arm);
+                            }
+                            Line {
+                                addr, bytes: self.buf[off..off + 4].to_vec(),
+                                region, text,
+                            }
+                        },
+                        None => Line {
+                            addr, bytes: self.buf[off..off + 2].to_vec(),
+                            region, text: d.thumb(addr, hi),
+                        },
+                    }
+                },
+                _ => {
+                    let len = if addr & 3 == 0 && avail >= 4 { 4 }
+                        else if addr & 1 == 0 && avail >= 2 { 2 }
+                        else { 1 };
+                    self.data_line(off, len)
+                },
+            };
+            off += line.bytes.len();
+            res.push(line);
+        }
+        res
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// An ARM function calling a Thumb function, followed by a literal.
+    const CODE_BE32: [u8; 16] = [
+        0xfa, 0x00, 0x00, 0x00, // blx  0x8
+        0xe1, 0x2f, 0xff, 0x1e, // bx   lr
+        0x20, 0x01,             // movs r0, #1
+        0x47, 0x70,             // bx   lr
+        0xde, 0xad, 0xbe, 0xef, // .word 0xdeadbeef
+    ];
+
+    fn texts(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(|l| l.text.replace('\t', " ")).collect()
+    }
+
+    #[test]
+    fn follows_blx() {
+        let mut d = Disassembler::new();
+        let mut s = LinearSweep::new(&CODE_BE32, 0, ByteOrder::Be32, Isa::Arm);
+        s.add_range(0xc, 0x10, Region::Data);
+        let lines = s.disassemble(&mut d);
+        assert_eq!(texts(&lines), vec![
+            "blx 0x8", "bx lr", "movs r0, #1", "bx lr", ".word 0xdeadbeef",
+        ]);
+        assert_eq!(lines[2].region, Region::Code(Isa::Thumb));
+        assert_eq!(lines[2].bytes, vec![0x20, 0x01]);
+        assert_eq!(lines[4].addr, 0xc);
+    }
+
+    #[test]
+    fn mapping_symbols() {
+        // Little-endian version of the same code, and a bogus target which
+        // should be ignored when mapping symbols are present.
+        let le: Vec<u8> = CODE_BE32[..8].chunks(4)
+            .flat_map(|w| w.iter().rev().cloned().collect::<Vec<u8>>())
+            .chain(CODE_BE32[8..12].chunks(2)
+                .flat_map(|h| vec![h[1], h[0]]))
+            .chain(CODE_BE32[12..].iter().rev().cloned())
+            .collect();
+        let mut d = Disassembler::new();
+        let mut s = LinearSweep::new(&le, 0x1000, ByteOrder::Le, Isa::Thumb);
+        assert!(s.add_mapping_symbol("$a", 0x1000));
+        assert!(s.add_mapping_symbol("$t.1", 0x1008));
+        assert!(s.add_mapping_symbol("$d", 0x100c));
+        assert!(!s.add_mapping_symbol("main", 0x1000));
+        s.add_target(0x1004 | 1);
+        let lines = s.disassemble(&mut d);
+        assert_eq!(texts(&lines), vec![
+            "blx 0x1008", "bx lr", "movs r0, #1", "bx lr", ".word 0xdeadbeef",
+        ]);
+        assert_eq!(format!("{}", lines[0]), "    1000: 00 00 00 fa \tblx\t0x1008");
+    }
+
+    #[test]
+    fn misaligned_and_truncated() {
+        let mut d = Disassembler::new();
+        let mut s = LinearSweep::new(&CODE_BE32[..11], 0, ByteOrder::Be32,
+            Isa::Arm);
+        s.add_range(0x4, 0x6, Region::Data);
+        let lines = s.disassemble(&mut d);
+        assert_eq!(texts(&lines), vec![
+            "blx 0x8", ".short 0xe12f", ".short 0xff1e", "movs r0, #1",
+            ".byte 0x47",
+        ]);
+    }
+
+    #[test]
+    fn top_of_memory() {
+        let mut d = Disassembler::new();
+        let lines = LinearSweep::new(&[0; 16], 0xffff_fff0, ByteOrder::Le, Isa::Arm)
+            .disassemble(&mut d);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3].addr, 0xffff_fffc);
+
+        let mut s = LinearSweep::new(&CODE_BE32, 0xffff_fff0, ByteOrder::Be32,
+            Isa::Arm);
+        s.add_range(0xffff_fffc, 0xffff_ffff, Region::Data);
+        let lines = s.disassemble(&mut d);
+        assert_eq!(texts(&lines), vec![
+            "blx 0xfffffff8", "bx lr", "movs r0, #1", "bx lr", ".short 0xdead",
+            ".byte 0xbe", ".byte 0xef",
+        ]);
+        assert_eq!(lines[6].addr, 0xffff_ffff);
+    }
+}
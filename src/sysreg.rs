@@ -0,0 +1,311 @@
+//! Names for system control (CP15) and debug (CP14) coprocessor registers.
+//!
+//! The meaning of an `mcr`/`mrc` depends on the core, so registers are
+//! looked up for a specific [Core] in a table keyed by the coprocessor
+//! number and the `opcd1`, `crn`, `crm` and `opcd2` fields. The
+//! [crate::disas::Disassembler] uses these names as comments when built
+//! with [crate::disas::Disassembler::with_sysregs].
+
+use std::fmt;
+
+use crate::traits::*;
+
+/// A core with a known set of system registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Core {
+    /// ARM926EJ-S (ARMv5TEJ, with an MMU).
+    Arm926ejs,
+    /// ARM946E-S (ARMv5TE, with an MPU).
+    Arm946es,
+    /// ARM1136J(F)-S (ARMv6).
+    Arm1136,
+}
+impl Core {
+    fn mask(self) -> u8 {
+        match self {
+            Core::Arm926ejs => C926,
+            Core::Arm946es => C946,
+            Core::Arm1136 => C1136,
+        }
+    }
+}
+impl fmt::Display for Core {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Core::Arm926ejs => "ARM926EJ-S",
+            Core::Arm946es => "ARM946E-S",
+            Core::Arm1136 => "ARM1136",
+        })
+    }
+}
+
+/// The direction of a register transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dir {
+    /// `mrc`, from the coprocessor.
+    Read,
+    /// `mcr`, to the coprocessor.
+    Write,
+}
+
+/// A coprocessor register, as named by the operands of `mcr`/`mrc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SysReg {
+    pub cp: u32,
+    pub op1: u32,
+    pub crn: u32,
+    pub crm: u32,
+    pub op2: u32,
+}
+impl SysReg {
+    pub const fn new(cp: u32, op1: u32, crn: u32, crm: u32, op2: u32) -> Self {
+        SysReg { cp, op1, crn, crm, op2 }
+    }
+
+    /// The register accessed by an `mcr`/`mrc` instruction.
+    pub fn from_inst<T: CoprocBits>(op: &T) -> Self {
+        SysReg::new(op.cp_num(), op.opcd1_rt(), op.crn(), op.crm(), op.opcd2())
+    }
+
+    /// Look up the name of this register on some core.
+    pub fn name(&self, core: Core, dir: Dir) -> Option<&'static str> {
+        lookup(core, *self, dir)
+    }
+}
+impl fmt::Display for SysReg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "p{}, {}, c{}, c{}, {}", self.cp, self.op1, self.crn, self.crm, self.op2)
+    }
+}
+
+const C926: u8 = 1 << 0;
+const C946: u8 = 1 << 1;
+const C1136: u8 = 1 << 2;
+const V5: u8 = C926 | C946;
+const ALL: u8 = C926 | C946 | C1136;
+
+/// Matches any value of `crm` or `opcd2`.
+const ANY: u8 = 0xff;
+
+/// Matches either direction.
+const RW: Option<Dir> = None;
+const R: Option<Dir> = Some(Dir::Read);
+const W: Option<Dir> = Some(Dir::Write);
+
+struct Entry {
+    cores: u8,
+    cp: u8,
+    op1: u8,
+    crn: u8,
+    crm: u8,
+    op2: u8,
+    dir: Option<Dir>,
+    name: &'static str,
+}
+
+macro_rules! table {
+    ($($cores:expr, $cp:literal, $op1:literal, $crn:literal, $crm:expr, $op2:expr,
+       $dir:expr, $name:literal;)*) =>
+    {
+        &[$(Entry {
+            cores: $cores, cp: $cp, op1: $op1, crn: $crn, crm: $crm, op2: $op2,
+            dir: $dir, name: $name,
+        }),*]
+    }
+}
+
+/// The catalogue, with columns for the cores, cp_num, opcd1, crn, crm, opcd2
+/// and direction. Entries are matched in order, so more specific entries
+/// come first.
+static TABLE: &[Entry] = table! {
+    // c0: identification
+    ALL,   15, 0, 0, 0, 0, R, "Main ID";
+    ALL,   15, 0, 0, 0, 1, R, "Cache Type";
+    C926,  15, 0, 0, 0, 2, R, "TCM Status";
+    C946,  15, 0, 0, 0, 2, R, "Tightly-Coupled Memory Size";
+    C1136, 15, 0, 0, 0, 2, R, "TCM Status";
+    C1136, 15, 0, 0, 0, 3, R, "TLB Type";
+    // Unimplemented ID registers read as the main ID
+    C926,  15, 0, 0, 0, ANY, R, "Main ID";
+    C1136, 15, 0, 0, 1, 0, R, "Processor Feature 0";
+    C1136, 15, 0, 0, 1, 1, R, "Processor Feature 1";
+    C1136, 15, 0, 0, 1, 2, R, "Debug Feature 0";
+    C1136, 15, 0, 0, 1, 3, R, "Auxiliary Feature 0";
+    C1136, 15, 0, 0, 1, 4, R, "Memory Model Feature 0";
+    C1136, 15, 0, 0, 1, 5, R, "Memory Model Feature 1";
+    C1136, 15, 0, 0, 1, 6, R, "Memory Model Feature 2";
+    C1136, 15, 0, 0, 1, 7, R, "Memory Model Feature 3";
+    C1136, 15, 0, 0, 2, 0, R, "Instruction Set Attribute 0";
+    C1136, 15, 0, 0, 2, 1, R, "Instruction Set Attribute 1";
+    C1136, 15, 0, 0, 2, 2, R, "Instruction Set Attribute 2";
+    C1136, 15, 0, 0, 2, 3, R, "Instruction Set Attribute 3";
+    C1136, 15, 0, 0, 2, 4, R, "Instruction Set Attribute 4";
+    C1136, 15, 0, 0, 2, 5, R, "Instruction Set Attribute 5";
+
+    // c1: system configuration
+    ALL,   15, 0, 1, 0, 0, RW, "Control";
+    C1136, 15, 0, 1, 0, 1, RW, "Auxiliary Control";
+    C1136, 15, 0, 1, 0, 2, RW, "Coprocessor Access Control";
+
+    // c2, c3: translation tables (or protection unit cacheability)
+    C926,  15, 0, 2, 0, 0, RW, "Translation Table Base";
+    C1136, 15, 0, 2, 0, 0, RW, "Translation Table Base 0";
+    C1136, 15, 0, 2, 0, 1, RW, "Translation Table Base 1";
+    C1136, 15, 0, 2, 0, 2, RW, "Translation Table Base Control";
+    C946,  15, 0, 2, 0, 0, RW, "Data Cacheable Bits";
+    C946,  15, 0, 2, 0, 1, RW, "Instruction Cacheable Bits";
+    C926 | C1136, 15, 0, 3, 0, 0, RW, "Domain Access Control";
+    C946,  15, 0, 3, 0, 0, RW, "Data Bufferable Bits";
+
+    // c5, c6: faults (or protection unit permissions and regions)
+    C926 | C1136, 15, 0, 5, 0, 0, RW, "Data Fault Status";
+    C926 | C1136, 15, 0, 5, 0, 1, RW, "Instruction Fault Status";
+    C926 | C1136, 15, 0, 6, 0, 0, RW, "Fault Address";
+    C1136, 15, 0, 6, 0, 1, RW, "Watchpoint Fault Address";
+    C946,  15, 0, 5, 0, 0, RW, "Data Access Permission";
+    C946,  15, 0, 5, 0, 1, RW, "Instruction Access Permission";
+    C946,  15, 0, 5, 0, 2, RW, "Extended Data Access Permission";
+    C946,  15, 0, 5, 0, 3, RW, "Extended Instruction Access Permission";
+    C946,  15, 0, 6, ANY, 0, RW, "Protection Region Base and Size";
+
+    // c7: cache operations
+    ALL,   15, 0, 7, 0, 4, W, "Wait for interrupt";
+    ALL,   15, 0, 7, 5, 0, W, "Invalidate entire ICache";
+    ALL,   15, 0, 7, 5, 1, W, "Invalidate ICache line (MVA)";
+    ALL,   15, 0, 7, 5, 2, W, "Invalidate ICache line (set/way)";
+    ALL,   15, 0, 7, 5, 4, W, "Flush prefetch buffer";
+    C1136, 15, 0, 7, 5, 6, W, "Flush entire branch target cache";
+    C1136, 15, 0, 7, 5, 7, W, "Flush branch target cache entry (MVA)";
+    ALL,   15, 0, 7, 6, 0, W, "Invalidate entire DCache";
+    ALL,   15, 0, 7, 6, 1, W, "Invalidate DCache line (MVA)";
+    ALL,   15, 0, 7, 6, 2, W, "Invalidate DCache line (set/way)";
+    ALL,   15, 0, 7, 7, 0, W, "Invalidate both caches";
+    C1136, 15, 0, 7, 10, 0, W, "Clean entire DCache";
+    ALL,   15, 0, 7, 10, 1, W, "Clean DCache line (MVA)";
+    ALL,   15, 0, 7, 10, 2, W, "Clean DCache line (set/way)";
+    C926,  15, 0, 7, 10, 3, R, "Test and clean DCache";
+    V5,    15, 0, 7, 10, 4, W, "Drain write buffer";
+    C1136, 15, 0, 7, 10, 4, W, "Data Synchronization Barrier";
+    C1136, 15, 0, 7, 10, 5, W, "Data Memory Barrier";
+    C1136, 15, 0, 7, 10, 6, R, "Cache Dirty Status";
+    ALL,   15, 0, 7, 13, 1, W, "Prefetch ICache line (MVA)";
+    C1136, 15, 0, 7, 14, 0, W, "Clean and invalidate entire DCache";
+    ALL,   15, 0, 7, 14, 1, W, "Clean and invalidate DCache line (MVA)";
+    ALL,   15, 0, 7, 14, 2, W, "Clean and invalidate DCache line (set/way)";
+    C926,  15, 0, 7, 14, 3, R, "Test, clean and invalidate DCache";
+
+    // c8: TLB operations
+    C926 | C1136, 15, 0, 8, 5, 0, W, "Invalidate ITLB";
+    C926 | C1136, 15, 0, 8, 5, 1, W, "Invalidate ITLB entry (MVA)";
+    C926 | C1136, 15, 0, 8, 6, 0, W, "Invalidate DTLB";
+    C926 | C1136, 15, 0, 8, 6, 1, W, "Invalidate DTLB entry (MVA)";
+    C926 | C1136, 15, 0, 8, 7, 0, W, "Invalidate TLBs";
+    C926 | C1136, 15, 0, 8, 7, 1, W, "Invalidate TLB entry (MVA)";
+
+    // c9, c10: lockdown and TCM
+    ALL,   15, 0, 9, 0, 0, RW, "Data Cache Lockdown";
+    ALL,   15, 0, 9, 0, 1, RW, "Instruction Cache Lockdown";
+    ALL,   15, 0, 9, 1, 0, RW, "Data TCM Region";
+    ALL,   15, 0, 9, 1, 1, RW, "Instruction TCM Region";
+    C926 | C1136, 15, 0, 10, 0, 0, RW, "TLB Lockdown";
+    C1136, 15, 0, 10, 2, 0, RW, "Primary Region Remap";
+    C1136, 15, 0, 10, 2, 1, RW, "Normal Memory Remap";
+
+    // c13: process IDs
+    C926 | C1136, 15, 0, 13, 0, 0, RW, "FCSE PID";
+    C926 | C1136, 15, 0, 13, 0, 1, RW, "Context ID";
+    C946,  15, 0, 13, 0, 1, RW, "Trace Process ID";
+    C1136, 15, 0, 13, 0, 2, RW, "User Read/Write Thread ID";
+    C1136, 15, 0, 13, 0, 3, RW, "User Read-only Thread ID";
+    C1136, 15, 0, 13, 0, 4, RW, "Privileged Thread ID";
+
+    // c15: implementation defined
+    C1136, 15, 0, 15, 12, 0, RW, "Performance Monitor Control";
+    C1136, 15, 0, 15, 12, 1, RW, "Cycle Counter";
+    C1136, 15, 0, 15, 12, 2, RW, "Count 0";
+    C1136, 15, 0, 15, 12, 3, RW, "Count 1";
+    ALL,   15, 0, 15, ANY, ANY, RW, "Test and debug";
+
+    // CP14: debug
+    V5,    14, 0, 0, 0, 0, R, "Debug Comms Control";
+    V5,    14, 0, 1, 0, 0, R, "Debug Comms Data Read";
+    V5,    14, 0, 1, 0, 0, W, "Debug Comms Data Write";
+    C1136, 14, 0, 0, 0, 0, R, "Debug ID";
+    C1136, 14, 0, 0, 1, 0, RW, "Debug Status and Control";
+    C1136, 14, 0, 0, 5, 0, R, "Data Transfer Receive";
+    C1136, 14, 0, 0, 5, 0, W, "Data Transfer Transmit";
+    C1136, 14, 0, 0, 7, 0, RW, "Vector Catch";
+    C1136, 14, 0, 0, ANY, 4, RW, "Breakpoint Value";
+    C1136, 14, 0, 0, ANY, 5, RW, "Breakpoint Control";
+    C1136, 14, 0, 0, ANY, 6, RW, "Watchpoint Value";
+    C1136, 14, 0, 0, ANY, 7, RW, "Watchpoint Control";
+};
+
+fn matches(pat: u8, val: u32) -> bool {
+    pat == ANY || pat as u32 == val
+}
+
+/// Look up the name of a register (or operation) on some core.
+pub fn lookup(core: Core, reg: SysReg, dir: Dir) -> Option<&'static str> {
+    TABLE.iter().find(|e| {
+        (e.cores & core.mask()) != 0
+            && e.cp as u32 == reg.cp
+            && e.op1 as u32 == reg.op1
+            && e.crn as u32 == reg.crn
+            && matches(e.crm, reg.crm)
+            && matches(e.op2, reg.op2)
+            && e.dir.unwrap_or(dir) == dir
+    }).map(|e| e.name)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::disas::Disassembler;
+    use crate::newtype::CoprocBf;
+
+    #[test]
+    fn lookups() {
+        let name = |core, reg, dir| lookup(core, reg, dir);
+        let ttb = SysReg::new(15, 0, 2, 0, 0);
+        assert_eq!(name(Core::Arm926ejs, ttb, Dir::Write), Some("Translation Table Base"));
+        assert_eq!(name(Core::Arm946es, ttb, Dir::Write), Some("Data Cacheable Bits"));
+        assert_eq!(name(Core::Arm1136, ttb, Dir::Read), Some("Translation Table Base 0"));
+
+        let dwb = SysReg::new(15, 0, 7, 10, 4);
+        assert_eq!(name(Core::Arm926ejs, dwb, Dir::Write), Some("Drain write buffer"));
+        assert_eq!(name(Core::Arm1136, dwb, Dir::Write), Some("Data Synchronization Barrier"));
+        assert_eq!(name(Core::Arm926ejs, dwb, Dir::Read), None);
+
+        let region = SysReg::new(15, 0, 6, 3, 0);
+        assert_eq!(name(Core::Arm946es, region, Dir::Read),
+            Some("Protection Region Base and Size"));
+        assert_eq!(name(Core::Arm926ejs, region, Dir::Read), None);
+
+        let dcc = SysReg::new(14, 0, 1, 0, 0);
+        assert_eq!(name(Core::Arm926ejs, dcc, Dir::Read), Some("Debug Comms Data Read"));
+        assert_eq!(name(Core::Arm926ejs, dcc, Dir::Write), Some("Debug Comms Data Write"));
+        let bcr = SysReg::new(14, 0, 0, 3, 5);
+        assert_eq!(bcr.name(Core::Arm1136, Dir::Write), Some("Breakpoint Control"));
+
+        assert_eq!(SysReg::from_inst(&CoprocBf(0xee153f10)), SysReg::new(15, 0, 5, 0, 0));
+        assert_eq!(name(Core::Arm926ejs, SysReg::new(15, 1, 1, 0, 0), Dir::Read), None);
+    }
+
+    #[test]
+    fn comments() {
+        let mut d = Disassembler::new().with_sysregs(Core::Arm926ejs);
+        assert_eq!(d.arm(0, 0xee070f15),
+            "mcr\tp15, #0, r0, c7, c5, #0\t@ Invalidate entire ICache");
+        assert_eq!(d.arm(0, 0xee021f10),
+            "mcr\tp15, #0, r1, c2, c0, #0\t@ Translation Table Base");
+        assert_eq!(d.arm(0, 0xee153f10),
+            "mrc\tp15, #0, r3, c5, c0, #0\t@ Data Fault Status");
+        assert_eq!(d.arm(0, 0xee123f10), "mrc\tp15, #0, r3, c2, c0, #0\t@ Translation Table Base");
+        // Unknown registers and the unconditional forms aren't annotated.
+        assert_eq!(d.arm(0, 0xee143f10), "mrc\tp15, #0, r3, c4, c0, #0");
+        assert_eq!(d.arm(0, 0xfe153f10), "mrc2\tp15, #0, r3, c5, c0, #0");
+        assert_eq!(Disassembler::new().arm(0, 0xee153f10), "mrc\tp15, #0, r3, c5, c0, #0");
+    }
+}
@@ -82,11 +82,39 @@ pub trait CoprocBits {
     fn opcd1_rt(&self) -> u32;
     fn cp_num(&self) -> u32;
     fn opcd2(&self) -> u32;
+    fn opcd_rr(&self) -> u32;
+    fn option(&self) -> u32;
+    fn n(&self) -> bool;
     fn crn(&self) -> u32;
     fn crd(&self) -> u32;
     fn crm(&self) -> u32;
 }
 
+/// Accessors common to VFP instructions.
+pub trait VfpBits {
+    fn double(&self) -> bool;
+    fn vfp_opcd(&self) -> u32;
+    fn ext_opcd(&self) -> u32;
+    fn sd(&self) -> u32;
+    fn sn(&self) -> u32;
+    fn sm(&self) -> u32;
+    fn dd(&self) -> u32;
+    fn dn(&self) -> u32;
+    fn dm(&self) -> u32;
+}
+
+/// Accessors common to FPA instructions.
+pub trait FpaBits {
+    fn fd(&self) -> u32;
+    fn fn_(&self) -> u32;
+    fn fm(&self) -> u32;
+    fn fm_imm(&self) -> bool;
+    fn monadic(&self) -> bool;
+    fn prec(&self) -> u32;
+    fn ls_prec(&self) -> u32;
+    fn rounding(&self) -> u32;
+}
+
 /// Accessors for common register fields.
 pub trait RegBits {
     fn rn(&self) -> u32;